@@ -0,0 +1,277 @@
+//! Decomposition of two line of sight data sets into components of the displacement.
+//!
+//! Two acquisition geometries, usually an ascending and a descending track, see the same
+//! displacement along different lines of sight. Once resampled on common positions, each
+//! point gives a 2x2 system solved for the vertical component and either the horizontal
+//! component along the section or the east component, the remaining horizontal component
+//! being assumed null.
+
+use super::vec_proj::{Vector2Rep, Vector3Rep};
+use crate::profile::interpol_linear;
+use crate::types::*;
+
+/// Components in which the displacement is decomposed
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DecompositionBasis {
+    /// Horizontal along the section and vertical
+    #[default]
+    Section,
+    /// East and up, the north component being null
+    EastUp,
+}
+
+impl DecompositionBasis {
+    /// All the available bases
+    pub const ALL: [DecompositionBasis; 2] =
+        [DecompositionBasis::Section, DecompositionBasis::EastUp];
+
+    /// Short name of the basis
+    pub fn name(&self) -> &'static str {
+        match self {
+            DecompositionBasis::Section => "Section / Vertical",
+            DecompositionBasis::EastUp => "East / Up",
+        }
+    }
+
+    /// Horizontal unit vector of the basis
+    fn horizontal(&self, section: &Orientation) -> Vector3Rep {
+        match self {
+            DecompositionBasis::Section => {
+                Vector3Rep::from_vertical_section_rad(&Vector2Rep::new(1., 0.), section.azimuth)
+            }
+            DecompositionBasis::EastUp => Vector3Rep::new(0., 1., 0.),
+        }
+    }
+}
+
+/// Displacement observed by two lines of sight, decomposed at common positions
+#[derive(Debug, Default, Clone)]
+pub struct LosDecomposition {
+    /// Components of the decomposition
+    pub basis: DecompositionBasis,
+    /// Common positions along the section
+    pub x: Vec<f32>,
+    /// Horizontal component, along the section or toward the east
+    pub horizontal: Vec<f32>,
+    /// Vertical component, positive upward
+    pub vertical: Vec<f32>,
+    /// True where the lines of sight are too close to separate the components
+    ///
+    /// The components of these points are null
+    pub ill_conditioned: Vec<bool>,
+    /// Observed displacement vectors in the section, at the DEM elevation
+    pub profile: DispProfile,
+}
+
+impl LosDecomposition {
+    /// Decompose two displacement data sets recorded along their own line of sight
+    ///
+    /// Both data sets are linearly resampled on the DEM sampling points lying where they
+    /// overlap, along with their line of sight if it varies. Points whose system has a
+    /// determinant lower than `min_determinant` in absolute value are flagged as
+    /// ill-conditioned.
+    ///
+    /// # Errors
+    /// * the data sets do not overlap on at least one DEM sampling point
+    pub fn new(
+        dem: &Dem1D,
+        section: &Orientation,
+        first: (&Orientation, &DispData),
+        second: (&Orientation, &DispData),
+        basis: DecompositionBasis,
+        min_determinant: f32,
+    ) -> Result<Self, VectorInputError> {
+        let first = ResampledData::sorted(first.0, first.1);
+        let second = ResampledData::sorted(second.0, second.1);
+        let (Some(start), Some(end)) = (
+            first
+                .x
+                .first()
+                .zip(second.x.first())
+                .map(|(a, b)| a.max(*b)),
+            first.x.last().zip(second.x.last()).map(|(a, b)| a.min(*b)),
+        ) else {
+            return Err(VectorInputError::EmptyVecs);
+        };
+        let x: Vec<f32> = dem
+            .x
+            .iter()
+            .copied()
+            .filter(|x| *x >= start && *x <= end)
+            .collect();
+        if x.is_empty() {
+            return Err(VectorInputError::EmptyVecs);
+        }
+        let first = first.resample(&x);
+        let second = second.resample(&x);
+
+        let horizontal = basis.horizontal(section);
+        let up = Vector3Rep::new(0., 0., 1.);
+        // Horizontal component of the basis seen in the section
+        let section_horizontal =
+            horizontal.inner_product(&DecompositionBasis::Section.horizontal(section));
+
+        let mut decomposition = LosDecomposition {
+            basis,
+            x: x.clone(),
+            horizontal: Vec::with_capacity(x.len()),
+            vertical: Vec::with_capacity(x.len()),
+            ill_conditioned: Vec::with_capacity(x.len()),
+            profile: DispProfile::default(),
+        };
+        let mut vecs = Vec::with_capacity(x.len());
+        for k in 0..x.len() {
+            // Sensitivity of each line of sight to the components of the basis
+            let (a1, b1) = (
+                horizontal.inner_product(&first.los[k]),
+                up.inner_product(&first.los[k]),
+            );
+            let (a2, b2) = (
+                horizontal.inner_product(&second.los[k]),
+                up.inner_product(&second.los[k]),
+            );
+            let (d1, d2) = (first.amplitude[k], second.amplitude[k]);
+            let determinant = a1 * b2 - a2 * b1;
+            let is_ill_conditioned = determinant.abs() < min_determinant;
+            let (h, v) = if is_ill_conditioned {
+                (0., 0.)
+            } else {
+                (
+                    (d1 * b2 - d2 * b1) / determinant,
+                    (a1 * d2 - a2 * d1) / determinant,
+                )
+            };
+            decomposition.horizontal.push(h);
+            decomposition.vertical.push(v);
+            decomposition.ill_conditioned.push(is_ill_conditioned);
+            vecs.push(Vector2Rep::new(h * section_horizontal, v));
+        }
+        let z = dem.interpolate_elevation_on_x(&x);
+        let origins = (0..x.len()).map(|k| [x[k], z[k]]).collect();
+        decomposition.profile = DispProfile::new(vecs, origins)?;
+        Ok(decomposition)
+    }
+}
+
+/// Amplitudes and lines of sight of displacement data sorted along the section
+struct ResampledData {
+    x: Vec<f32>,
+    amplitude: Vec<f32>,
+    los: Vec<Vector3Rep>,
+}
+
+impl ResampledData {
+    fn sorted(orientation: &Orientation, data: &DispData) -> Self {
+        let los = data.los_vectors(orientation);
+        let mut order: Vec<usize> = (0..data.x.len()).collect();
+        order.sort_by(|i, j| data.x[*i].total_cmp(&data.x[*j]));
+        ResampledData {
+            x: order.iter().map(|k| data.x[*k]).collect(),
+            amplitude: order.iter().map(|k| data.amplitude[*k]).collect(),
+            los: order.iter().map(|k| los[*k]).collect(),
+        }
+    }
+
+    /// Linear interpolation at new positions, the lines of sight being normalized again
+    fn resample(&self, x: &[f32]) -> Self {
+        let component = |c: fn(&Vector3Rep) -> f32| {
+            interpol_linear(&self.x, &self.los.iter().map(c).collect::<Vec<f32>>(), x)
+        };
+        let (north, east, up) = (
+            component(|v| v.coords().0),
+            component(|v| v.coords().1),
+            component(|v| v.coords().2),
+        );
+        ResampledData {
+            x: x.to_vec(),
+            amplitude: interpol_linear(&self.x, &self.amplitude, x),
+            los: (0..x.len())
+                .map(|k| *Vector3Rep::new(north[k], east[k], up[k]).unit())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_decomposition() {
+        let dem = Dem1D {
+            x: vec![0., 10., 20., 30., 40.],
+            surface: Surface1D::new(vec![40., 30., 20., 10., 0.]),
+        };
+        let section = Orientation::from_deg(90., 90.).unwrap();
+        let ascending = Orientation::from_deg(80., 40.).unwrap();
+        let descending = Orientation::from_deg(280., 35.).unwrap();
+
+        // Displacement of 2 along the section and -1 vertically
+        let truth =
+            Vector3Rep::from_vertical_section_rad(&Vector2Rep::new(2., -1.), section.azimuth);
+        let in_los = |los: &Orientation| {
+            let los3: Vector3Rep = los.clone().into();
+            truth.inner_product(&los3)
+        };
+        let asc_data = DispData::new(vec![25., 5., 15.], vec![in_los(&ascending); 3]).unwrap();
+        let desc_data = DispData::new(vec![8., 35.], vec![in_los(&descending); 2]).unwrap();
+
+        let decomposition = LosDecomposition::new(
+            &dem,
+            &section,
+            (&ascending, &asc_data),
+            (&descending, &desc_data),
+            DecompositionBasis::Section,
+            0.1,
+        )
+        .unwrap();
+        // Overlap of the data sets from 8 to 25
+        assert_eq!(decomposition.x, vec![10., 20.]);
+        for k in 0..decomposition.x.len() {
+            assert_approx_eq!(decomposition.horizontal[k], 2., 1e-4);
+            assert_approx_eq!(decomposition.vertical[k], -1., 1e-4);
+            let (vx, vz) = decomposition.profile.vecs[k].coords();
+            assert_approx_eq!(vx, 2., 1e-4);
+            assert_approx_eq!(vz, -1., 1e-4);
+        }
+        assert_eq!(decomposition.profile.origins[0], [10., 30.]);
+
+        // The section points to the east, so the east component is the horizontal one
+        let decomposition = LosDecomposition::new(
+            &dem,
+            &section,
+            (&ascending, &asc_data),
+            (&descending, &desc_data),
+            DecompositionBasis::EastUp,
+            0.1,
+        )
+        .unwrap();
+        assert_approx_eq!(decomposition.horizontal[0], 2., 1e-4);
+
+        // The same geometry twice cannot separate the components
+        let decomposition = LosDecomposition::new(
+            &dem,
+            &section,
+            (&ascending, &asc_data),
+            (&ascending, &asc_data),
+            DecompositionBasis::Section,
+            0.1,
+        )
+        .unwrap();
+        assert!(decomposition.ill_conditioned.iter().all(|flag| *flag));
+
+        // Data sets without overlap
+        let far_data = DispData::new(vec![30., 40.], vec![1., 1.]).unwrap();
+        let near_data = DispData::new(vec![0., 5.], vec![1., 1.]).unwrap();
+        assert!(LosDecomposition::new(
+            &dem,
+            &section,
+            (&ascending, &far_data),
+            (&descending, &near_data),
+            DecompositionBasis::Section,
+            0.1,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Dense linear least squares solvers used to calibrate the composed models.
+//!
+//! The predicted displacement in the line of sight is linear in the weights of the
+//! surfaces, so the calibration reduces to solving `A.x = b` in the least squares sense,
+//! with `A` the design matrix holding the projected unit profiles as columns.
+
+use nalgebra::{DMatrix, DVector};
+
+/// Relative tolerance used to truncate the small singular values
+const SVD_EPS: f32 = 1e-6;
+
+/// Solve the unconstrained linear least squares problem min ||A.x - b||
+///
+/// The system is solved using the singular value decomposition so that rank deficient
+/// systems still return the minimum norm solution.
+pub(crate) fn lstsq(a: &DMatrix<f32>, b: &DVector<f32>) -> Option<DVector<f32>> {
+    if a.nrows() != b.len() || a.ncols() == 0 {
+        return None;
+    }
+    let svd = a.clone().svd(true, true);
+    let max_singular = svd.singular_values.max();
+    let eps = (max_singular * SVD_EPS).max(f32::MIN_POSITIVE);
+    match svd.solve(b, eps) {
+        Ok(x) if x.iter().all(|v| v.is_finite()) => Some(x),
+        _ => None,
+    }
+}
+
+/// Solve the non-negative linear least squares problem min ||A.x - b|| with x >= 0
+///
+/// Implements the active set method of Lawson and Hanson (1974).
+pub(crate) fn nnls(a: &DMatrix<f32>, b: &DVector<f32>) -> Option<DVector<f32>> {
+    if a.nrows() != b.len() || a.ncols() == 0 {
+        return None;
+    }
+    let n = a.ncols();
+    let tol = 10. * f32::EPSILON * a.norm() * (n.max(a.nrows()) as f32);
+    let mut x = DVector::<f32>::zeros(n);
+    let mut passive = vec![false; n];
+    let max_iter = 3 * n + 30;
+
+    for _ in 0..max_iter {
+        let gradient = a.transpose() * (b - a * &x);
+        let candidate = (0..n)
+            .filter(|j| !passive[*j])
+            .max_by(|i, j| gradient[*i].total_cmp(&gradient[*j]));
+        match candidate {
+            Some(j) if gradient[j] > tol => passive[j] = true,
+            _ => return Some(x),
+        }
+
+        // Inner loop, keep the passive set feasible
+        loop {
+            let s = passive_lstsq(a, b, &passive)?;
+            if (0..n).filter(|j| passive[*j]).all(|j| s[j] > tol) {
+                x = s;
+                break;
+            }
+            let alpha = (0..n)
+                .filter(|j| passive[*j] && s[*j] <= tol)
+                .map(|j| x[j] / (x[j] - s[j]))
+                .fold(f32::INFINITY, f32::min);
+            x += (s - &x) * alpha;
+            for j in 0..n {
+                if passive[j] && x[j] <= tol {
+                    passive[j] = false;
+                    x[j] = 0.;
+                }
+            }
+        }
+    }
+    Some(x)
+}
+
+/// Solve the bounded variables least squares problem min ||A.x - b|| with l <= x <= u
+///
+/// Implements the active set method of Stark and Parker (1995), starting with the bounded
+/// variables at one of their bounds. Infinite bounds are allowed on either side.
+pub(crate) fn bvls(
+    a: &DMatrix<f32>,
+    b: &DVector<f32>,
+    lower: &DVector<f32>,
+    upper: &DVector<f32>,
+) -> Option<DVector<f32>> {
+    let n = a.ncols();
+    if a.nrows() != b.len() || n == 0 || lower.len() != n || upper.len() != n {
+        return None;
+    }
+    if (0..n).any(|j| lower[j] > upper[j]) {
+        return None;
+    }
+    let tol = 10. * f32::EPSILON * a.norm() * (n.max(a.nrows()) as f32);
+    // Bounded variables start at one of their bounds, unbounded ones are free
+    let mut x = DVector::from_fn(n, |j, _| {
+        match (lower[j].is_finite(), upper[j].is_finite()) {
+            (true, _) => lower[j],
+            (false, true) => upper[j],
+            (false, false) => 0.,
+        }
+    });
+    let mut free: Vec<bool> = (0..n)
+        .map(|j| !lower[j].is_finite() && !upper[j].is_finite())
+        .collect();
+    if free.iter().any(|f| *f) {
+        bounded_step(a, b, lower, upper, tol, &mut x, &mut free)?;
+    }
+    let max_iter = 3 * n + 30;
+
+    for _ in 0..max_iter {
+        // A bound variable can be freed if the gradient pushes it inside the box
+        let gradient = a.transpose() * (b - a * &x);
+        let candidate = (0..n)
+            .filter(|j| !free[*j] && lower[*j] < upper[*j])
+            .map(|j| {
+                let push = if x[j] <= lower[j] {
+                    gradient[j]
+                } else {
+                    -gradient[j]
+                };
+                (j, push)
+            })
+            .max_by(|(_, p1), (_, p2)| p1.total_cmp(p2));
+        match candidate {
+            Some((j, push)) if push > tol => free[j] = true,
+            _ => return Some(x),
+        }
+        bounded_step(a, b, lower, upper, tol, &mut x, &mut free)?;
+    }
+    Some(x)
+}
+
+/// Move the free variables toward their least squares solution without leaving the box
+///
+/// Free variables reaching one of their bounds are fixed at this bound.
+fn bounded_step(
+    a: &DMatrix<f32>,
+    b: &DVector<f32>,
+    lower: &DVector<f32>,
+    upper: &DVector<f32>,
+    tol: f32,
+    x: &mut DVector<f32>,
+    free: &mut [bool],
+) -> Option<()> {
+    let n = free.len();
+    loop {
+        if !free.iter().any(|f| *f) {
+            return Some(());
+        }
+        let s = free_lstsq(a, b, x, free)?;
+        let inside = |j: usize| s[j] > lower[j] && s[j] < upper[j];
+        if (0..n).filter(|j| free[*j]).all(inside) {
+            *x = s;
+            return Some(());
+        }
+        let alpha = (0..n)
+            .filter(|j| free[*j] && !inside(*j))
+            .map(|j| {
+                let bound = if s[j] <= lower[j] { lower[j] } else { upper[j] };
+                let step = s[j] - x[j];
+                if step != 0. {
+                    (bound - x[j]) / step
+                } else {
+                    0.
+                }
+            })
+            .fold(f32::INFINITY, f32::min)
+            .clamp(0., 1.);
+        *x += (s - &*x) * alpha;
+        for j in 0..n {
+            if free[j] && x[j] <= lower[j] + tol {
+                free[j] = false;
+                x[j] = lower[j];
+            } else if free[j] && x[j] >= upper[j] - tol {
+                free[j] = false;
+                x[j] = upper[j];
+            }
+        }
+    }
+}
+
+/// Solve the least squares problem on the free columns, others kept at their current value
+fn free_lstsq(
+    a: &DMatrix<f32>,
+    b: &DVector<f32>,
+    x: &DVector<f32>,
+    free: &[bool],
+) -> Option<DVector<f32>> {
+    let mut residual = b.clone();
+    for j in (0..free.len()).filter(|j| !free[*j]) {
+        residual -= a.column(j) * x[j];
+    }
+    let sub_x = passive_lstsq(a, &residual, free)?;
+    Some(DVector::from_fn(free.len(), |j, _| {
+        if free[j] {
+            sub_x[j]
+        } else {
+            x[j]
+        }
+    }))
+}
+
+/// Solve the least squares problem restricted to the passive columns, others set to zero
+fn passive_lstsq(a: &DMatrix<f32>, b: &DVector<f32>, passive: &[bool]) -> Option<DVector<f32>> {
+    let columns: Vec<usize> = (0..passive.len()).filter(|j| passive[*j]).collect();
+    let mut sub = DMatrix::<f32>::zeros(a.nrows(), columns.len());
+    for (c, j) in columns.iter().enumerate() {
+        sub.set_column(c, &a.column(*j));
+    }
+    let sub_x = lstsq(&sub, b)?;
+    let mut x = DVector::<f32>::zeros(passive.len());
+    for (c, j) in columns.iter().enumerate() {
+        x[*j] = sub_x[c];
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_lstsq_exact() {
+        let a = DMatrix::from_row_slice(4, 2, &[1., 0., 0., 1., 1., 1., 2., 1.]);
+        let x = DVector::from_vec(vec![3., -2.]);
+        let b = &a * &x;
+        let result = lstsq(&a, &b).unwrap();
+        assert_approx_eq!(result[0], 3., 1e-4);
+        assert_approx_eq!(result[1], -2., 1e-4);
+    }
+
+    #[test]
+    fn test_nnls_unconstrained_optimum() {
+        let a = DMatrix::from_row_slice(4, 2, &[1., 0., 0., 1., 1., 1., 2., 1.]);
+        let x = DVector::from_vec(vec![3., 2.]);
+        let b = &a * &x;
+        let result = nnls(&a, &b).unwrap();
+        assert_approx_eq!(result[0], 3., 1e-4);
+        assert_approx_eq!(result[1], 2., 1e-4);
+    }
+
+    #[test]
+    fn test_bvls_bounds() {
+        let a = DMatrix::from_row_slice(3, 2, &[1., 0., 0., 1., 1., 1.]);
+        let b = DVector::from_vec(vec![2., -1., 1.]);
+        // Unconstrained solution (2, -1) lies within the bounds
+        let lower = DVector::from_vec(vec![-5., -5.]);
+        let upper = DVector::from_vec(vec![5., 5.]);
+        let result = bvls(&a, &b, &lower, &upper).unwrap();
+        assert_approx_eq!(result[0], 2., 1e-4);
+        assert_approx_eq!(result[1], -1., 1e-4);
+
+        // First weight bounded above at 1.2, second one adjusts within its bounds
+        let upper = DVector::from_vec(vec![1.2, 5.]);
+        let result = bvls(&a, &b, &lower, &upper).unwrap();
+        assert_approx_eq!(result[0], 1.2, 1e-4);
+        assert_approx_eq!(result[1], -0.6, 1e-4);
+
+        // Unbounded first weight, second one bounded below at -0.5
+        let lower = DVector::from_vec(vec![f32::NEG_INFINITY, -0.5]);
+        let upper = DVector::from_vec(vec![f32::INFINITY, 5.]);
+        let result = bvls(&a, &b, &lower, &upper).unwrap();
+        assert_approx_eq!(result[0], 1.75, 1e-4);
+        assert_approx_eq!(result[1], -0.5, 1e-4);
+    }
+
+    #[test]
+    fn test_nnls_active_constraint() {
+        let a = DMatrix::from_row_slice(3, 2, &[1., 0., 0., 1., 1., 1.]);
+        let b = DVector::from_vec(vec![2., -1., 1.]);
+        let result = nnls(&a, &b).unwrap();
+        // Unconstrained solution is (2, -1), second weight is clamped to zero
+        assert_approx_eq!(result[0], 1.5, 1e-4);
+        assert_approx_eq!(result[1], 0., 1e-6);
+    }
+}
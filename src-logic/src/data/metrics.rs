@@ -0,0 +1,246 @@
+//! Goodness-of-fit metrics of a calibrated model against displacement data.
+//!
+//! The information criteria assume gaussian residuals, so that models with a different
+//! number of surfaces can be compared on the same data set: the lower, the better.
+
+use super::rmse;
+
+/// Goodness-of-fit of a prediction against observations
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FitMetrics {
+    /// Number of observations
+    pub nb_points: usize,
+    /// Number of fitted parameters, free weights, gradient control points and nuisance terms
+    pub nb_params: usize,
+    /// Root mean square error
+    pub rmse: f32,
+    /// Mean absolute error
+    pub mae: f32,
+    /// Mean of the prediction minus the observation
+    pub bias: f32,
+    /// Coefficient of determination
+    pub r2: f32,
+    /// RMSE divided by the range of the observations
+    pub nrmse: f32,
+    /// Largest absolute residual
+    pub max_abs_residual: f32,
+    /// Akaike information criterion
+    pub aic: f32,
+    /// Bayesian information criterion
+    pub bic: f32,
+}
+
+impl FitMetrics {
+    /// Names of the metrics, in the order of `values`
+    pub const NAMES: [&'static str; 10] = [
+        "nb_points",
+        "nb_params",
+        "rmse",
+        "mae",
+        "bias",
+        "r2",
+        "nrmse",
+        "max_abs_residual",
+        "aic",
+        "bic",
+    ];
+
+    /// Compute all the metrics of a prediction fitted with `nb_params` parameters
+    ///
+    /// None if there is no observation or if the lengths differ
+    pub fn new(prediction: &[f32], observation: &[f32], nb_params: usize) -> Option<Self> {
+        if prediction.is_empty() || prediction.len() != observation.len() {
+            return None;
+        }
+        Some(FitMetrics {
+            nb_points: prediction.len(),
+            nb_params,
+            rmse: rmse(prediction, observation),
+            mae: mae(prediction, observation),
+            bias: bias(prediction, observation),
+            r2: r_squared(prediction, observation),
+            nrmse: nrmse(prediction, observation),
+            max_abs_residual: max_abs_residual(prediction, observation),
+            aic: aic(prediction, observation, nb_params),
+            bic: bic(prediction, observation, nb_params),
+        })
+    }
+
+    /// Values of the metrics, in the order of `NAMES`
+    pub fn values(&self) -> [f32; 10] {
+        [
+            self.nb_points as f32,
+            self.nb_params as f32,
+            self.rmse,
+            self.mae,
+            self.bias,
+            self.r2,
+            self.nrmse,
+            self.max_abs_residual,
+            self.aic,
+            self.bic,
+        ]
+    }
+}
+
+/// Residuals of a fit, observation minus prediction
+pub fn residuals(prediction: &[f32], observation: &[f32]) -> Vec<f32> {
+    debug_assert_eq!(prediction.len(), observation.len());
+    prediction
+        .iter()
+        .zip(observation.iter())
+        .map(|(p, o)| o - p)
+        .collect()
+}
+
+fn residual_sum_of_squares(prediction: &[f32], observation: &[f32]) -> f32 {
+    prediction
+        .iter()
+        .zip(observation.iter())
+        .map(|(p, o)| (p - o) * (p - o))
+        .sum()
+}
+
+/// Mean absolute error
+pub fn mae(prediction: &[f32], observation: &[f32]) -> f32 {
+    debug_assert_eq!(prediction.len(), observation.len());
+    let sum: f32 = prediction
+        .iter()
+        .zip(observation.iter())
+        .map(|(p, o)| (p - o).abs())
+        .sum();
+    sum / prediction.len() as f32
+}
+
+/// Mean of the prediction minus the observation
+pub fn bias(prediction: &[f32], observation: &[f32]) -> f32 {
+    debug_assert_eq!(prediction.len(), observation.len());
+    let sum: f32 = prediction
+        .iter()
+        .zip(observation.iter())
+        .map(|(p, o)| p - o)
+        .sum();
+    sum / prediction.len() as f32
+}
+
+/// Coefficient of determination, 1 for a perfect fit
+///
+/// Negative infinity if the observations are constant and the fit is not perfect
+pub fn r_squared(prediction: &[f32], observation: &[f32]) -> f32 {
+    debug_assert_eq!(prediction.len(), observation.len());
+    let mean = observation.iter().sum::<f32>() / observation.len() as f32;
+    let total: f32 = observation.iter().map(|o| (o - mean) * (o - mean)).sum();
+    let residual = residual_sum_of_squares(prediction, observation);
+    if residual == 0. {
+        1.
+    } else if total == 0. {
+        f32::NEG_INFINITY
+    } else {
+        1. - residual / total
+    }
+}
+
+/// RMSE divided by the range of the observations, 0 for a perfect fit
+///
+/// Infinity if the observations are constant and the fit is not perfect
+pub fn nrmse(prediction: &[f32], observation: &[f32]) -> f32 {
+    let min = observation.iter().cloned().fold(f32::INFINITY, f32::min);
+    let max = observation
+        .iter()
+        .cloned()
+        .fold(f32::NEG_INFINITY, f32::max);
+    let rmse = rmse(prediction, observation);
+    if rmse == 0. {
+        0.
+    } else if max == min {
+        f32::INFINITY
+    } else {
+        rmse / (max - min)
+    }
+}
+
+/// Largest absolute residual
+pub fn max_abs_residual(prediction: &[f32], observation: &[f32]) -> f32 {
+    debug_assert_eq!(prediction.len(), observation.len());
+    prediction
+        .iter()
+        .zip(observation.iter())
+        .map(|(p, o)| (p - o).abs())
+        .fold(0., f32::max)
+}
+
+/// Akaike information criterion of a least squares fit with `nb_params` parameters
+pub fn aic(prediction: &[f32], observation: &[f32], nb_params: usize) -> f32 {
+    let n = prediction.len() as f32;
+    n * log_mean_square(prediction, observation) + 2. * nb_params as f32
+}
+
+/// Bayesian information criterion of a least squares fit with `nb_params` parameters
+pub fn bic(prediction: &[f32], observation: &[f32], nb_params: usize) -> f32 {
+    let n = prediction.len() as f32;
+    n * log_mean_square(prediction, observation) + nb_params as f32 * n.ln()
+}
+
+/// Logarithm of the mean square residual, floored to stay finite for a perfect fit
+fn log_mean_square(prediction: &[f32], observation: &[f32]) -> f32 {
+    let n = prediction.len() as f32;
+    (residual_sum_of_squares(prediction, observation) / n)
+        .max(f32::MIN_POSITIVE)
+        .ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_metrics() {
+        let prediction = [1., 2., 4., 4.];
+        let observation = [1., 3., 3., 5.];
+        let metrics = FitMetrics::new(&prediction, &observation, 1).unwrap();
+        assert_approx_eq!(metrics.rmse, 0.75_f32.sqrt());
+        assert_approx_eq!(metrics.mae, 0.75);
+        assert_approx_eq!(metrics.bias, -0.25);
+        // Observations mean is 3, total sum of squares is 8
+        assert_approx_eq!(metrics.r2, 1. - 3. / 8.);
+        assert_approx_eq!(metrics.nrmse, 0.75_f32.sqrt() / 4.);
+        assert_approx_eq!(metrics.max_abs_residual, 1.);
+        assert_approx_eq!(metrics.aic, 4. * 0.75_f32.ln() + 2.);
+        assert_approx_eq!(metrics.bic, 4. * 0.75_f32.ln() + 4_f32.ln());
+        assert!(FitMetrics::new(&prediction, &observation[..3], 1).is_none());
+        assert_eq!(residuals(&prediction, &observation), vec![0., 1., -1., 1.]);
+    }
+
+    #[test]
+    fn test_information_criteria_penalise_parameters() {
+        let prediction = [1., 2., 4., 4.];
+        let observation = [1., 3., 3., 5.];
+        let simple = FitMetrics::new(&prediction, &observation, 1).unwrap();
+        let complex = FitMetrics::new(&prediction, &observation, 3).unwrap();
+        assert!(complex.aic > simple.aic);
+        assert!(complex.bic > simple.bic);
+    }
+
+    #[test]
+    fn test_nrmse_constant_observations() {
+        let observation = [2., 2., 2.];
+        assert_eq!(nrmse(&observation, &observation), 0.);
+        assert_eq!(nrmse(&[1., 2., 3.], &observation), f32::INFINITY);
+        assert_eq!(nrmse(&[2.], &[2.]), 0.);
+        assert_eq!(nrmse(&[1.], &[2.]), f32::INFINITY);
+        let metrics = FitMetrics::new(&[2.], &[2.], 1).unwrap();
+        assert_eq!(metrics.nrmse, 0.);
+        assert_eq!(metrics.r2, 1.);
+    }
+
+    #[test]
+    fn test_information_criteria_perfect_fit() {
+        let observation = [1., 3., 3., 5.];
+        let perfect = FitMetrics::new(&observation, &observation, 2).unwrap();
+        let imperfect = FitMetrics::new(&[1., 2., 4., 4.], &observation, 2).unwrap();
+        assert!(perfect.aic.is_finite() && perfect.bic.is_finite());
+        assert!(perfect.aic < imperfect.aic);
+        assert!(perfect.bic < imperfect.bic);
+    }
+}
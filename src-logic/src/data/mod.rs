@@ -1,8 +1,19 @@
+pub mod decomposition;
+pub(crate) mod lsq;
+pub mod metrics;
+pub mod sampling;
+pub mod sensitivity;
+pub mod series;
+pub mod uncertainty;
 pub mod vec_proj;
-use eqsolver::{global_optimisers::ParticleSwarm, SolverError};
-use nalgebra::base::DVector;
+use eqsolver::global_optimisers::ParticleSwarm;
+use nalgebra::base::{DMatrix, DVector};
+use std::ops::Range;
+use uncertainty::{resample_residuals, WeightUncertainty, XorShift};
 use vec_proj::*;
 
+use crate::profile::interpol_linear;
+use crate::slide::slope1d;
 use crate::types::*;
 
 impl DispProfile {
@@ -15,11 +26,82 @@ impl DispProfile {
             })
             .collect()
     }
+
+    /// Projected amplitude of each vector in its own line of sight
+    ///
+    /// `los` holds the line of sight of each vector of the profile.
+    pub fn projected_amplitude_onto_each(
+        &self,
+        los: &[Vector3Rep],
+        section: &Orientation,
+    ) -> Vec<f32> {
+        debug_assert_eq!(self.vecs.len(), los.len());
+        self.vecs
+            .iter()
+            .zip(los.iter())
+            .map(|(vec, los3)| {
+                Vector3Rep::from_vertical_section_rad(vec, section.azimuth).inner_product(los3)
+            })
+            .collect()
+    }
 }
 
 impl DispData {
-    pub fn project_on_section(&mut self, _section: Orientation) {
-        todo!()
+    /// Project the amplitude recorded in the line of sight into 2D vectors of the section
+    ///
+    /// The displacement is assumed parallel to a direction given by the slope of a surface,
+    /// or by the slope of the DEM if no surface is given. The vectors are oriented downslope
+    /// according to the global dip of the direction surface.
+    ///
+    /// The line of sight of each point is used if the data give one. It only sees the
+    /// component of the assumed displacement along it, points where the absolute sensitivity
+    /// is lower than `min_sensitivity` are flagged as ill-conditioned and receive a null vector.
+    pub fn project_on_section(
+        &mut self,
+        dem: &Dem1D,
+        direction: Option<&Surface1D>,
+        section: &Orientation,
+        los: &Orientation,
+        min_sensitivity: f32,
+    ) -> Result<(), VectorInputError> {
+        let direction_z = match direction {
+            Some(surface) => &surface.z,
+            None => &dem.surface.z,
+        };
+        if direction_z.len() != dem.x.len() {
+            return Err(VectorInputError::InconsistentLen {
+                vec1: direction_z.len(),
+                vec2: dem.x.len(),
+            });
+        }
+        if dem.x.len() < 2 {
+            return Err(VectorInputError::EmptyVecs);
+        }
+        let slope = slope1d(&dem.x, direction_z);
+        let local_slope = interpol_linear(&dem.x, &slope, &self.x);
+        let is_right = direction_z.last() < direction_z.first();
+        let los_vecs = self.los_vectors(los);
+
+        self.projected_vecs = Vec::with_capacity(self.x.len());
+        self.ill_conditioned = Vec::with_capacity(self.x.len());
+        for ((slope, amplitude), los3) in local_slope
+            .iter()
+            .zip(self.amplitude.iter())
+            .zip(los_vecs.iter())
+        {
+            let mut vec = Vector2Rep::from_rad(*slope, is_right);
+            let sensitivity =
+                Vector3Rep::from_vertical_section_rad(&vec, section.azimuth).inner_product(los3);
+            if sensitivity.abs() < min_sensitivity {
+                self.projected_vecs.push(Vector2Rep::default());
+                self.ill_conditioned.push(true);
+            } else {
+                vec.multiply(amplitude / sensitivity);
+                self.projected_vecs.push(vec);
+                self.ill_conditioned.push(false);
+            }
+        }
+        Ok(())
     }
 }
 
@@ -33,6 +115,31 @@ pub fn rmse(prediction: &[f32], observation: &[f32]) -> f32 {
         .sqrt()
 }
 
+/// Chi-square of a prediction against observations with known standard deviations
+pub fn chi_square(prediction: &[f32], observation: &[f32], sigma: &[f32]) -> f32 {
+    debug_assert_eq!(prediction.len(), observation.len());
+    debug_assert_eq!(prediction.len(), sigma.len());
+    (0..prediction.len()).fold(0.0, |acc, k| {
+        acc + ((prediction[k] - observation[k]) / sigma[k]).powi(2)
+    })
+}
+
+/// Chi-square divided by the degrees of freedom of a model with `nb_params` parameters
+///
+/// None if there is not more observations than parameters
+pub fn reduced_chi_square(
+    prediction: &[f32],
+    observation: &[f32],
+    sigma: &[f32],
+    nb_params: usize,
+) -> Option<f32> {
+    if prediction.len() <= nb_params {
+        return None;
+    }
+    let dof = (prediction.len() - nb_params) as f32;
+    Some(chi_square(prediction, observation, sigma) / dof)
+}
+
 pub fn vec_to_na(vec: Vec<f32>) -> DVector<f32> {
     DVector::from_vec(vec)
 }
@@ -41,90 +148,696 @@ pub fn na_to_vec(vec: DVector<f32>) -> Vec<f32> {
     vec.data.as_vec().to_vec()
 }
 
+/// Solver used to calibrate the weights of the surfaces of a model
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Solver {
+    /// Direct linear least squares with non-negative weights
+    #[default]
+    NonNegativeLinear,
+    /// Direct linear least squares without constraint on the weights
+    Linear,
+    /// Direct linear least squares with the weights within their bounds
+    BoundedLinear,
+    /// Global optimisation of the weights within their bounds
+    ParticleSwarm,
+}
+
+impl Solver {
+    /// All the available solvers
+    pub const ALL: [Solver; 4] = [
+        Solver::NonNegativeLinear,
+        Solver::Linear,
+        Solver::BoundedLinear,
+        Solver::ParticleSwarm,
+    ];
+
+    /// Short name of the solver
+    pub fn name(&self) -> &'static str {
+        match self {
+            Solver::NonNegativeLinear => "Non-negative least squares",
+            Solver::Linear => "Least squares",
+            Solver::BoundedLinear => "Bounded least squares",
+            Solver::ParticleSwarm => "Particle swarm",
+        }
+    }
+
+    /// True if the solver takes the bounds of the weights into account
+    pub fn is_bounded(&self) -> bool {
+        matches!(self, Solver::BoundedLinear | Solver::ParticleSwarm)
+    }
+}
+
+/// Settings of the calibration of the weights of a model, one value per surface
+///
+/// The bounds are only used by the bounded solvers, and the initial guess by the
+/// particle swarm. Fixed weights are excluded from the calibration whatever the solver.
+///
+/// The control points of the amplitude gradient of a surface can be calibrated along
+/// with its weight, the settings of the surface then apply to each control point.
+/// Gradients of fixed weights are never calibrated.
+///
+/// A constant offset and a linear ramp along the section can be fitted to each data set
+/// along with the weights, to absorb the arbitrary reference point of the InSAR data and
+/// orbital or atmospheric trends. These nuisance terms are never bounded.
+///
+/// The model can be averaged over the footprint of each data point given in the data,
+/// instead of being sampled at its center.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationSettings {
+    /// Solver used to calibrate the weights
+    pub solver: Solver,
+    /// Lower bound of each weight
+    pub lower_bounds: Vec<f32>,
+    /// Upper bound of each weight
+    pub upper_bounds: Vec<f32>,
+    /// Weights kept at a given value during the calibration
+    pub fixed_weights: Vec<Option<f32>>,
+    /// Initial guess of each weight
+    pub initial_guess: Vec<f32>,
+    /// Number of bootstrap resamples used to estimate the uncertainty, 0 to linearise
+    pub bootstrap_samples: usize,
+    /// Surfaces whose gradient control points are calibrated
+    pub free_gradients: Vec<bool>,
+    /// Penalty on the curvature of the calibrated gradients, 0 for none
+    pub gradient_smoothing: f32,
+    /// Fit a constant offset to each data set
+    pub fit_offset: bool,
+    /// Fit a linear ramp along the section to each data set
+    pub fit_ramp: bool,
+    /// Average the model over the footprint of the data points
+    pub footprint_average: bool,
+}
+
+impl CalibrationSettings {
+    /// Default settings for a model of `nb_surfaces` surfaces
+    ///
+    /// Weights are bounded from 0 to 1000 and the initial guess is 1 for every surface.
+    pub fn new(nb_surfaces: usize) -> Self {
+        CalibrationSettings {
+            solver: Solver::default(),
+            lower_bounds: vec![0.; nb_surfaces],
+            upper_bounds: vec![1000.; nb_surfaces],
+            fixed_weights: vec![None; nb_surfaces],
+            initial_guess: vec![1.; nb_surfaces],
+            bootstrap_samples: 0,
+            free_gradients: vec![false; nb_surfaces],
+            gradient_smoothing: 0.,
+            fit_offset: false,
+            fit_ramp: false,
+            footprint_average: false,
+        }
+    }
+
+    /// Check the consistency of the settings with the number of surfaces
+    ///
+    /// # Errors
+    /// * the length of a setting differs from the number of surfaces
+    /// * a lower bound is greater than its upper bound
+    /// * the smoothing of the gradients is negative
+    pub fn check(&self, nb_surfaces: usize) -> Result<(), VectorInputError> {
+        for len in [
+            self.lower_bounds.len(),
+            self.upper_bounds.len(),
+            self.fixed_weights.len(),
+            self.initial_guess.len(),
+            self.free_gradients.len(),
+        ] {
+            if len != nb_surfaces {
+                return Err(VectorInputError::InconsistentLen {
+                    vec1: nb_surfaces,
+                    vec2: len,
+                });
+            }
+        }
+        if (0..nb_surfaces).any(|k| self.lower_bounds[k] > self.upper_bounds[k]) {
+            return Err(VectorInputError::InvalidBounds);
+        }
+        if self.gradient_smoothing.is_nan() || self.gradient_smoothing < 0. {
+            return Err(VectorInputError::InvalidSmoothing);
+        }
+        Ok(())
+    }
+
+    /// True if the gradient control points of the surface are calibrated
+    pub fn calibrates_gradient(&self, surface: usize) -> bool {
+        self.free_gradients[surface] && self.fixed_weights[surface].is_none()
+    }
+
+    /// Number of parameters fitted to each data set with the gradient control points of
+    /// each surface
+    ///
+    /// A free surface counts for its weight or for its control points when its gradient is
+    /// calibrated, and the offset and ramp of the data set are counted when fitted.
+    pub fn nb_params(&self, gradients: &[Vec<(usize, f32)>]) -> usize {
+        let surfaces: usize = (0..self.fixed_weights.len())
+            .filter(|k| self.fixed_weights[*k].is_none())
+            .map(|k| match gradients.get(k) {
+                Some(points) if self.calibrates_gradient(k) => points.len(),
+                _ => 1,
+            })
+            .sum();
+        surfaces + self.fit_offset as usize + self.fit_ramp as usize
+    }
+
+    /// Settings of the columns of a composed model, `column_surfaces` holding the
+    /// surface of each column
+    pub(crate) fn expand(&self, column_surfaces: &[usize]) -> Self {
+        let select = |values: &Vec<f32>| column_surfaces.iter().map(|k| values[*k]).collect();
+        CalibrationSettings {
+            solver: self.solver,
+            lower_bounds: select(&self.lower_bounds),
+            upper_bounds: select(&self.upper_bounds),
+            fixed_weights: column_surfaces
+                .iter()
+                .map(|k| self.fixed_weights[*k])
+                .collect(),
+            initial_guess: select(&self.initial_guess),
+            bootstrap_samples: self.bootstrap_samples,
+            free_gradients: vec![false; column_surfaces.len()],
+            gradient_smoothing: 0.,
+            fit_offset: self.fit_offset,
+            fit_ramp: self.fit_ramp,
+            footprint_average: self.footprint_average,
+        }
+    }
+
+    /// Indices of the weights to calibrate
+    fn free_weights(&self) -> Vec<usize> {
+        (0..self.fixed_weights.len())
+            .filter(|k| self.fixed_weights[*k].is_none())
+            .collect()
+    }
+}
+
+/// Result of the calibration of the weights of a model
+#[derive(Debug, Default, Clone)]
+pub struct Calibration {
+    /// Calibrated weight of each surface
+    pub weights: Vec<f32>,
+    /// Uncertainty of the weights, if it can be estimated
+    pub uncertainty: Option<WeightUncertainty>,
+    /// RMSE of the calibrated model for each displacement data set
+    pub misfits: Vec<f32>,
+    /// Gradient control points of each surface, calibrated or not
+    pub gradients: Vec<Vec<(usize, f32)>>,
+    /// Offset fitted to each displacement data set, 0 if not fitted
+    pub offsets: Vec<f32>,
+    /// Ramp fitted to each data set, per meter along the section, 0 if not fitted
+    pub ramps: Vec<f32>,
+}
+
+impl Calibration {
+    /// Nuisance terms fitted to a data set at positions `x` along the section
+    ///
+    /// Subtracting them from the data gives the displacement explained by the model.
+    pub fn nuisance(&self, dataset: usize, x: &[f32]) -> Vec<f32> {
+        let offset = self.offsets.get(dataset).copied().unwrap_or(0.);
+        let ramp = self.ramps.get(dataset).copied().unwrap_or(0.);
+        x.iter().map(|x| offset + ramp * x).collect()
+    }
+
+    /// Gather the calibrated columns of a composed model into the weight and the
+    /// gradient of each surface
+    ///
+    /// A surface with a calibrated gradient has one column per control point. Its weight
+    /// is the coefficient of largest magnitude, so that the gradient peaks at 1, and the
+    /// uncertainty of the weight is the one of this control point.
+    pub(crate) fn collapse(
+        self,
+        column_surfaces: &[usize],
+        gradients: &[Vec<(usize, f32)>],
+    ) -> Self {
+        let mut weights = vec![0.; gradients.len()];
+        let mut gradients = gradients.to_vec();
+        let mut weight_columns = vec![0; gradients.len()];
+        for (surface, gradient) in gradients.iter_mut().enumerate() {
+            let columns: Vec<usize> = (0..column_surfaces.len())
+                .filter(|c| column_surfaces[*c] == surface)
+                .collect();
+            let Some(peak) = columns
+                .iter()
+                .copied()
+                .max_by(|i, j| self.weights[*i].abs().total_cmp(&self.weights[*j].abs()))
+            else {
+                continue;
+            };
+            weights[surface] = self.weights[peak];
+            weight_columns[surface] = peak;
+            if columns.len() > 1 && self.weights[peak] != 0. {
+                for (point, c) in gradient.iter_mut().zip(columns.iter()) {
+                    point.1 = self.weights[*c] / self.weights[peak];
+                }
+            }
+        }
+        let uncertainty = self.uncertainty.map(|u| WeightUncertainty {
+            covariance: weight_columns
+                .iter()
+                .map(|i| {
+                    weight_columns
+                        .iter()
+                        .map(|j| u.covariance[*i][*j])
+                        .collect()
+                })
+                .collect(),
+            std_errors: weight_columns.iter().map(|i| u.std_errors[*i]).collect(),
+            confidence_95: weight_columns.iter().map(|i| u.confidence_95[*i]).collect(),
+            bootstrap_samples: u.bootstrap_samples,
+        });
+        Calibration {
+            weights,
+            uncertainty,
+            misfits: self.misfits,
+            gradients,
+            offsets: self.offsets,
+            ramps: self.ramps,
+        }
+    }
+}
+
+/// Composed model of unit profiles against the displacement data
+///
+/// The rows of the system are weighted by the inverse of the standard deviation of the
+/// data when it is known, so that the least squares solvers minimise the chi-square.
+/// Points without standard deviation keep a unit weight.
+///
+/// Nuisance terms, offsets and ramps of the data sets, are eliminated from the system by
+/// projecting the data rows onto the orthogonal complement of their columns. The weights
+/// are then solved on the projected system whatever the solver, and the nuisance terms
+/// recovered from the residuals of the weights.
 #[derive(Debug)]
 pub(crate) struct ComposedModel {
-    dem: Dem1D,
-    profiles_regul: Vec<DispProfile>,
-    section_geometry: Orientation,
-    los_geometry: Orientation,
-    los_data: DispData,
+    /// Weighted projected amplitude of each unit profile (columns) at each data point (rows)
+    design: DMatrix<f32>,
+    /// Weighted observed amplitude at each data point
+    observed: DVector<f32>,
+    /// Weight of each row, inverse of the standard deviation of the data point
+    row_weights: DVector<f32>,
+    /// Rows of the design matrix belonging to each displacement data set
+    datasets: Vec<Range<usize>>,
+    /// Position along the section of each data point
+    x: DVector<f32>,
+    /// Weighted nuisance terms (columns) at each data point (rows)
+    nuisance: DMatrix<f32>,
+    /// Pseudo-inverse of the nuisance matrix
+    nuisance_pinv: DMatrix<f32>,
+    /// Data set and kind of each nuisance column, true for a ramp
+    nuisance_terms: Vec<(usize, bool)>,
+    /// Penalty rows appended to the design matrix when solving, expected to be null
+    penalty: DMatrix<f32>,
 }
 
 impl ComposedModel {
+    /// Construct the composed model of unit profiles against one or multiple data sets
+    ///
+    /// Each data set is associated with its own acquisition geometry, and its rows are
+    /// stacked in the design matrix so that a single set of weights is fitted to all of them.
+    ///
+    /// With `footprint_average`, the unit profiles are averaged over the footprint of the
+    /// points of the data sets that give one, the profiles being sampled on the DEM.
     pub fn new(
         dem: &Dem1D,
         profiles_regul: &[DispProfile],
         section_geometry: &Orientation,
-        los_geometry: &Orientation,
-        los_data: &DispData,
+        los_datasets: &[(Orientation, DispData)],
+        footprint_average: bool,
     ) -> Self {
+        let nb_rows = los_datasets.iter().map(|(_, d)| d.x.len()).sum();
+        let mut design = DMatrix::<f32>::zeros(nb_rows, profiles_regul.len());
+        let mut observed = DVector::<f32>::zeros(nb_rows);
+        let mut row_weights = DVector::<f32>::repeat(nb_rows, 1.);
+        let mut x = DVector::<f32>::zeros(nb_rows);
+        let mut datasets = Vec::with_capacity(los_datasets.len());
+
+        let mut first_row = 0;
+        for (los_geometry, los_data) in los_datasets {
+            let los_x = los_data.x.to_owned();
+            let los_y = dem.interpolate_elevation_on_x(&los_x);
+            let los_origins = (0..los_x.len()).map(|k| [los_x[k], los_y[k]]).collect();
+            let rows = first_row..(first_row + los_x.len());
+            let los_vecs = los_data.los_vectors(los_geometry);
+
+            // Each unit profile is interpolated and projected only once
+            for (p, profile) in profiles_regul.iter().enumerate() {
+                let column = match (&los_data.footprint, footprint_average) {
+                    (Some(footprint), true) => profile.footprint_amplitude_onto(
+                        &los_vecs,
+                        section_geometry,
+                        &los_x,
+                        footprint,
+                    ),
+                    _ => {
+                        let mut profile = profile.to_owned();
+                        profile.interpolate_on_origins(&los_origins);
+                        profile.projected_amplitude_onto_each(&los_vecs, section_geometry)
+                    }
+                };
+                design
+                    .view_mut((rows.start, p), (rows.len(), 1))
+                    .copy_from_slice(&column);
+            }
+            observed
+                .rows_mut(rows.start, rows.len())
+                .copy_from_slice(&los_data.amplitude);
+            x.rows_mut(rows.start, rows.len()).copy_from_slice(&los_x);
+            if let Some(sigma) = &los_data.sigma {
+                for (k, s) in rows.clone().zip(sigma.iter()) {
+                    row_weights[k] = 1. / s;
+                }
+            }
+
+            first_row = rows.end;
+            datasets.push(rows);
+        }
+
+        for (mut row, w) in design.row_iter_mut().zip(row_weights.iter()) {
+            row *= *w;
+        }
+        observed.component_mul_assign(&row_weights);
+
+        let penalty = DMatrix::<f32>::zeros(0, profiles_regul.len());
         ComposedModel {
-            dem: dem.clone(),
-            profiles_regul: profiles_regul.to_owned(),
-            section_geometry: section_geometry.clone(),
-            los_geometry: los_geometry.clone(),
-            los_data: los_data.clone(),
+            design,
+            observed,
+            row_weights,
+            datasets,
+            x,
+            nuisance: DMatrix::<f32>::zeros(nb_rows, 0),
+            nuisance_pinv: DMatrix::<f32>::zeros(0, nb_rows),
+            nuisance_terms: vec![],
+            penalty,
+        }
+    }
+
+    /// Fit a constant offset and/or a linear ramp along the section to each data set
+    pub fn with_nuisance(mut self, offset: bool, ramp: bool) -> Self {
+        let mut terms = vec![];
+        for d in 0..self.datasets.len() {
+            if offset {
+                terms.push((d, false));
+            }
+            if ramp {
+                terms.push((d, true));
+            }
+        }
+        if terms.is_empty() {
+            return self;
+        }
+        let mut nuisance = DMatrix::<f32>::zeros(self.design.nrows(), terms.len());
+        for (c, (d, is_ramp)) in terms.iter().enumerate() {
+            for k in self.datasets[*d].clone() {
+                let value = if *is_ramp { self.x[k] } else { 1. };
+                nuisance[(k, c)] = value * self.row_weights[k];
+            }
         }
+        let eps = (nuisance.norm() * 1e-6).max(f32::MIN_POSITIVE);
+        self.nuisance_pinv = nuisance
+            .clone()
+            .pseudo_inverse(eps)
+            .unwrap_or_else(|_| DMatrix::<f32>::zeros(terms.len(), nuisance.nrows()));
+        self.nuisance = nuisance;
+        self.nuisance_terms = terms;
+        self
     }
 
-    pub fn current_rmse(&self, disp_model: &Vec<f32>) -> f32 {
-        rmse(disp_model, &self.los_data.amplitude)
+    /// Penalise the curvature of groups of columns holding the control points of a gradient
+    ///
+    /// Each triplet of consecutive control points adds the second difference of their
+    /// coefficients, multiplied by `smoothing`, as a row expected to be null.
+    pub fn with_smoothing(mut self, groups: &[Range<usize>], smoothing: f32) -> Self {
+        let nb_rows: usize = groups.iter().map(|g| g.len().saturating_sub(2)).sum();
+        let mut penalty = DMatrix::<f32>::zeros(nb_rows, self.design.ncols());
+        let mut row = 0;
+        for group in groups {
+            for c in group.start..group.end.saturating_sub(2) {
+                penalty[(row, c)] = smoothing;
+                penalty[(row, c + 1)] = -2. * smoothing;
+                penalty[(row, c + 2)] = smoothing;
+                row += 1;
+            }
+        }
+        self.penalty = penalty;
+        self
     }
 
-    pub fn compose_model(&self, weights: Vec<f32>) -> DispProfile {
-        let nb_vecs = self.profiles_regul[0].vecs.len();
-        let origins = self.profiles_regul[0].origins.to_owned();
+    /// RMSE of a prediction against each data set
+    pub fn misfits(&self, predicted: &DVector<f32>) -> Vec<f32> {
+        let observed = self.observed.component_div(&self.row_weights);
+        self.datasets
+            .iter()
+            .map(|rows| {
+                rmse(
+                    &predicted.as_slice()[rows.clone()],
+                    &observed.as_slice()[rows.clone()],
+                )
+            })
+            .collect()
+    }
 
-        let mut sum_vx = vec![0.; nb_vecs];
-        let mut sum_vz = vec![0.; nb_vecs];
+    /// Predicted amplitude divided by the standard deviation of the data points,
+    /// nuisance terms included
+    fn predict_weighted(&self, weights: &DVector<f32>, nuisance: &DVector<f32>) -> DVector<f32> {
+        &self.design * weights + &self.nuisance * nuisance
+    }
 
-        for k in 0..self.profiles_regul.len() {
-            let mut profile = self.profiles_regul[k].to_owned();
-            profile.weight_disp(weights[k]);
-            for i in 0..nb_vecs {
-                sum_vx[i] += profile.vecs[i].coords().0;
-                sum_vz[i] += profile.vecs[i].coords().1;
+    /// Nuisance terms best explaining the weighted observations left by the weights
+    fn fit_nuisance(&self, observed: &DVector<f32>, weights: &DVector<f32>) -> DVector<f32> {
+        &self.nuisance_pinv * (observed - &self.design * weights)
+    }
+
+    /// Remove from the data rows their component explained by the nuisance terms
+    fn project_out_nuisance(&self, matrix: &DMatrix<f32>) -> DMatrix<f32> {
+        if self.nuisance.ncols() == 0 {
+            return matrix.clone();
+        }
+        matrix - &self.nuisance * (&self.nuisance_pinv * matrix)
+    }
+
+    pub fn fit_disp(&self, settings: &CalibrationSettings) -> Result<Vec<f32>, VectorInputError> {
+        self.fit_observed(settings, &self.observed)
+    }
+
+    /// Calibrate the weights and estimate their uncertainty
+    ///
+    /// The uncertainty is linearised if no bootstrap samples are asked, otherwise
+    /// the weights are fitted again on each resample of the residuals.
+    pub fn calibrate(
+        &self,
+        settings: &CalibrationSettings,
+    ) -> Result<Calibration, VectorInputError> {
+        settings.check(self.design.ncols())?;
+        let weights = self.fit_disp(settings)?;
+        let nuisance = self.fit_nuisance(&self.observed, &DVector::from_column_slice(&weights));
+        let fitted = self.predict_weighted(&DVector::from_column_slice(&weights), &nuisance);
+        let misfits = self.misfits(&fitted.component_div(&self.row_weights));
+        let uncertainty = if settings.bootstrap_samples == 0 {
+            // Fixed weights do not take part in the linearised problem
+            let free = settings.free_weights();
+            let (design, observed) = self.reduced_system(settings, &self.observed);
+            let free_weights = DVector::from_iterator(free.len(), free.iter().map(|k| weights[*k]));
+            WeightUncertainty::linearised(
+                &design,
+                &observed,
+                &free_weights,
+                self.design.nrows(),
+                self.nuisance.ncols(),
+            )
+            .map(|u| u.expand(&free, &weights))
+        } else {
+            let residuals = &self.observed - &fitted;
+            let mut rng = XorShift::new(settings.bootstrap_samples as u64);
+            let mut samples = Vec::with_capacity(settings.bootstrap_samples);
+            for _ in 0..settings.bootstrap_samples {
+                let resampled = resample_residuals(&fitted, &residuals, &self.datasets, &mut rng);
+                samples.push(self.fit_observed(settings, &resampled)?);
+            }
+            WeightUncertainty::from_samples(&samples)
+        };
+
+        let mut offsets = vec![0.; self.datasets.len()];
+        let mut ramps = vec![0.; self.datasets.len()];
+        for ((d, is_ramp), value) in self.nuisance_terms.iter().zip(nuisance.iter()) {
+            if *is_ramp {
+                ramps[*d] = *value;
+            } else {
+                offsets[*d] = *value;
             }
         }
 
-        let vecs = (0..nb_vecs)
-            .map(|k| Vector2Rep::new(sum_vx[k], sum_vz[k]))
+        Ok(Calibration {
+            weights,
+            uncertainty,
+            misfits,
+            gradients: vec![],
+            offsets,
+            ramps,
+        })
+    }
+
+    /// Design matrix restricted to the free weights, and observations minus the
+    /// contribution of the fixed weights, both cleared of the nuisance terms and
+    /// followed by the penalty rows
+    fn reduced_system(
+        &self,
+        settings: &CalibrationSettings,
+        observed: &DVector<f32>,
+    ) -> (DMatrix<f32>, DVector<f32>) {
+        let free = settings.free_weights();
+        let nb_rows = self.design.nrows();
+        let nb_penalty = self.penalty.nrows();
+        let mut design = DMatrix::<f32>::zeros(nb_rows + nb_penalty, self.design.ncols());
+        design
+            .rows_mut(0, nb_rows)
+            .copy_from(&self.project_out_nuisance(&self.design));
+        design
+            .rows_mut(nb_rows, nb_penalty)
+            .copy_from(&self.penalty);
+        let observed = self.project_out_nuisance(&DMatrix::from_column_slice(
+            observed.len(),
+            1,
+            observed.as_slice(),
+        ));
+        let mut reduced_observed = DVector::from_column_slice(observed.as_slice())
+            .resize_vertically(nb_rows + nb_penalty, 0.);
+        for (k, fixed) in settings.fixed_weights.iter().enumerate() {
+            if let Some(w) = fixed {
+                reduced_observed -= design.column(k) * *w;
+            }
+        }
+        (design.select_columns(free.iter()), reduced_observed)
+    }
+
+    fn fit_observed(
+        &self,
+        settings: &CalibrationSettings,
+        observed: &DVector<f32>,
+    ) -> Result<Vec<f32>, VectorInputError> {
+        let free = settings.free_weights();
+        let mut weights: Vec<f32> = settings
+            .fixed_weights
+            .iter()
+            .map(|w| w.unwrap_or(0.))
             .collect();
+        if free.is_empty() {
+            return Ok(weights);
+        }
+        let (design, observed) = self.reduced_system(settings, observed);
+        let select = |values: &[f32]| -> DVector<f32> {
+            DVector::from_iterator(free.len(), free.iter().map(|k| values[*k]))
+        };
+        let lower = select(&settings.lower_bounds);
+        let upper = select(&settings.upper_bounds);
 
-        DispProfile::new(vecs, origins).unwrap()
+        let solution = match settings.solver {
+            Solver::NonNegativeLinear => lsq::nnls(&design, &observed),
+            Solver::Linear => lsq::lstsq(&design, &observed),
+            Solver::BoundedLinear => lsq::bvls(&design, &observed, &lower, &upper),
+            Solver::ParticleSwarm => Some(fit_disp_swarm(
+                &design,
+                &observed,
+                lower,
+                upper,
+                select(&settings.initial_guess),
+            )?),
+        };
+        let Some(solution) = solution else {
+            return Err(VectorInputError::LeastSquaresError);
+        };
+        for (c, k) in free.iter().enumerate() {
+            weights[*k] = solution[c];
+        }
+        Ok(weights)
     }
+}
 
-    pub fn objective_function(&self, vec: DVector<f32>) -> f32 {
-        let mut profile = self.compose_model(na_to_vec(vec));
-        let los_x = self.los_data.x.to_owned();
-        let los_y = self.dem.interpolate_elevation_on_x(&los_x);
-        let los_origins = &(0..los_x.len()).map(|k| [los_x[k], los_y[k]]).collect();
-        profile.interpolate_on_origins(los_origins);
-        let predicted = profile.projected_amplitude_onto(
-            self.los_geometry.to_owned(),
-            self.section_geometry.to_owned(),
+/// Global optimisation of the weights of the design matrix within their bounds
+fn fit_disp_swarm(
+    design: &DMatrix<f32>,
+    observed: &DVector<f32>,
+    lower_bounds: DVector<f32>,
+    upper_bounds: DVector<f32>,
+    first_guess: DVector<f32>,
+) -> Result<DVector<f32>, VectorInputError> {
+    // Objective function definition
+    let f = |v: DVector<f32>| {
+        let predicted = design * v;
+        rmse(predicted.as_slice(), observed.as_slice())
+    };
+
+    // Solver call
+    let solution = ParticleSwarm::new(f, lower_bounds, upper_bounds).solve(first_guess);
+
+    // Propagate the error
+    solution.map_err(VectorInputError::SolverError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_reduced_chi_square() {
+        let prediction = [1., 2., 3., 4.];
+        let observation = [1.5, 2., 2., 4.];
+        let sigma = [0.5, 1., 0.5, 2.];
+        assert_approx_eq!(chi_square(&prediction, &observation, &sigma), 5.);
+        assert_approx_eq!(
+            reduced_chi_square(&prediction, &observation, &sigma, 2).unwrap(),
+            2.5
         );
+        assert!(reduced_chi_square(&prediction, &observation, &sigma, 4).is_none());
+    }
 
-        self.current_rmse(&predicted)
+    #[test]
+    fn test_nb_params() {
+        let mut settings = CalibrationSettings::new(3);
+        let gradients = vec![
+            vec![],
+            vec![(0, 1.), (5, 1.), (9, 1.)],
+            vec![(0, 1.), (9, 1.)],
+        ];
+        assert_eq!(settings.nb_params(&gradients), 3);
+
+        // Calibrated control points, except under a fixed weight
+        settings.free_gradients = vec![false, true, true];
+        settings.fixed_weights[2] = Some(1.);
+        assert_eq!(settings.nb_params(&gradients), 4);
+
+        settings.fit_offset = true;
+        settings.fit_ramp = true;
+        assert_eq!(settings.nb_params(&gradients), 6);
     }
 
-    pub fn fit_disp(&self) -> Result<Vec<f32>, SolverError> {
-        // Profile weights can range from 0. to 1000.
-        let lower_bounds = DVector::repeat(self.profiles_regul.len(), 0.0_f32);
-        let upper_bounds = DVector::repeat(self.profiles_regul.len(), 1000.0_f32);
-        // First guess with all weights at 1.
-        let first_guess = DVector::repeat(self.profiles_regul.len(), 1.0_f32);
-        // Objective function definition
-        let f = |v: DVector<f32>| self.objective_function(v);
-        dbg!(&first_guess);
+    #[test]
+    fn test_project_on_section() {
+        let dem = Dem1D {
+            x: vec![0., 10., 20., 30., 40.],
+            surface: Surface1D::new(vec![40., 30., 20., 10., 0.]),
+        };
+        let section = Orientation::from_deg(90., 90.).unwrap();
+        let los = Orientation::from_deg(100., 40.).unwrap();
 
-        // Solver call
-        let solution = ParticleSwarm::new(f, lower_bounds, upper_bounds).solve(first_guess);
+        // Displacement of 2 parallel to the 45° slope, recorded in the line of sight
+        let mut truth = Vector2Rep::from_deg(-45., true);
+        truth.with_norm(2.);
+        let los3: Vector3Rep = los.clone().into();
+        let in_los =
+            Vector3Rep::from_vertical_section_rad(&truth, section.azimuth).inner_product(&los3);
+        let mut data = DispData::new(vec![5., 15., 25.], vec![in_los; 3]).unwrap();
 
-        // Propagate the error or convert to vector
-        match solution {
-            Err(e) => Err(e),
-            Ok(matrix) => Ok(matrix.data.as_vec().to_vec()),
+        data.project_on_section(&dem, None, &section, &los, 0.1)
+            .unwrap();
+        assert!(data.ill_conditioned.iter().all(|flag| !flag));
+        for vec in data.projected_vecs.iter() {
+            assert_approx_eq!(vec.coords().0, truth.coords().0, 1e-4);
+            assert_approx_eq!(vec.coords().1, truth.coords().1, 1e-4);
         }
+
+        // A line of sight perpendicular to the slope is blind to the displacement
+        let blind = Orientation::from_deg(270., 45.).unwrap();
+        data.project_on_section(&dem, None, &section, &blind, 0.1)
+            .unwrap();
+        assert!(data.ill_conditioned.iter().all(|flag| *flag));
+        assert!(data.projected_vecs.iter().all(|vec| vec.amplitude() == 0.));
     }
 }
@@ -0,0 +1,268 @@
+//! Comparison of the model with sparse displacement data.
+//!
+//! An InSAR point is the average displacement over a pixel, so the model can be averaged
+//! over the footprint of each point instead of being sampled at its center. Dense and noisy
+//! data can also be gathered into bins along the section, each bin being summarised by the
+//! median of its points and their spread.
+
+use super::vec_proj::{Vector2Rep, Vector3Rep};
+use crate::profile::interpol_linear;
+use crate::types::*;
+
+/// Scale factor of the median absolute deviation to the standard deviation of a gaussian
+const MAD_TO_STD: f32 = 1.4826;
+
+/// Ratio of the standard error of the median to the one of the mean for gaussian data
+const MEDIAN_EFFICIENCY: f32 = 1.2533;
+
+impl DispProfile {
+    /// Projected amplitude in the line of sight averaged over the footprint of each point
+    ///
+    /// The footprint of a point is centered on its x and as wide as its `footprint`, `los`
+    /// gives the line of sight of each point. The profile is linear between its origins, so
+    /// the average is exact. Points with a null footprint take the value at their center.
+    /// The origins of the profile must be sorted along x.
+    pub fn footprint_amplitude_onto(
+        &self,
+        los: &[Vector3Rep],
+        section: &Orientation,
+        x: &[f32],
+        footprint: &[f32],
+    ) -> Vec<f32> {
+        debug_assert_eq!(x.len(), footprint.len());
+        debug_assert_eq!(x.len(), los.len());
+        let profile_x: Vec<f32> = self.origins.iter().map(|o| o[0]).collect();
+        let (vx, vz): (Vec<f32>, Vec<f32>) = self.vecs.iter().map(|v| v.coords()).unzip();
+        x.iter()
+            .zip(footprint.iter())
+            .zip(los.iter())
+            .map(|((x, width), los)| {
+                let (start, end) = (x - width / 2., x + width / 2.);
+                // The projection is linear, so the vector components are averaged first
+                let averaged = Vector2Rep::new(
+                    linear_average(&profile_x, &vx, start, end),
+                    linear_average(&profile_x, &vz, start, end),
+                );
+                Vector3Rep::from_vertical_section_rad(&averaged, section.azimuth).inner_product(los)
+            })
+            .collect()
+    }
+}
+
+/// Average of the linear interpolation of `values` between `start` and `end`
+fn linear_average(x: &[f32], values: &[f32], start: f32, end: f32) -> f32 {
+    let mut nodes = vec![start];
+    nodes.extend(x.iter().filter(|x| **x > start && **x < end));
+    nodes.push(end);
+    let interpolated = interpol_linear(x, values, &nodes);
+    if end <= start {
+        return interpolated[0];
+    }
+    let integral = (1..nodes.len()).fold(0., |acc, k| {
+        acc + (nodes[k] - nodes[k - 1]) * (interpolated[k] + interpolated[k - 1]) / 2.
+    });
+    integral / (end - start)
+}
+
+/// Displacement data gathered into bins along the section
+#[derive(Debug, Default, Clone)]
+pub struct DispBins {
+    /// Median x of the points of each bin
+    pub x: Vec<f32>,
+    /// Median amplitude of each bin
+    pub median: Vec<f32>,
+    /// Spread of the amplitude in each bin, the scaled median absolute deviation
+    pub spread: Vec<f32>,
+    /// Number of points in each bin
+    pub count: Vec<usize>,
+    /// Mean line of sight of the points of each bin, None if the geometry is constant
+    pub los: Option<Vec<Vector3Rep>>,
+}
+
+impl DispBins {
+    /// Standard error of the median of each bin
+    ///
+    /// Bins whose spread cannot be estimated, with a single point or identical values,
+    /// take the largest standard error of the other bins. None if no bin has a spread.
+    pub fn std_errors(&self) -> Option<Vec<f32>> {
+        let errors: Vec<f32> = self
+            .spread
+            .iter()
+            .zip(self.count.iter())
+            .map(|(s, n)| MEDIAN_EFFICIENCY * s / (*n as f32).sqrt())
+            .collect();
+        let largest = errors.iter().copied().fold(0., f32::max);
+        if largest <= 0. {
+            return None;
+        }
+        Some(
+            errors
+                .into_iter()
+                .map(|e| if e > 0. { e } else { largest })
+                .collect(),
+        )
+    }
+
+    /// Displacement data at the bins, weighted by the standard error of their median
+    pub fn to_disp_data(&self) -> Result<DispData, VectorInputError> {
+        let mut data = DispData::new(self.x.clone(), self.median.clone())?;
+        if let Some(los) = &self.los {
+            data = data.with_los(los.clone())?;
+        }
+        match self.std_errors() {
+            Some(sigma) => data.with_sigma(sigma),
+            None => Ok(data),
+        }
+    }
+}
+
+impl DispData {
+    /// Gather the points into bins of `width` along the section, starting at the first x
+    ///
+    /// Empty bins are skipped, bins with less than `min_count` points are discarded. The
+    /// line of sight of a bin is the mean of the ones of its points.
+    ///
+    /// # Errors
+    /// * the width is not strictly positive
+    /// * no bin holds enough points
+    pub fn bin(&self, width: f32, min_count: usize) -> Result<DispBins, VectorInputError> {
+        if !(width.is_finite() && width > 0.) {
+            return Err(VectorInputError::InvalidBinWidth);
+        }
+        let start = self.x.iter().copied().fold(f32::INFINITY, f32::min);
+        let mut points: Vec<(usize, f32, f32, usize)> = self
+            .x
+            .iter()
+            .zip(self.amplitude.iter())
+            .enumerate()
+            .map(|(k, (x, a))| (((x - start) / width).floor() as usize, *x, *a, k))
+            .collect();
+        points.sort_by_key(|p| p.0);
+
+        let mut bins = DispBins {
+            los: self.los.as_ref().map(|_| vec![]),
+            ..DispBins::default()
+        };
+        for group in points.chunk_by(|p, q| p.0 == q.0) {
+            if group.len() < min_count.max(1) {
+                continue;
+            }
+            let x: Vec<f32> = group.iter().map(|p| p.1).collect();
+            let amplitude: Vec<f32> = group.iter().map(|p| p.2).collect();
+            let median_amplitude = median(&amplitude);
+            let deviations: Vec<f32> = amplitude
+                .iter()
+                .map(|a| (a - median_amplitude).abs())
+                .collect();
+            bins.x.push(median(&x));
+            bins.median.push(median_amplitude);
+            bins.spread.push(MAD_TO_STD * median(&deviations));
+            bins.count.push(group.len());
+            if let (Some(bins_los), Some(los)) = (&mut bins.los, &self.los) {
+                let mean = group.iter().fold(Vector3Rep::default(), |acc, p| {
+                    let (x, y, z) = los[p.3].coords();
+                    let (ax, ay, az) = acc.coords();
+                    Vector3Rep::new(ax + x, ay + y, az + z)
+                });
+                bins_los.push(*mean.clone().unit());
+            }
+        }
+        if bins.x.is_empty() {
+            return Err(VectorInputError::EmptyVecs);
+        }
+        Ok(bins)
+    }
+}
+
+/// Median of non empty values
+pub(crate) fn median(values: &[f32]) -> f32 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.
+    } else {
+        sorted[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_linear_average() {
+        let x = vec![0., 10., 20.];
+        let values = vec![0., 10., 0.];
+        // Symmetric footprint around the peak
+        assert_approx_eq!(linear_average(&x, &values, 5., 15.), 7.5);
+        // Footprint on a linear part, average equals the center value
+        assert_approx_eq!(linear_average(&x, &values, 2., 6.), 4.);
+        // Null footprint
+        assert_approx_eq!(linear_average(&x, &values, 10., 10.), 10.);
+    }
+
+    #[test]
+    fn test_footprint_amplitude() {
+        let section = Orientation::from_deg(90., 90.).unwrap();
+        let los = Orientation::from_deg(90., 90.).unwrap();
+        // Horizontal displacement peaking in the middle of the profile
+        let profile = DispProfile::new(
+            vec![
+                Vector2Rep::new(0., 0.),
+                Vector2Rep::new(1., 0.),
+                Vector2Rep::new(0., 0.),
+            ],
+            vec![[0., 0.], [10., 0.], [20., 0.]],
+        )
+        .unwrap();
+        let los: Vec<Vector3Rep> = vec![los.into()];
+        let center = profile.footprint_amplitude_onto(&los, &section, &[10.], &[0.]);
+        let averaged = profile.footprint_amplitude_onto(&los, &section, &[10.], &[10.]);
+        assert_approx_eq!(center[0].abs(), 1., 1e-5);
+        assert_approx_eq!(averaged[0].abs(), 0.75, 1e-5);
+    }
+
+    #[test]
+    fn test_bin() {
+        let data = DispData::new(
+            vec![0., 1., 2., 3., 10., 11., 12., 25.],
+            vec![1., 2., 100., 3., -1., -2., -3., 5.],
+        )
+        .unwrap();
+        let bins = data.bin(5., 1).unwrap();
+        assert_eq!(bins.count, vec![4, 3, 1]);
+        assert_eq!(bins.x, vec![1.5, 11., 25.]);
+        // The outlier barely moves the median
+        assert_eq!(bins.median, vec![2.5, -2., 5.]);
+        assert_approx_eq!(bins.spread[0], MAD_TO_STD);
+        assert_eq!(bins.spread[2], 0.);
+
+        let binned = bins.to_disp_data().unwrap();
+        let sigma = binned.sigma.unwrap();
+        assert_approx_eq!(sigma[0], MEDIAN_EFFICIENCY * MAD_TO_STD / 2.);
+        assert_eq!(sigma[2], sigma.iter().copied().fold(0., f32::max));
+
+        // Single points are discarded with a minimum count
+        assert_eq!(data.bin(5., 2).unwrap().count, vec![4, 3]);
+        assert!(matches!(
+            data.bin(0., 1),
+            Err(VectorInputError::InvalidBinWidth)
+        ));
+
+        // The line of sight of a bin is the mean of the ones of its points
+        let data = DispData::new(vec![0., 1.], vec![1., 1.])
+            .unwrap()
+            .with_los(vec![
+                Vector3Rep::new(1., 0., 0.),
+                Vector3Rep::new(0., 1., 0.),
+            ])
+            .unwrap();
+        let los = data.bin(5., 1).unwrap().los.unwrap();
+        let (x, y, z) = los[0].coords();
+        assert_approx_eq!(x, 0.5_f32.sqrt());
+        assert_approx_eq!(y, 0.5_f32.sqrt());
+        assert_eq!(z, 0.);
+    }
+}
@@ -0,0 +1,124 @@
+//! Sensitivity of a line of sight to the displacement along the section.
+//!
+//! A satellite only records the component of the displacement along its line of sight. Where
+//! the local movement is almost perpendicular to it, the data carry no information on the
+//! model whatever their quality, and these blind zones can be located before calibrating.
+
+use super::vec_proj::Vector3Rep;
+use crate::types::*;
+
+/// Projection factor of the local movement direction onto a line of sight
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LosSensitivity {
+    /// Sampling points of the DEM
+    pub x: Vec<f32>,
+    /// Projection of the unit movement direction onto the line of sight, between -1 and 1
+    ///
+    /// Null where the profile has no displacement
+    pub factor: Vec<f32>,
+    /// True where the profile moves but the factor is lower than the threshold in absolute value
+    pub blind: Vec<bool>,
+}
+
+impl LosSensitivity {
+    /// Fraction of the moving points that are blind
+    pub fn blind_ratio(&self) -> f32 {
+        let nb_moving = self
+            .factor
+            .iter()
+            .zip(self.blind.iter())
+            .filter(|(f, b)| **f != 0. || **b)
+            .count();
+        let nb_blind = self.blind.iter().filter(|b| **b).count();
+        if nb_moving == 0 {
+            return 0.;
+        }
+        nb_blind as f32 / nb_moving as f32
+    }
+}
+
+impl DispProfile {
+    /// Sensitivity of a line of sight to the movement direction of the profile at each
+    /// sampling point of the DEM
+    ///
+    /// Points where the absolute factor is lower than `min_sensitivity` are flagged as blind.
+    ///
+    /// # Errors
+    /// * the profile is not sampled on the DEM
+    pub fn los_sensitivity(
+        &self,
+        dem: &Dem1D,
+        section: &Orientation,
+        los: &Orientation,
+        min_sensitivity: f32,
+    ) -> Result<LosSensitivity, VectorInputError> {
+        if self.vecs.len() != dem.x.len() {
+            return Err(VectorInputError::InconsistentLen {
+                vec1: self.vecs.len(),
+                vec2: dem.x.len(),
+            });
+        }
+        let los3: Vector3Rep = los.to_owned().into();
+        let mut sensitivity = LosSensitivity {
+            x: dem.x.clone(),
+            factor: Vec::with_capacity(dem.x.len()),
+            blind: Vec::with_capacity(dem.x.len()),
+        };
+        for vec in self.vecs.iter() {
+            if vec.amplitude() == 0. {
+                sensitivity.factor.push(0.);
+                sensitivity.blind.push(false);
+                continue;
+            }
+            let mut direction = vec.to_owned();
+            direction.unit();
+            let factor = Vector3Rep::from_vertical_section_rad(&direction, section.azimuth)
+                .inner_product(&los3);
+            sensitivity.factor.push(factor);
+            sensitivity.blind.push(factor.abs() < min_sensitivity);
+        }
+        Ok(sensitivity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::vec_proj::Vector2Rep;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_los_sensitivity() {
+        let dem = Dem1D {
+            x: vec![0., 10., 20.],
+            surface: Surface1D::new(vec![20., 10., 0.]),
+        };
+        let section = Orientation::from_deg(90., 90.).unwrap();
+        // Looking straight down
+        let los = Orientation::from_deg(90., 0.).unwrap();
+        let profile = DispProfile::new(
+            vec![
+                Vector2Rep::new(0., -3.),
+                Vector2Rep::new(2., 0.),
+                Vector2Rep::new(0., 0.),
+            ],
+            vec![[0., 20.], [10., 10.], [20., 0.]],
+        )
+        .unwrap();
+        let sensitivity = profile.los_sensitivity(&dem, &section, &los, 0.2).unwrap();
+        // Vertical movement fully seen, horizontal movement unseen
+        assert_approx_eq!(sensitivity.factor[0].abs(), 1., 1e-5);
+        assert_approx_eq!(sensitivity.factor[1], 0., 1e-5);
+        assert_eq!(sensitivity.factor[2], 0.);
+        assert_eq!(sensitivity.blind, vec![false, true, false]);
+        assert_approx_eq!(sensitivity.blind_ratio(), 0.5);
+
+        let short_dem = Dem1D {
+            x: vec![0., 10.],
+            surface: Surface1D::new(vec![20., 10.]),
+        };
+        assert!(profile
+            .los_sensitivity(&short_dem, &section, &los, 0.2)
+            .is_err());
+    }
+}
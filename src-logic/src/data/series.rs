@@ -0,0 +1,180 @@
+//! Calibration of the surface weights at each epoch of a displacement time series.
+//!
+//! The model is calibrated independently against the displacement of each acquisition
+//! date, so that the evolution of the weights shows which failure surface accelerates.
+
+use super::CalibrationSettings;
+use crate::types::*;
+
+/// Weights of the surfaces of a model calibrated at each epoch of a time series
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WeightSeries {
+    /// Label of each acquisition date
+    pub dates: Vec<String>,
+    /// Calibrated weight of each surface, one vector per date
+    pub weights: Vec<Vec<f32>>,
+    /// RMSE of the calibrated model at each date
+    pub misfits: Vec<f32>,
+}
+
+impl WeightSeries {
+    /// Time of each date in decimal years
+    ///
+    /// Falls back to the index of the dates if one of them cannot be parsed.
+    pub fn times(&self) -> Vec<f32> {
+        let years: Option<Vec<f32>> = self.dates.iter().map(|d| decimal_year(d)).collect();
+        years.unwrap_or_else(|| (0..self.dates.len()).map(|k| k as f32).collect())
+    }
+
+    /// Evolution of the weight of a surface over the dates
+    pub fn surface_weights(&self, surface: usize) -> Vec<f32> {
+        self.weights.iter().map(|w| w[surface]).collect()
+    }
+}
+
+/// Calibrate the weights of the surfaces against each epoch of a time series
+///
+/// Gradients calibrated by the settings only serve the fit of each epoch, the weights
+/// alone are kept.
+///
+/// # Errors
+/// * the calibration of an epoch fails
+#[allow(clippy::too_many_arguments)]
+pub fn calibrate_series(
+    dem: &Dem1D,
+    surfaces: &Vec<Surface1D>,
+    boundaries: &Vec<[usize; 2]>,
+    gradient: &Vec<Vec<(usize, f32)>>,
+    los: &Orientation,
+    series: &DispSeries,
+    section_orientation: &Orientation,
+    settings: &CalibrationSettings,
+) -> Result<WeightSeries, VectorInputError> {
+    let mut weights = Vec::with_capacity(series.nb_epochs());
+    let mut misfits = Vec::with_capacity(series.nb_epochs());
+    for epoch in 0..series.nb_epochs() {
+        let (_, calibration) = DispProfile::from_solver(
+            dem,
+            surfaces,
+            boundaries,
+            gradient,
+            &[(los.to_owned(), series.epoch(epoch))],
+            section_orientation,
+            settings,
+        )?;
+        weights.push(calibration.weights);
+        misfits.push(calibration.misfits[0]);
+    }
+    Ok(WeightSeries {
+        dates: series.dates.clone(),
+        weights,
+        misfits,
+    })
+}
+
+/// Convert a date written as `YYYY-MM-DD`, `YYYY/MM/DD` or `YYYYMMDD` into decimal years
+pub fn decimal_year(date: &str) -> Option<f32> {
+    let date = date.trim();
+    let (year, month, day) = if date.len() == 8 && date.chars().all(|c| c.is_ascii_digit()) {
+        (&date[0..4], &date[4..6], &date[6..8])
+    } else {
+        let mut parts = date.split(['-', '/']);
+        let parsed = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+        parsed
+    };
+    let year: i32 = year.parse().ok()?;
+    let month: usize = month.parse().ok()?;
+    let day: u32 = day.parse().ok()?;
+
+    let is_leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let mut month_days = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    if is_leap {
+        month_days[1] = 29;
+    }
+    if !(1..=12).contains(&month) || day == 0 || day > month_days[month - 1] {
+        return None;
+    }
+    let day_of_year: u32 = month_days[..month - 1].iter().sum::<u32>() + day - 1;
+    let year_days = if is_leap { 366. } else { 365. };
+    Some(year as f32 + day_of_year as f32 / year_days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Solver;
+    use crate::profile::test_fitter::{synthetic_model, synthetic_truth};
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_decimal_year() {
+        assert_approx_eq!(decimal_year("2020-01-01").unwrap(), 2020.);
+        assert_approx_eq!(decimal_year("20200702").unwrap(), 2020.5);
+        assert_approx_eq!(decimal_year("2021/07/02").unwrap(), 2021. + 182. / 365.);
+        assert!(decimal_year("2021-02-29").is_none());
+        assert!(decimal_year("epoch 1").is_none());
+
+        let series = WeightSeries {
+            dates: vec!["first".to_string(), "second".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(series.times(), vec![0., 1.]);
+    }
+
+    #[test]
+    fn test_calibrate_series() {
+        let section_orientation = Orientation::from_deg(260., 90.).unwrap();
+        let los_orientation = Orientation::from_deg(286., 35.).unwrap();
+        let (dem, surfaces, boundaries, _) = synthetic_model();
+
+        // The first surface moves steadily while the second one accelerates
+        let true_weights = [[0., 0.], [1., 0.1], [2., 0.4], [3., 1.6]];
+        let data_x = vec![3., 5., 7., 9., 11., 13., 15.];
+        let amplitudes = true_weights
+            .iter()
+            .map(|w| {
+                synthetic_truth(
+                    &dem,
+                    &surfaces,
+                    &boundaries,
+                    &vec![vec![], vec![]],
+                    &w.to_vec(),
+                    &data_x,
+                )
+                .projected_amplitude_onto(los_orientation.clone(), section_orientation.clone())
+            })
+            .collect();
+        let dates = ["2020-01-01", "2020-04-01", "2020-07-01", "2020-10-01"]
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        let series = DispSeries::new(data_x, dates, amplitudes).unwrap();
+
+        let weight_series = calibrate_series(
+            &dem,
+            &surfaces,
+            &boundaries,
+            &vec![vec![], vec![]],
+            &los_orientation,
+            &series,
+            &section_orientation,
+            &CalibrationSettings {
+                solver: Solver::Linear,
+                ..CalibrationSettings::new(2)
+            },
+        )
+        .unwrap();
+        assert_eq!(weight_series.dates.len(), 4);
+        for surface in 0..2 {
+            let weights = weight_series.surface_weights(surface);
+            for (weight, expect) in weights.iter().zip(true_weights.iter()) {
+                assert_approx_eq!(weight, expect[surface], 1e-3);
+            }
+        }
+        assert!(weight_series.misfits.iter().all(|m| *m < 1e-3));
+        assert_approx_eq!(weight_series.times()[2], 2020. + 182. / 366.);
+    }
+}
@@ -0,0 +1,283 @@
+//! Uncertainty of the calibrated surface weights.
+//!
+//! The covariance of the weights is estimated from the linearised problem, using the
+//! residual variance and the design matrix, or from a residual bootstrap where synthetic
+//! data sets are built by resampling the residuals of the best fit.
+
+use nalgebra::{DMatrix, DVector};
+use std::ops::Range;
+
+/// Uncertainty of the weights of a calibrated model
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WeightUncertainty {
+    /// Covariance matrix of the weights, one row per weight
+    pub covariance: Vec<Vec<f32>>,
+    /// Standard error of each weight
+    pub std_errors: Vec<f32>,
+    /// Lower and upper bounds of the 95% confidence interval of each weight
+    pub confidence_95: Vec<[f32; 2]>,
+    /// Number of bootstrap resamples used, 0 if estimated from the linearised problem
+    pub bootstrap_samples: usize,
+}
+
+impl WeightUncertainty {
+    /// Estimate the uncertainty from the linearised problem A.w = b
+    ///
+    /// The first `nb_data` rows hold the data points, the following ones the penalty rows
+    /// which constrain the weights without being observations. The `nb_nuisance` nuisance
+    /// terms projected out of the problem are also fitted parameters.
+    ///
+    /// None if there is not more data points than fitted parameters
+    pub(crate) fn linearised(
+        design: &DMatrix<f32>,
+        observed: &DVector<f32>,
+        weights: &DVector<f32>,
+        nb_data: usize,
+        nb_nuisance: usize,
+    ) -> Option<Self> {
+        let nb_weights = design.ncols();
+        if nb_data <= nb_weights + nb_nuisance {
+            return None;
+        }
+        let dof = nb_data - nb_weights - nb_nuisance;
+        let residuals = (observed - design * weights).rows(0, nb_data).into_owned();
+        let variance = residuals.norm_squared() / dof as f32;
+
+        let normal = design.transpose() * design;
+        let eps = 1e-7 * normal.norm();
+        let inverse = normal.pseudo_inverse(eps).ok()?;
+        let covariance = inverse * variance;
+
+        let t = student_t_975(dof);
+        let std_errors: Vec<f32> = (0..nb_weights)
+            .map(|k| covariance[(k, k)].max(0.).sqrt())
+            .collect();
+        let confidence_95 = (0..nb_weights)
+            .map(|k| {
+                [
+                    weights[k] - t * std_errors[k],
+                    weights[k] + t * std_errors[k],
+                ]
+            })
+            .collect();
+
+        Some(WeightUncertainty {
+            covariance: matrix_to_rows(&covariance),
+            std_errors,
+            confidence_95,
+            bootstrap_samples: 0,
+        })
+    }
+
+    /// Estimate the uncertainty from the weights fitted on bootstrap resamples
+    ///
+    /// None if less than two resamples are given
+    pub(crate) fn from_samples(samples: &[Vec<f32>]) -> Option<Self> {
+        if samples.len() < 2 {
+            return None;
+        }
+        let nb_samples = samples.len();
+        let nb_weights = samples[0].len();
+        let mean: Vec<f32> = (0..nb_weights)
+            .map(|k| samples.iter().map(|s| s[k]).sum::<f32>() / nb_samples as f32)
+            .collect();
+
+        let mut covariance = vec![vec![0.; nb_weights]; nb_weights];
+        for (i, row) in covariance.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = samples
+                    .iter()
+                    .map(|s| (s[i] - mean[i]) * (s[j] - mean[j]))
+                    .sum::<f32>()
+                    / (nb_samples - 1) as f32;
+            }
+        }
+
+        let std_errors = (0..nb_weights).map(|k| covariance[k][k].sqrt()).collect();
+        let confidence_95 = (0..nb_weights)
+            .map(|k| {
+                let mut values: Vec<f32> = samples.iter().map(|s| s[k]).collect();
+                values.sort_by(|a, b| a.total_cmp(b));
+                [percentile(&values, 0.025), percentile(&values, 0.975)]
+            })
+            .collect();
+
+        Some(WeightUncertainty {
+            covariance,
+            std_errors,
+            confidence_95,
+            bootstrap_samples: nb_samples,
+        })
+    }
+}
+
+impl WeightUncertainty {
+    /// Expand the uncertainty of the free weights to all the weights
+    ///
+    /// `free` holds the index of each free weight among all `weights`, the other
+    /// weights were fixed and have no uncertainty.
+    pub(crate) fn expand(self, free: &[usize], weights: &[f32]) -> Self {
+        let nb_weights = weights.len();
+        let mut covariance = vec![vec![0.; nb_weights]; nb_weights];
+        let mut std_errors = vec![0.; nb_weights];
+        let mut confidence_95: Vec<[f32; 2]> = weights.iter().map(|w| [*w, *w]).collect();
+        for (i, k) in free.iter().enumerate() {
+            for (j, l) in free.iter().enumerate() {
+                covariance[*k][*l] = self.covariance[i][j];
+            }
+            std_errors[*k] = self.std_errors[i];
+            confidence_95[*k] = self.confidence_95[i];
+        }
+        WeightUncertainty {
+            covariance,
+            std_errors,
+            confidence_95,
+            bootstrap_samples: self.bootstrap_samples,
+        }
+    }
+}
+
+/// Build a new observation vector by resampling the residuals of the fit with replacement
+///
+/// Residuals are only drawn within their own group of rows, so that data sets with
+/// different noise levels are not mixed.
+pub(crate) fn resample_residuals(
+    fitted: &DVector<f32>,
+    residuals: &DVector<f32>,
+    groups: &[Range<usize>],
+    rng: &mut XorShift,
+) -> DVector<f32> {
+    let mut resampled = fitted.clone();
+    for rows in groups {
+        for k in rows.clone() {
+            resampled[k] += residuals[rows.start + rng.next_index(rows.len())];
+        }
+    }
+    resampled
+}
+
+/// Small deterministic pseudo random generator so that the bootstrap is reproducible
+#[derive(Debug, Clone)]
+pub(crate) struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub(crate) fn new(seed: u64) -> Self {
+        XorShift { state: seed.max(1) }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Random index in [0, len[
+    pub(crate) fn next_index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+/// Linear interpolation of the percentile of sorted values
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    let position = p * (sorted.len() - 1) as f32;
+    let low = position.floor() as usize;
+    let high = position.ceil() as usize;
+    sorted[low] + (sorted[high] - sorted[low]) * (position - low as f32)
+}
+
+fn matrix_to_rows(matrix: &DMatrix<f32>) -> Vec<Vec<f32>> {
+    (0..matrix.nrows())
+        .map(|i| (0..matrix.ncols()).map(|j| matrix[(i, j)]).collect())
+        .collect()
+}
+
+/// Two-sided 95% quantile of the Student t distribution
+fn student_t_975(dof: usize) -> f32 {
+    const TABLE: [f32; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+    match dof {
+        0 => f32::INFINITY,
+        d if d <= TABLE.len() => TABLE[d - 1],
+        d => {
+            // Cornish-Fisher expansion around the normal quantile
+            let z: f32 = 1.959964;
+            let n = d as f32;
+            z + (z.powi(3) + z) / (4. * n)
+                + (5. * z.powi(5) + 16. * z.powi(3) + 3. * z) / (96. * n * n)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_linearised_straight_line() {
+        // Fit of y = a.x on noisy data, variance of a is s2 / sum(x2)
+        let x = [1., 2., 3., 4., 5.];
+        let y = [1.1, 1.9, 3.2, 3.9, 5.1];
+        let design = DMatrix::from_column_slice(5, 1, &x);
+        let observed = DVector::from_column_slice(&y);
+        let sxy: f32 = x.iter().zip(y.iter()).map(|(a, b)| a * b).sum();
+        let sxx: f32 = x.iter().map(|a| a * a).sum();
+        let slope = sxy / sxx;
+        let ssr: f32 = x
+            .iter()
+            .zip(y.iter())
+            .map(|(a, b)| (b - slope * a) * (b - slope * a))
+            .sum();
+
+        let weights = DVector::from_vec(vec![slope]);
+        let result = WeightUncertainty::linearised(&design, &observed, &weights, 5, 0).unwrap();
+        let expect_se = (ssr / 4. / sxx).sqrt();
+        assert_approx_eq!(result.std_errors[0], expect_se, 1e-5);
+        assert_approx_eq!(result.confidence_95[0][0], slope - 2.776 * expect_se, 1e-4);
+        assert_approx_eq!(result.confidence_95[0][1], slope + 2.776 * expect_se, 1e-4);
+
+        // A null penalty row is not a data point
+        let penalized = design.clone().insert_row(5, 0.);
+        let penalized_observed = observed.clone().insert_row(5, 0.);
+        let result =
+            WeightUncertainty::linearised(&penalized, &penalized_observed, &weights, 5, 0).unwrap();
+        assert_approx_eq!(result.std_errors[0], expect_se, 1e-5);
+        assert!(
+            WeightUncertainty::linearised(&penalized, &penalized_observed, &weights, 1, 0)
+                .is_none()
+        );
+
+        // Two nuisance terms leave 2 degrees of freedom
+        let result = WeightUncertainty::linearised(&design, &observed, &weights, 5, 2).unwrap();
+        assert_approx_eq!(result.std_errors[0], (ssr / 2. / sxx).sqrt(), 1e-5);
+        assert_approx_eq!(
+            result.confidence_95[0][1],
+            slope + 4.303 * (ssr / 2. / sxx).sqrt(),
+            1e-4
+        );
+        assert!(WeightUncertainty::linearised(&design, &observed, &weights, 5, 4).is_none());
+    }
+
+    #[test]
+    fn test_from_samples() {
+        let samples = vec![vec![1., 2.], vec![2., 4.], vec![3., 6.]];
+        let result = WeightUncertainty::from_samples(&samples).unwrap();
+        assert_approx_eq!(result.covariance[0][0], 1.);
+        assert_approx_eq!(result.covariance[0][1], 2.);
+        assert_approx_eq!(result.covariance[1][1], 4.);
+        assert_approx_eq!(result.std_errors[1], 2.);
+        assert_eq!(result.bootstrap_samples, 3);
+    }
+
+    #[test]
+    fn test_student_quantile_continuity() {
+        assert!(student_t_975(31) < student_t_975(30));
+        assert_approx_eq!(student_t_975(120), 1.980, 1e-3);
+    }
+}
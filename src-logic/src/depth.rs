@@ -0,0 +1,220 @@
+//! Depth of a surface below the DEM.
+//!
+//! Failure surfaces are often given as depths below the ground rather than as elevations.
+//! The depth is measured either vertically, or perpendicular to the DEM along its downward
+//! normal, and is converted to and from elevations with the project's DEM.
+
+use crate::profile::interpol_linear;
+use crate::slide::slope1d;
+use crate::types::*;
+
+/// Direction in which the depth below the DEM is measured
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DepthMode {
+    /// Vertically below the DEM at each point
+    #[default]
+    Vertical,
+    /// Perpendicular to the DEM, along its downward normal at each point
+    Normal,
+}
+
+impl DepthMode {
+    /// All the available depth modes
+    pub const ALL: [DepthMode; 2] = [DepthMode::Vertical, DepthMode::Normal];
+
+    /// Short name of the depth mode
+    pub fn name(&self) -> &'static str {
+        match self {
+            DepthMode::Vertical => "Vertical",
+            DepthMode::Normal => "Normal to the DEM",
+        }
+    }
+}
+
+/// Downward unit normal of the DEM at each sampling point
+fn dem_normals(dem: &Dem1D) -> Vec<[f32; 2]> {
+    if dem.x.len() < 2 {
+        return vec![[0., -1.]; dem.x.len()];
+    }
+    slope1d(&dem.x, &dem.surface.z)
+        .iter()
+        .map(|angle| [angle.sin(), -angle.cos()])
+        .collect()
+}
+
+impl Surface1D {
+    /// Construct a surface from its depths below the DEM at each sampling point
+    ///
+    /// With a normal depth, the points below the DEM are interpolated back on the DEM
+    /// sampling, and those falling outside of the DEM are ignored.
+    ///
+    /// # Errors
+    /// * the length of the depths and of the DEM differ
+    /// * a depth is negative or not finite
+    pub fn from_depths(
+        depths: &[f32],
+        dem: &Dem1D,
+        mode: DepthMode,
+    ) -> Result<Self, VectorInputError> {
+        if depths.len() != dem.x.len() {
+            return Err(VectorInputError::InconsistentLen {
+                vec1: depths.len(),
+                vec2: dem.x.len(),
+            });
+        }
+        if depths.iter().any(|d| !(d.is_finite() && *d >= 0.)) {
+            return Err(VectorInputError::InvalidDepth);
+        }
+        let vertical = match mode {
+            DepthMode::Vertical => depths.to_vec(),
+            DepthMode::Normal => {
+                let normals = dem_normals(dem);
+                let (first, last) = (dem.x[0], dem.x[dem.x.len() - 1]);
+                let mut points: Vec<(f32, f32)> = (0..depths.len())
+                    .map(|k| {
+                        (
+                            dem.x[k] + depths[k] * normals[k][0],
+                            dem.surface.z[k] + depths[k] * normals[k][1],
+                        )
+                    })
+                    .filter(|(x, _)| *x >= first && *x <= last)
+                    .collect();
+                points.sort_by(|a, b| a.0.total_cmp(&b.0));
+                points.dedup_by(|a, b| a.0 == b.0);
+                if points.is_empty() {
+                    // Planar approximation on each point
+                    depths
+                        .iter()
+                        .zip(normals.iter())
+                        .map(|(d, n)| d / -n[1])
+                        .collect()
+                } else {
+                    let (x, z): (Vec<f32>, Vec<f32>) = points.into_iter().unzip();
+                    let ground = dem.interpolate_elevation_on_x(&x);
+                    let vertical: Vec<f32> =
+                        ground.iter().zip(z.iter()).map(|(g, z)| g - z).collect();
+                    interpol_linear(&x, &vertical, &dem.x)
+                }
+            }
+        };
+        let z = dem
+            .surface
+            .z
+            .iter()
+            .zip(vertical.iter())
+            .map(|(ground, d)| ground - d)
+            .collect();
+        Ok(Surface1D::new(z))
+    }
+
+    /// Construct a surface on the DEM sampling from depths sampled at other positions
+    ///
+    /// Outside the range of the samples, the depth is null and the surface falls back to
+    /// the DEM elevation.
+    ///
+    /// # Errors
+    /// * the length of the x and depths vectors differ
+    /// * there are less than two samples, or their x are not finite or duplicated
+    /// * a depth is negative or not finite
+    pub fn from_depth_samples(
+        x: &[f32],
+        depths: &[f32],
+        dem: &Dem1D,
+        method: SurfaceInterpolation,
+        mode: DepthMode,
+    ) -> Result<Self, VectorInputError> {
+        let zeros = vec![0.; dem.x.len()];
+        let depths = interpol_samples(x, depths, &dem.x, &zeros, method)?;
+        Surface1D::from_depths(&depths, dem, mode)
+    }
+
+    /// Depth of the surface below the DEM at each sampling point
+    ///
+    /// The normal depth is the distance from the DEM to the first crossing of the surface
+    /// along the downward normal. Where the normal leaves the section without crossing the
+    /// surface, or where the surface is above the DEM, the vertical depth is projected on
+    /// the normal instead.
+    pub fn depths(&self, dem: &Dem1D, mode: DepthMode) -> Vec<f32> {
+        let vertical: Vec<f32> = dem
+            .surface
+            .z
+            .iter()
+            .zip(self.z.iter())
+            .map(|(ground, z)| ground - z)
+            .collect();
+        if mode == DepthMode::Vertical {
+            return vertical;
+        }
+        let normals = dem_normals(dem);
+        let cross = |a: [f32; 2], b: [f32; 2]| a[0] * b[1] - a[1] * b[0];
+        (0..dem.x.len())
+            .map(|k| {
+                let fallback = vertical[k] * -normals[k][1];
+                if vertical[k] <= 0. {
+                    return fallback;
+                }
+                let origin = [dem.x[k], dem.surface.z[k]];
+                let normal = normals[k];
+                let mut depth: Option<f32> = None;
+                for i in 0..(dem.x.len() - 1) {
+                    let a = [dem.x[i], self.z[i]];
+                    let edge = [dem.x[i + 1] - a[0], self.z[i + 1] - a[1]];
+                    let denom = cross(normal, edge);
+                    if denom.abs() < f32::EPSILON {
+                        continue;
+                    }
+                    let to_a = [a[0] - origin[0], a[1] - origin[1]];
+                    let t = cross(to_a, edge) / denom;
+                    let u = cross(to_a, normal) / denom;
+                    if t >= 0. && (-1e-6..=1. + 1e-6).contains(&u) {
+                        depth = Some(depth.map_or(t, |d| d.min(t)));
+                    }
+                }
+                depth.unwrap_or(fallback)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_surface_depths() {
+        // Planar slope with a parallel surface 10 m below
+        let x: Vec<f32> = (0..=10).map(|k| 10. * k as f32).collect();
+        let dem = Dem1D::new(x.clone(), x.iter().map(|x| 0.5 * x).collect()).unwrap();
+        let surface = Surface1D::new(x.iter().map(|x| 0.5 * x - 10.).collect());
+        let cos = 1. / 1.25_f32.sqrt();
+
+        for depth in surface.depths(&dem, DepthMode::Vertical) {
+            assert_approx_eq!(depth, 10., 1e-4);
+        }
+        let normal = surface.depths(&dem, DepthMode::Normal);
+        for depth in normal.iter() {
+            assert_approx_eq!(depth, 10. * cos, 1e-3);
+        }
+
+        let rebuilt = Surface1D::from_depths(&normal, &dem, DepthMode::Normal).unwrap();
+        for (z, expected) in rebuilt.z.iter().zip(surface.z.iter()) {
+            assert_approx_eq!(z, expected, 1e-3);
+        }
+        assert!(Surface1D::from_depths(&[-1.; 11], &dem, DepthMode::Vertical).is_err());
+        assert!(Surface1D::from_depths(&[1.; 3], &dem, DepthMode::Vertical).is_err());
+
+        // Depth samples on part of the section only
+        let rebuilt = Surface1D::from_depth_samples(
+            &[20., 60.],
+            &[4., 8.],
+            &dem,
+            SurfaceInterpolation::Linear,
+            DepthMode::Vertical,
+        )
+        .unwrap();
+        assert_approx_eq!(rebuilt.z[1], dem.surface.z[1]);
+        assert_approx_eq!(rebuilt.z[4], dem.surface.z[4] - 6.);
+        assert_approx_eq!(rebuilt.z[8], dem.surface.z[8]);
+    }
+}
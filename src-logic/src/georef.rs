@@ -0,0 +1,151 @@
+//! Georeference of the section in projected map coordinates.
+//!
+//! The section is the straight line from a start point to an end point given as easting /
+//! northing. The x of the section is the distance from the start point along this line,
+//! which relates every object of the section to real-world coordinates.
+
+use crate::types::Orientation;
+use std::f64::consts::PI;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SectionLineError {
+    #[error("The start and end points of the section are the same")]
+    DegenerateLine,
+    #[error("The coordinates of the section are not finite")]
+    InvalidCoordinates,
+}
+
+/// Straight section line between two points in projected map coordinates
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SectionLine {
+    /// Easting and northing of the start point, where x is null
+    pub start: [f64; 2],
+    /// Easting and northing of the end point
+    pub end: [f64; 2],
+    /// Label of the coordinate reference system, informative only
+    pub crs: Option<String>,
+}
+
+impl SectionLine {
+    /// Construct a section line from its start and end points
+    ///
+    /// # Errors
+    /// * a coordinate is not finite
+    /// * the start and end points are the same
+    pub fn new(
+        start: [f64; 2],
+        end: [f64; 2],
+        crs: Option<String>,
+    ) -> Result<Self, SectionLineError> {
+        if !start.iter().chain(end.iter()).all(|c| c.is_finite()) {
+            return Err(SectionLineError::InvalidCoordinates);
+        }
+        if start == end {
+            return Err(SectionLineError::DegenerateLine);
+        }
+        Ok(SectionLine { start, end, crs })
+    }
+
+    /// Length of the line in map units
+    pub fn length(&self) -> f64 {
+        let (de, dn) = self.delta();
+        (de * de + dn * dn).sqrt()
+    }
+
+    /// Clockwise angle in radians between the North and the line, in [0, 2π)
+    pub fn azimuth(&self) -> f32 {
+        let (de, dn) = self.delta();
+        let azimuth = de.atan2(dn).rem_euclid(2. * PI) as f32;
+        // Rounding can give 2π for azimuths just below it
+        if azimuth >= 2. * std::f32::consts::PI {
+            0.
+        } else {
+            azimuth
+        }
+    }
+
+    /// Orientation of the vertical section along the line
+    pub fn orientation(&self) -> Orientation {
+        Orientation {
+            azimuth: self.azimuth(),
+            incidence: std::f32::consts::PI / 2.,
+        }
+    }
+
+    /// Easting and northing of the point at `x` along the section
+    pub fn point_at(&self, x: f32) -> [f64; 2] {
+        let (ue, un) = self.unit();
+        [self.start[0] + ue * x as f64, self.start[1] + un * x as f64]
+    }
+
+    /// Position along the section of the projection of a map point, and its signed distance
+    /// to the line, positive on the right of the line
+    pub fn locate(&self, point: [f64; 2]) -> (f32, f32) {
+        let (ue, un) = self.unit();
+        let (de, dn) = (point[0] - self.start[0], point[1] - self.start[1]);
+        ((de * ue + dn * un) as f32, (de * un - dn * ue) as f32)
+    }
+
+    /// Regular positions along the section from the start to the end point
+    ///
+    /// The last position is the end point if the length is not a multiple of `spacing`.
+    /// None if the spacing is not strictly positive.
+    pub fn sample_x(&self, spacing: f32) -> Option<Vec<f32>> {
+        if !(spacing.is_finite() && spacing > 0.) {
+            return None;
+        }
+        let length = self.length() as f32;
+        let nb_steps = (length / spacing).floor() as usize;
+        let mut x: Vec<f32> = (0..=nb_steps).map(|k| k as f32 * spacing).collect();
+        if length - x[nb_steps] > spacing * 1e-3 {
+            x.push(length);
+        }
+        Some(x)
+    }
+
+    fn delta(&self) -> (f64, f64) {
+        (self.end[0] - self.start[0], self.end[1] - self.start[1])
+    }
+
+    fn unit(&self) -> (f64, f64) {
+        let (de, dn) = self.delta();
+        let length = self.length();
+        (de / length, dn / length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_section_line() {
+        // Toward the south-east
+        let line = SectionLine::new([1000., 5000.], [1300., 4600.], None).unwrap();
+        assert_approx_eq!(line.length(), 500.);
+        assert_approx_eq!(
+            line.azimuth(),
+            (180. - (0.75_f32).atan().to_degrees()).to_radians(),
+            1e-6
+        );
+
+        let point = line.point_at(250.);
+        assert_approx_eq!(point[0], 1150.);
+        assert_approx_eq!(point[1], 4800.);
+        let (x, offset) = line.locate([1150. + 8., 4800. + 6.]);
+        assert_approx_eq!(x, 250., 1e-3);
+        // On the left of a line heading south-east
+        assert_approx_eq!(offset, -10., 1e-3);
+
+        assert_eq!(line.sample_x(200.).unwrap(), vec![0., 200., 400., 500.]);
+        assert_eq!(line.sample_x(250.).unwrap(), vec![0., 250., 500.]);
+        assert!(line.sample_x(0.).is_none());
+
+        assert!(SectionLine::new([0., 0.], [0., 0.], None).is_err());
+        // Due north
+        let line = SectionLine::new([0., 0.], [0., 10.], None).unwrap();
+        assert_eq!(line.azimuth(), 0.);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::data::vec_proj::Vector2Rep;
+use crate::data::vec_proj::{deg2rad, Vector2Rep, Vector3Rep};
 use crate::types::*;
 use anyhow::anyhow;
 use anyhow::bail;
@@ -22,6 +22,15 @@ enum CsvReadError {
 
 impl CsvReader {
     pub fn read(file_path: String, delimiter: Option<u8>) -> Result<Self> {
+        Self::read_fields(file_path, delimiter, false)
+    }
+
+    /// Read a file where empty fields are missing values, read as NaN
+    pub fn read_with_missing(file_path: String, delimiter: Option<u8>) -> Result<Self> {
+        Self::read_fields(file_path, delimiter, true)
+    }
+
+    fn read_fields(file_path: String, delimiter: Option<u8>, missing: bool) -> Result<Self> {
         let delimiter = match delimiter {
             Some(d) => d,
             _ => b';',
@@ -43,7 +52,11 @@ impl CsvReader {
         for j in reader.records() {
             let j = j?;
             for k in 0..j.len() {
-                let value = j[k].parse::<f32>()?;
+                let value = if missing && j[k].trim().is_empty() {
+                    f32::NAN
+                } else {
+                    j[k].parse::<f32>()?
+                };
                 data[k].push(value);
             }
         }
@@ -72,6 +85,7 @@ impl CsvReader {
 pub struct CsvWriter {
     pub headers: Vec<String>,
     datas: Vec<Vec<f32>>,
+    precise_datas: Vec<Vec<f64>>,
 }
 
 impl CsvWriter {
@@ -79,10 +93,28 @@ impl CsvWriter {
         if datas.len() != headers.len() {
             bail!("Datas and headers dos not match.");
         }
-        let csv_writer = CsvWriter { headers, datas };
+        let csv_writer = CsvWriter {
+            headers,
+            datas,
+            precise_datas: vec![],
+        };
         Ok(csv_writer)
     }
 
+    /// Append columns written in double precision, such as map coordinates
+    pub fn with_precise_datas(
+        mut self,
+        datas: Vec<Vec<f64>>,
+        headers: Vec<String>,
+    ) -> Result<Self> {
+        if datas.len() != headers.len() {
+            bail!("Datas and headers dos not match.");
+        }
+        self.headers.extend(headers);
+        self.precise_datas.extend(datas);
+        Ok(self)
+    }
+
     pub fn write(self, file_path: &String, delimiter: Option<u8>) -> Result<()> {
         let delimiter = match delimiter {
             Some(d) => d,
@@ -102,6 +134,9 @@ impl CsvWriter {
             for d in 0..self.datas.len() {
                 line.push(self.datas[d][k].to_string());
             }
+            for d in 0..self.precise_datas.len() {
+                line.push(self.precise_datas[d][k].to_string());
+            }
             writer.write_record(line)?;
         }
         writer.flush()?;
@@ -231,10 +266,17 @@ impl DispProfile {
 }
 
 impl DispData {
+    /// Read displacement data, with optional standard deviation and footprint columns
+    ///
+    /// If no sigma header is given, the columns `sigma` then `std` are looked for,
+    /// and the data are considered without uncertainty if none of them exists.
+    /// The width of the pixels is read from a `footprint` or `pixel_size` column if any, and
+    /// the line of sight of each point from the columns described in `los_from_csv_reader`.
     pub fn from_csv_reader(
         csv_reader: &CsvReader,
         x_header: &mut String,
         amp_header: &mut String,
+        sigma_header: &mut String,
     ) -> Result<Self> {
         let x_header = if x_header.is_empty() {
             &String::from("x")
@@ -246,14 +288,114 @@ impl DispData {
         } else {
             amp_header
         };
+        let sigma_header = if sigma_header.is_empty() {
+            ["sigma", "std"]
+                .iter()
+                .map(|h| h.to_string())
+                .find(|h| csv_reader.headers.contains(h))
+        } else {
+            Some(sigma_header.to_owned())
+        };
 
         let x_data = csv_reader.get_data(x_header)?;
         let amp_data = csv_reader.get_data(amp_header)?;
 
         if x_data.len() != amp_data.len() {
-            Err(anyhow!(FromCsvError::DataInconsistentLen))
+            return Err(anyhow!(FromCsvError::DataInconsistentLen));
+        }
+        let mut disp_data = DispData::new(x_data, amp_data)?;
+        if let Some(header) = ["footprint", "pixel_size"]
+            .iter()
+            .map(|h| h.to_string())
+            .find(|h| csv_reader.headers.contains(h))
+        {
+            disp_data = disp_data.with_footprint(csv_reader.get_data(&header)?)?;
+        }
+        if let Some(los) = los_from_csv_reader(csv_reader)? {
+            disp_data = disp_data.with_los(los)?;
+        }
+        match sigma_header {
+            Some(header) => Ok(disp_data.with_sigma(csv_reader.get_data(&header)?)?),
+            None => Ok(disp_data),
+        }
+    }
+}
+
+/// Read the line of sight of each point, if the geometry varies along the section
+///
+/// The unit vector is read from the `los_e`, `los_n`, `los_u` columns, from the satellite to
+/// the ground or reversed if pointing upward. Otherwise the `incidence` and `heading` columns, in degrees,
+/// give the geometry of a right-looking sensor whose look direction is the heading plus 90°.
+/// None if neither set of columns exists.
+fn los_from_csv_reader(csv_reader: &CsvReader) -> Result<Option<Vec<Vector3Rep>>> {
+    let has_headers = |headers: &[&str]| {
+        headers
+            .iter()
+            .all(|h| csv_reader.headers.contains(&h.to_string()))
+    };
+    if has_headers(&["los_e", "los_n", "los_u"]) {
+        let east = csv_reader.get_data(&"los_e".to_string())?;
+        let north = csv_reader.get_data(&"los_n".to_string())?;
+        let up = csv_reader.get_data(&"los_u".to_string())?;
+        Ok(Some(
+            (0..east.len())
+                .map(|k| Vector3Rep::new(north[k], east[k], up[k]))
+                .collect(),
+        ))
+    } else if has_headers(&["incidence", "heading"]) {
+        let incidence = csv_reader.get_data(&"incidence".to_string())?;
+        let heading = csv_reader.get_data(&"heading".to_string())?;
+        let mut los = Vec::with_capacity(incidence.len());
+        for (incidence, heading) in incidence.iter().zip(heading.iter()) {
+            let orientation =
+                Orientation::from_heading(deg2rad(*heading), deg2rad(*incidence), LookSide::Right)?;
+            los.push(orientation.into());
+        }
+        Ok(Some(los))
+    } else {
+        Ok(None)
+    }
+}
+
+impl DispSeries {
+    /// Read a displacement time series from a wide csv, with one column per date
+    ///
+    /// Every column other than the x and sigma columns is an acquisition date, named by its
+    /// header, in the order of the file. If no sigma header is given, the columns `sigma`
+    /// then `std` are looked for, and the data are considered without uncertainty if none
+    /// of them exists.
+    pub fn from_csv_reader(
+        csv_reader: &CsvReader,
+        x_header: &mut String,
+        sigma_header: &mut String,
+    ) -> Result<Self> {
+        let x_header = if x_header.is_empty() {
+            &String::from("x")
         } else {
-            Ok(DispData::new(x_data, amp_data)?)
+            x_header
+        };
+        let sigma_header = if sigma_header.is_empty() {
+            ["sigma", "std"]
+                .iter()
+                .map(|h| h.to_string())
+                .find(|h| csv_reader.headers.contains(h))
+        } else {
+            Some(sigma_header.to_owned())
+        };
+
+        let x_data = csv_reader.get_data(x_header)?;
+        let dates: Vec<String> = csv_reader
+            .headers
+            .iter()
+            .filter(|h| *h != x_header && Some(*h) != sigma_header.as_ref())
+            .cloned()
+            .collect();
+        let amplitudes = csv_reader.get_datas(&dates)?;
+
+        let series = DispSeries::new(x_data, dates, amplitudes)?;
+        match sigma_header {
+            Some(header) => Ok(series.with_sigma(csv_reader.get_data(&header)?)?),
+            None => Ok(series),
         }
     }
 }
@@ -294,4 +436,159 @@ mod tests {
             assert_eq!(z[k], get_z[k]);
         }
     }
+
+    #[test]
+    fn test_reading_missing() {
+        let path = std::env::temp_dir()
+            .join("sldm_missing.csv")
+            .display()
+            .to_string();
+        std::fs::write(&path, "x;z\n0;1\n1;\n2; \n3;4\n").unwrap();
+        assert!(CsvReader::read(path.clone(), None).is_err());
+
+        let reader = CsvReader::read_with_missing(path, None).unwrap();
+        let z = reader.get_data(&"z".to_string()).unwrap();
+        assert_eq!(z[0], 1.);
+        assert!(z[1].is_nan() && z[2].is_nan());
+        assert_eq!(z[3], 4.);
+    }
+
+    #[test]
+    fn test_disp_data_sigma() {
+        let path = std::env::temp_dir()
+            .join("sldm_disp_sigma.csv")
+            .display()
+            .to_string();
+        let writer = CsvWriter::from_datas_headers(
+            vec![
+                vec![0., 1., 2.],
+                vec![-1., -2., -3.],
+                vec![0.5, 0.5, 1.],
+                vec![20., 20., 20.],
+            ],
+            ["x", "disp", "std", "pixel_size"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+        )
+        .unwrap();
+        writer.write(&path, None).unwrap();
+        let reader = CsvReader::read(path, None).unwrap();
+
+        let data = DispData::from_csv_reader(
+            &reader,
+            &mut String::new(),
+            &mut String::new(),
+            &mut String::new(),
+        )
+        .unwrap();
+        assert_eq!(data.sigma, Some(vec![0.5, 0.5, 1.]));
+        assert_eq!(data.footprint, Some(vec![20., 20., 20.]));
+        assert!(data.los.is_none());
+
+        let wrong_header = DispData::from_csv_reader(
+            &reader,
+            &mut String::new(),
+            &mut String::new(),
+            &mut String::from("sigma"),
+        );
+        assert!(wrong_header.is_err());
+    }
+
+    #[test]
+    fn test_disp_data_los() {
+        let path = std::env::temp_dir()
+            .join("sldm_disp_los.csv")
+            .display()
+            .to_string();
+        let writer = CsvWriter::from_datas_headers(
+            vec![
+                vec![0., 1.],
+                vec![-1., -2.],
+                vec![0., 0.6],
+                vec![0., 0.],
+                vec![-2., -0.8],
+                vec![30., 40.],
+                vec![190., 190.],
+            ],
+            [
+                "x",
+                "disp",
+                "los_e",
+                "los_n",
+                "los_u",
+                "incidence",
+                "heading",
+            ]
+            .iter()
+            .map(|h| h.to_string())
+            .collect(),
+        )
+        .unwrap();
+        writer.write(&path, None).unwrap();
+        let reader = CsvReader::read(path, None).unwrap();
+
+        // The unit vectors take precedence over the angles, and are normalized
+        let data = DispData::from_csv_reader(
+            &reader,
+            &mut String::new(),
+            &mut String::new(),
+            &mut String::new(),
+        )
+        .unwrap();
+        let los = data.los.unwrap();
+        assert_eq!(los[0].coords(), (0., 0., -1.));
+        assert_eq!(los[1].coords(), (0., 0.6, -0.8));
+
+        // Vectors toward the satellite are reversed
+        let data = DispData::new(vec![0.], vec![1.])
+            .unwrap()
+            .with_los(vec![Vector3Rep::new(0., -0.6, 0.8)])
+            .unwrap();
+        assert_eq!(data.los.unwrap()[0].coords(), (0., 0.6, -0.8));
+
+        // Look direction of a descending track toward the west
+        let los = los_from_csv_reader(&CsvReader {
+            headers: vec!["incidence".to_string(), "heading".to_string()],
+            data: vec![vec![30.], vec![190.]],
+        })
+        .unwrap()
+        .unwrap();
+        let (north, east, up) = los[0].coords();
+        assert!(east < 0. && north.abs() < east.abs());
+        assert!((up + 30_f32.to_radians().cos()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_disp_series() {
+        let path = std::env::temp_dir()
+            .join("sldm_disp_series.csv")
+            .display()
+            .to_string();
+        let writer = CsvWriter::from_datas_headers(
+            vec![
+                vec![0., 1., 2.],
+                vec![0.5, 0.5, 1.],
+                vec![0., 0., 0.],
+                vec![-1., -2., -3.],
+                vec![-2., -4., -6.],
+            ],
+            ["x", "sigma", "2020-01-01", "2020-07-01", "2021-01-01"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+        )
+        .unwrap();
+        writer.write(&path, None).unwrap();
+        let reader = CsvReader::read(path, None).unwrap();
+
+        let series =
+            DispSeries::from_csv_reader(&reader, &mut String::new(), &mut String::new()).unwrap();
+        assert_eq!(series.dates, vec!["2020-01-01", "2020-07-01", "2021-01-01"]);
+        assert_eq!(series.nb_epochs(), 3);
+        assert_eq!(series.sigma, Some(vec![0.5, 0.5, 1.]));
+        let epoch = series.epoch(2);
+        assert_eq!(epoch.amplitude, vec![-2., -4., -6.]);
+        assert_eq!(epoch.sigma, series.sigma);
+    }
 }
@@ -0,0 +1,368 @@
+//! Elevation rasters sampled along a section line.
+//!
+//! A raster is a regular grid of elevations located in projected map coordinates by an affine
+//! transform, read from a GeoTIFF or an ESRI ASCII grid. Sampling it along a [`SectionLine`]
+//! gives the DEM of the section without preparing the profile in another GIS tool.
+
+use crate::georef::SectionLine;
+use crate::types::*;
+use anyhow::{anyhow, bail, Result};
+use std::fs::File;
+use thiserror::Error;
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::tags::Tag;
+use tiff::ColorType;
+
+/// GeoTIFF key giving whether the raster coordinates refer to the corner or the center of
+/// the pixels
+const GT_RASTER_TYPE_GEO_KEY: u16 = 1025;
+const RASTER_PIXEL_IS_POINT: u16 = 2;
+
+#[derive(Debug, Error)]
+enum RasterError {
+    #[error("The number of values ({values}) does not match the raster size ({width}x{height})")]
+    InconsistentSize {
+        values: usize,
+        width: usize,
+        height: usize,
+    },
+    #[error("The raster transform is not invertible")]
+    InvalidTransform,
+    #[error("The raster should have a single band")]
+    NotSingleBand,
+    #[error("The raster has no georeference")]
+    NoGeoreference,
+    #[error("Invalid ESRI ASCII header: {0}")]
+    InvalidHeader(String),
+    #[error("The spacing along the section must be strictly positive")]
+    InvalidSpacing,
+    #[error("{0} points of the section are outside the raster or have no data")]
+    MissingValues(usize),
+}
+
+/// Single band elevation grid in projected map coordinates
+#[derive(Debug, Default, Clone)]
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    /// Affine transform from the pixel centers to map coordinates, as
+    /// `[e0, de/dcol, de/drow, n0, dn/dcol, dn/drow]` where `(e0, n0)` is the center of the
+    /// top left pixel
+    pub transform: [f64; 6],
+    /// Row major values, starting from the top left pixel
+    pub values: Vec<f32>,
+    /// Value of the pixels without data
+    pub nodata: Option<f32>,
+}
+
+impl Raster {
+    /// Construct a raster from its values and its transform
+    ///
+    /// # Errors
+    /// * the raster is empty or the number of values does not match its size
+    /// * the transform is not invertible
+    pub fn new(
+        width: usize,
+        height: usize,
+        transform: [f64; 6],
+        values: Vec<f32>,
+        nodata: Option<f32>,
+    ) -> Result<Self> {
+        if width == 0 || height == 0 || values.len() != width * height {
+            bail!(RasterError::InconsistentSize {
+                values: values.len(),
+                width,
+                height
+            });
+        }
+        let det = transform[1] * transform[5] - transform[2] * transform[4];
+        if !(det.is_finite() && det != 0.) {
+            bail!(RasterError::InvalidTransform);
+        }
+        Ok(Raster {
+            width,
+            height,
+            transform,
+            values,
+            nodata,
+        })
+    }
+
+    /// Read a single band GeoTIFF
+    ///
+    /// The georeference is read from the model transformation tag, or else from the tie point
+    /// and pixel scale tags. The no data value is read from the GDAL tag if present.
+    pub fn from_geotiff(file_path: String) -> Result<Self> {
+        let mut decoder = Decoder::new(File::open(file_path)?)?;
+        let (width, height) = decoder.dimensions()?;
+        if !matches!(decoder.colortype()?, ColorType::Gray(_)) {
+            bail!(RasterError::NotSingleBand);
+        }
+
+        // Transform from the raster space, where (0, 0) is the corner of the top left pixel
+        // unless the pixels are points
+        let mut transform = match decoder.find_tag(Tag::ModelTransformationTag)? {
+            Some(value) => {
+                let m = value.into_f64_vec()?;
+                if m.len() < 8 {
+                    bail!(RasterError::NoGeoreference);
+                }
+                [m[3], m[0], m[1], m[7], m[4], m[5]]
+            }
+            None => {
+                let tie_point = decoder
+                    .find_tag(Tag::ModelTiepointTag)?
+                    .ok_or(anyhow!(RasterError::NoGeoreference))?
+                    .into_f64_vec()?;
+                let scale = decoder
+                    .find_tag(Tag::ModelPixelScaleTag)?
+                    .ok_or(anyhow!(RasterError::NoGeoreference))?
+                    .into_f64_vec()?;
+                if tie_point.len() < 6 || scale.len() < 2 {
+                    bail!(RasterError::NoGeoreference);
+                }
+                [
+                    tie_point[3] - tie_point[0] * scale[0],
+                    scale[0],
+                    0.,
+                    tie_point[4] + tie_point[1] * scale[1],
+                    0.,
+                    -scale[1],
+                ]
+            }
+        };
+        let pixel_is_point = decoder
+            .find_tag_unsigned_vec::<u16>(Tag::GeoKeyDirectoryTag)?
+            .map(|keys| {
+                keys.chunks_exact(4).skip(1).any(|key| {
+                    key[0] == GT_RASTER_TYPE_GEO_KEY
+                        && key[1] == 0
+                        && key[3] == RASTER_PIXEL_IS_POINT
+                })
+            })
+            .unwrap_or(false);
+        if !pixel_is_point {
+            transform[0] += 0.5 * (transform[1] + transform[2]);
+            transform[3] += 0.5 * (transform[4] + transform[5]);
+        }
+
+        let nodata = match decoder.find_tag(Tag::GdalNodata)? {
+            Some(value) => value
+                .into_string()?
+                .trim_matches(|c: char| c.is_whitespace() || c == '\0')
+                .parse::<f32>()
+                .ok(),
+            None => None,
+        };
+
+        let values: Vec<f32> = match decoder.read_image()? {
+            DecodingResult::U8(v) => v.into_iter().map(|a| a as f32).collect(),
+            DecodingResult::U16(v) => v.into_iter().map(|a| a as f32).collect(),
+            DecodingResult::U32(v) => v.into_iter().map(|a| a as f32).collect(),
+            DecodingResult::U64(v) => v.into_iter().map(|a| a as f32).collect(),
+            DecodingResult::F32(v) => v,
+            DecodingResult::F64(v) => v.into_iter().map(|a| a as f32).collect(),
+            DecodingResult::I8(v) => v.into_iter().map(|a| a as f32).collect(),
+            DecodingResult::I16(v) => v.into_iter().map(|a| a as f32).collect(),
+            DecodingResult::I32(v) => v.into_iter().map(|a| a as f32).collect(),
+            DecodingResult::I64(v) => v.into_iter().map(|a| a as f32).collect(),
+        };
+
+        Raster::new(width as usize, height as usize, transform, values, nodata)
+    }
+
+    /// Read an ESRI ASCII grid
+    ///
+    /// The header gives `ncols`, `nrows`, `xllcorner` or `xllcenter`, `yllcorner` or
+    /// `yllcenter`, `cellsize` and optionally `nodata_value`, followed by the values from the
+    /// top row.
+    pub fn from_esri_ascii(file_path: String) -> Result<Self> {
+        let content = std::fs::read_to_string(file_path)?;
+        let mut tokens = content.split_whitespace().peekable();
+
+        let mut header = std::collections::HashMap::new();
+        while let Some(key) = tokens.next_if(|t| t.starts_with(|c: char| c.is_ascii_alphabetic())) {
+            let value = tokens
+                .next()
+                .ok_or(anyhow!(RasterError::InvalidHeader(key.to_string())))?
+                .parse::<f64>()
+                .map_err(|_| anyhow!(RasterError::InvalidHeader(key.to_string())))?;
+            header.insert(key.to_lowercase(), value);
+        }
+        let get = |key: &str| {
+            header
+                .get(key)
+                .copied()
+                .ok_or(anyhow!(RasterError::InvalidHeader(key.to_string())))
+        };
+        let width = get("ncols")? as usize;
+        let height = get("nrows")? as usize;
+        let cell_size = get("cellsize")?;
+        // Center of the lower left pixel
+        let (e_ll, n_ll) = match (header.get("xllcenter"), header.get("yllcenter")) {
+            (Some(e), Some(n)) => (*e, *n),
+            _ => (
+                get("xllcorner")? + 0.5 * cell_size,
+                get("yllcorner")? + 0.5 * cell_size,
+            ),
+        };
+        let nodata = header.get("nodata_value").map(|v| *v as f32);
+
+        let values = tokens
+            .map(|t| t.parse::<f32>())
+            .collect::<Result<Vec<f32>, _>>()?;
+        let transform = [
+            e_ll,
+            cell_size,
+            0.,
+            n_ll + (height as f64 - 1.) * cell_size,
+            0.,
+            -cell_size,
+        ];
+        Raster::new(width, height, transform, values, nodata)
+    }
+
+    /// Column and row of a map point, in pixels from the center of the top left pixel
+    fn pixel_of(&self, point: [f64; 2]) -> (f64, f64) {
+        let [e0, a, b, n0, c, d] = self.transform;
+        let (de, dn) = (point[0] - e0, point[1] - n0);
+        let det = a * d - b * c;
+        ((d * de - b * dn) / det, (a * dn - c * de) / det)
+    }
+
+    fn value(&self, col: usize, row: usize) -> Option<f32> {
+        let value = self.values[row * self.width + col];
+        if value.is_nan() || Some(value) == self.nodata {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    /// Bilinear interpolation of the raster at a map point
+    ///
+    /// None if the point is outside the raster or next to a pixel without data.
+    pub fn value_at(&self, point: [f64; 2]) -> Option<f32> {
+        let (col, row) = self.pixel_of(point);
+        let in_range = |p: f64, n: usize| p >= -0.5 && p <= n as f64 - 0.5;
+        if !(in_range(col, self.width) && in_range(row, self.height)) {
+            return None;
+        }
+        let neighbours = |p: f64, n: usize| {
+            let p = p.clamp(0., (n - 1) as f64);
+            let p0 = (p.floor() as usize).min(n - 1);
+            let t = p - p0 as f64;
+            // A neighbour without weight is not read, so that it may have no data
+            let p1 = if t > 0. { (p0 + 1).min(n - 1) } else { p0 };
+            (p0, p1, t as f32)
+        };
+        let (c0, c1, tc) = neighbours(col, self.width);
+        let (r0, r1, tr) = neighbours(row, self.height);
+        let top = self.value(c0, r0)? * (1. - tc) + self.value(c1, r0)? * tc;
+        let bottom = self.value(c0, r1)? * (1. - tc) + self.value(c1, r1)? * tc;
+        Some(top * (1. - tr) + bottom * tr)
+    }
+
+    /// DEM of the section sampled every `spacing` along the line with bilinear interpolation,
+    /// along with the azimuth of the section in radians
+    ///
+    /// # Errors
+    /// * the spacing is not strictly positive
+    /// * a point of the section is outside the raster or has no data
+    pub fn sample_section(&self, line: &SectionLine, spacing: f32) -> Result<(Dem1D, f32)> {
+        let x = line
+            .sample_x(spacing)
+            .ok_or(anyhow!(RasterError::InvalidSpacing))?;
+        let z: Vec<f32> = x
+            .iter()
+            .map(|x| self.value_at(line.point_at(*x)).unwrap_or(f32::NAN))
+            .collect();
+        let nb_missing = z.iter().filter(|z| z.is_nan()).count();
+        if nb_missing > 0 {
+            bail!(RasterError::MissingValues(nb_missing));
+        }
+        Ok((Dem1D::new(x, z)?, line.azimuth()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use tiff::encoder::{colortype, TiffEncoder};
+
+    #[test]
+    fn test_raster_section() {
+        // Plane z = 100 + 0.5 * easting - 0.25 * northing, with 10 m pixels
+        let (width, height) = (6, 5);
+        let mut values = vec![];
+        for row in 0..height {
+            for col in 0..width {
+                let (e, n) = (1005. + 10. * col as f32, 2045. - 10. * row as f32);
+                values.push(100. + 0.5 * e - 0.25 * n);
+            }
+        }
+        values[width * height - 1] = -9999.;
+
+        let path = std::env::temp_dir().join("sldm_test_raster_section.tif");
+        {
+            let mut encoder = TiffEncoder::new(File::create(&path).unwrap()).unwrap();
+            let mut image = encoder
+                .new_image::<colortype::Gray32Float>(width as u32, height as u32)
+                .unwrap();
+            let directory = image.encoder();
+            directory
+                .write_tag(Tag::ModelPixelScaleTag, &[10., 10., 0.][..])
+                .unwrap();
+            directory
+                .write_tag(Tag::ModelTiepointTag, &[0., 0., 0., 1000., 2050., 0.][..])
+                .unwrap();
+            directory.write_tag(Tag::GdalNodata, "-9999").unwrap();
+            image.write_data(&values).unwrap();
+        }
+        let raster = Raster::from_geotiff(path.display().to_string()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(raster.nodata, Some(-9999.));
+        assert_approx_eq!(raster.transform[0], 1005.);
+        assert_approx_eq!(raster.transform[3], 2045.);
+
+        let line = SectionLine::new([1010., 2040.], [1040., 2020.], None).unwrap();
+        let (dem, azimuth) = raster.sample_section(&line, 5.).unwrap();
+        assert_approx_eq!(azimuth, line.azimuth());
+        assert_eq!(dem.x.len(), 9);
+        for (x, z) in dem.x.iter().zip(dem.surface.z.iter()) {
+            let [e, n] = line.point_at(*x);
+            assert_approx_eq!(*z, 100. + 0.5 * e as f32 - 0.25 * n as f32, 1e-3);
+        }
+
+        // The section crosses the pixel without data
+        let line = SectionLine::new([1010., 2040.], [1055., 2005.], None).unwrap();
+        assert!(raster.sample_section(&line, 5.).is_err());
+        assert!(raster.value_at([2000., 2000.]).is_none());
+    }
+
+    #[test]
+    fn test_raster_esri_ascii() {
+        let path = std::env::temp_dir().join("sldm_test_raster_esri_ascii.asc");
+        std::fs::write(
+            &path,
+            "ncols 3\nnrows 2\nxllcorner 100.0\nyllcorner 200.0\ncellsize 10\nNODATA_value -9999\n\
+            1 2 3\n\
+            4 5 -9999\n",
+        )
+        .unwrap();
+        let raster = Raster::from_esri_ascii(path.display().to_string()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((raster.width, raster.height), (3, 2));
+        assert_eq!(raster.nodata, Some(-9999.));
+        // Centers of the top left and bottom left pixels
+        assert_approx_eq!(raster.value_at([105., 215.]).unwrap(), 1.);
+        assert_approx_eq!(raster.value_at([105., 205.]).unwrap(), 4.);
+        assert_approx_eq!(raster.value_at([110., 210.]).unwrap(), 3.);
+        assert!(raster.value_at([125., 205.]).is_none());
+
+        let line = SectionLine::new([105., 215.], [115., 215.], None).unwrap();
+        let (dem, _) = raster.sample_section(&line, 5.).unwrap();
+        assert_eq!(dem.surface.z, vec![1., 1.5, 2.]);
+    }
+}
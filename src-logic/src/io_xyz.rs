@@ -0,0 +1,198 @@
+//! Elevation point clouds projected on a section line.
+//!
+//! Lidar DEMs are often delivered as XYZ points rather than grids. The points close to the
+//! section are projected onto it and gathered into regular bins along the line, each bin
+//! giving one elevation of the DEM.
+
+use crate::georef::SectionLine;
+use crate::types::*;
+use anyhow::{anyhow, bail, Result};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum PointCloudError {
+    #[error("Invalid point at line {0}")]
+    InvalidPoint(usize),
+    #[error("The point cloud is empty")]
+    EmptyCloud,
+    #[error("The spacing along the section must be strictly positive")]
+    InvalidSpacing,
+    #[error("The buffer around the section must be positive")]
+    InvalidBuffer,
+    #[error("{0} bins of the section have no point")]
+    EmptyBins(usize),
+}
+
+/// Aggregation of the elevations of the points gathered in a bin
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PointAggregation {
+    /// Lowest point, which filters the vegetation and buildings left in the cloud
+    Min,
+    #[default]
+    Mean,
+    Max,
+}
+
+impl PointAggregation {
+    /// All the available aggregations
+    pub const ALL: [PointAggregation; 3] = [
+        PointAggregation::Min,
+        PointAggregation::Mean,
+        PointAggregation::Max,
+    ];
+
+    /// Short name of the aggregation
+    pub fn name(&self) -> &'static str {
+        match self {
+            PointAggregation::Min => "Minimum",
+            PointAggregation::Mean => "Mean",
+            PointAggregation::Max => "Maximum",
+        }
+    }
+
+    fn aggregate(&self, z: &[f32]) -> f32 {
+        match self {
+            PointAggregation::Min => z.iter().copied().fold(f32::INFINITY, f32::min),
+            PointAggregation::Mean => z.iter().sum::<f32>() / z.len() as f32,
+            PointAggregation::Max => z.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+        }
+    }
+}
+
+/// Elevation points in projected map coordinates
+#[derive(Debug, Default, Clone)]
+pub struct PointCloud {
+    /// Easting, northing and elevation of each point
+    pub points: Vec<[f64; 3]>,
+}
+
+impl PointCloud {
+    /// Read a XYZ text file
+    ///
+    /// Each line holds the easting, northing and elevation of a point separated by spaces,
+    /// tabulations, commas or semicolons, and any further column is ignored. Empty lines,
+    /// lines starting with `#` and a header on the first line are skipped.
+    pub fn from_xyz(file_path: String) -> Result<Self> {
+        let content = std::fs::read_to_string(file_path)?;
+        let mut points = vec![];
+        for (k, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values = line
+                .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                .filter(|v| !v.is_empty())
+                .take(3)
+                .map(|v| v.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>();
+            match values {
+                Ok(v) if v.len() == 3 => points.push([v[0], v[1], v[2]]),
+                Err(_) if k == 0 => continue,
+                _ => bail!(PointCloudError::InvalidPoint(k + 1)),
+            }
+        }
+        if points.is_empty() {
+            bail!(PointCloudError::EmptyCloud);
+        }
+        Ok(PointCloud { points })
+    }
+
+    /// DEM of the section from the points within `buffer` of the line, binned every `spacing`
+    /// along the line, along with the azimuth of the section in radians
+    ///
+    /// Each sampling point of the section is the center of its bin.
+    ///
+    /// # Errors
+    /// * the spacing is not strictly positive or the buffer is negative
+    /// * a bin has no point
+    pub fn sample_section(
+        &self,
+        line: &SectionLine,
+        spacing: f32,
+        buffer: f32,
+        aggregation: PointAggregation,
+    ) -> Result<(Dem1D, f32)> {
+        let x = line
+            .sample_x(spacing)
+            .ok_or(anyhow!(PointCloudError::InvalidSpacing))?;
+        if !(buffer.is_finite() && buffer >= 0.) {
+            bail!(PointCloudError::InvalidBuffer);
+        }
+
+        let mut bins: Vec<Vec<f32>> = vec![vec![]; x.len()];
+        for [e, n, z] in self.points.iter() {
+            let (along, offset) = line.locate([*e, *n]);
+            if offset.abs() > buffer || !z.is_finite() {
+                continue;
+            }
+            // Nearest sampling point, the last one may be closer than the spacing
+            let k = x.partition_point(|xk| *xk < along);
+            let k = if k == x.len() || (k > 0 && along - x[k - 1] < x[k] - along) {
+                k - 1
+            } else {
+                k
+            };
+            if (along - x[k]).abs() <= spacing / 2. {
+                bins[k].push(*z as f32);
+            }
+        }
+
+        let nb_empty = bins.iter().filter(|b| b.is_empty()).count();
+        if nb_empty > 0 {
+            bail!(PointCloudError::EmptyBins(nb_empty));
+        }
+        let z = bins.iter().map(|b| aggregation.aggregate(b)).collect();
+        Ok((Dem1D::new(x, z)?, line.azimuth()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_point_cloud_section() {
+        let path = std::env::temp_dir().join("sldm_test_point_cloud_section.xyz");
+        let mut content = String::from("x y z\n");
+        // Two points on each side of the line every 2.5 m, and one far point
+        for k in 0..=8 {
+            let e = 100. + 2.5 * k as f64;
+            content += &format!("{} 49 {}\n", e, 10. + k as f64);
+            content += &format!("{} 51.5 {}\n", e, 12. + k as f64);
+        }
+        content += "110 80 1000\n";
+        std::fs::write(&path, content).unwrap();
+        let cloud = PointCloud::from_xyz(path.display().to_string()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(cloud.points.len(), 19);
+
+        // Due east along the northing 50
+        let line = SectionLine::new([100., 50.], [120., 50.], None).unwrap();
+        let (dem, azimuth) = cloud
+            .sample_section(&line, 5., 2., PointAggregation::Min)
+            .unwrap();
+        assert_approx_eq!(azimuth, std::f32::consts::PI / 2.);
+        assert_eq!(dem.x, vec![0., 5., 10., 15., 20.]);
+        // Points halfway between two sampling points go to the next one along the section
+        assert_eq!(dem.surface.z, vec![10., 11., 13., 15., 17.]);
+
+        let (dem, _) = cloud
+            .sample_section(&line, 5., 2., PointAggregation::Max)
+            .unwrap();
+        assert_eq!(dem.surface.z, vec![12., 14., 16., 18., 20.]);
+        let (dem, _) = cloud
+            .sample_section(&line, 5., 2., PointAggregation::Mean)
+            .unwrap();
+        assert_approx_eq!(dem.surface.z[1], 12.5);
+
+        // Too narrow bins leave holes
+        assert!(cloud
+            .sample_section(&line, 1., 2., PointAggregation::Mean)
+            .is_err());
+        assert!(cloud
+            .sample_section(&line, 5., -1., PointAggregation::Mean)
+            .is_err());
+    }
+}
@@ -6,15 +6,39 @@
 //! located on the section.
 
 pub mod data;
+pub mod depth;
+pub mod georef;
 pub mod io_csv;
+pub mod io_raster;
+pub mod io_xyz;
+pub mod mass;
 pub mod profile;
 pub mod slide;
 pub mod types;
+pub mod validation;
 // pub mod plotter;
 
 pub mod prelude {
-    pub use crate::data::rmse;
+    pub use crate::data::decomposition::{DecompositionBasis, LosDecomposition};
+    pub use crate::data::metrics::{residuals, FitMetrics};
+    pub use crate::data::sampling::DispBins;
+    pub use crate::data::sensitivity::LosSensitivity;
+    pub use crate::data::series::{calibrate_series, decimal_year, WeightSeries};
+    pub use crate::data::uncertainty::WeightUncertainty;
     pub use crate::data::vec_proj::{deg2rad, rad2deg, Vector2Rep, Vector3Rep};
+    pub use crate::data::{
+        chi_square, reduced_chi_square, rmse, Calibration, CalibrationSettings, Solver,
+    };
+    pub use crate::depth::DepthMode;
+    pub use crate::georef::SectionLine;
     pub use crate::io_csv::{CsvReader, CsvWriter};
-    pub use crate::types::{Dem1D, DispData, DispProfile, Orientation, Surface1D};
+    pub use crate::io_raster::Raster;
+    pub use crate::io_xyz::{PointAggregation, PointCloud};
+    pub use crate::mass::{LateralExtent, SlidingMass};
+    pub use crate::slide::search::{search_slbl, SlblCandidate, SlblSearch};
+    pub use crate::types::{
+        AngleUnit, AzimuthConvention, Dem1D, DispData, DispProfile, DispSeries, GeometryConvention,
+        LookSide, Orientation, Surface1D, SurfaceInterpolation,
+    };
+    pub use crate::validation::{DemRepair, DemReport};
 }
@@ -0,0 +1,155 @@
+//! Geometry of the sliding mass between the DEM and a failure surface.
+//!
+//! The thickness of the mass, its maximum depth and the area of the section summarize a
+//! surface, and the volume is estimated from the area with the lateral extent of the mass
+//! across the section.
+
+use crate::depth::DepthMode;
+use crate::types::*;
+
+/// Lateral extent of the sliding mass across the section, used to estimate its volume
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LateralExtent {
+    /// Constant width of the mass, which is a prism of the section area
+    Width(f32),
+    /// Ratio between the width of the mass and its length along the section
+    ShapeFactor(f32),
+}
+
+impl Default for LateralExtent {
+    fn default() -> Self {
+        LateralExtent::ShapeFactor(1.)
+    }
+}
+
+impl LateralExtent {
+    /// Short name of the kind of lateral extent
+    pub fn name(&self) -> &'static str {
+        match self {
+            LateralExtent::Width(_) => "Width",
+            LateralExtent::ShapeFactor(_) => "Shape factor",
+        }
+    }
+
+    /// Width or shape factor
+    pub fn value(&self) -> f32 {
+        match self {
+            LateralExtent::Width(v) | LateralExtent::ShapeFactor(v) => *v,
+        }
+    }
+}
+
+/// Thickness, maximum depth and section area of the mass between the DEM and a surface
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SlidingMass {
+    /// Vertical thickness at each sampling point, null where the surface is above the DEM
+    pub vertical_thickness: Vec<f32>,
+    /// Thickness perpendicular to the DEM at each sampling point, null where the surface is
+    /// above the DEM
+    pub normal_thickness: Vec<f32>,
+    /// Maximum vertical thickness
+    pub max_depth: f32,
+    /// Position along the section of the maximum depth
+    pub max_depth_x: f32,
+    /// Length along the section of the parts where the surface is below the DEM
+    pub length: f32,
+    /// Area of the section between the DEM and the surface
+    pub area: f32,
+}
+
+impl SlidingMass {
+    /// Compute the geometry of the mass between the DEM and a surface on its sampling
+    ///
+    /// The area and the length are integrated linearly between the sampling points, up to
+    /// the crossing of the DEM and the surface.
+    ///
+    /// # Errors
+    /// * the length of the surface and of the DEM differ
+    /// * the DEM is empty
+    pub fn new(surface: &Surface1D, dem: &Dem1D) -> Result<Self, VectorInputError> {
+        if surface.z.len() != dem.x.len() {
+            return Err(VectorInputError::InconsistentLen {
+                vec1: surface.z.len(),
+                vec2: dem.x.len(),
+            });
+        }
+        if dem.x.is_empty() {
+            return Err(VectorInputError::EmptyVecs);
+        }
+        let depths = surface.depths(dem, DepthMode::Vertical);
+        let mut mass = SlidingMass {
+            vertical_thickness: depths.iter().map(|d| d.max(0.)).collect(),
+            normal_thickness: surface
+                .depths(dem, DepthMode::Normal)
+                .iter()
+                .map(|d| d.max(0.))
+                .collect(),
+            max_depth_x: dem.x[0],
+            ..Default::default()
+        };
+        for (k, depth) in depths.iter().enumerate() {
+            if *depth > mass.max_depth {
+                mass.max_depth = *depth;
+                mass.max_depth_x = dem.x[k];
+            }
+        }
+        for k in 0..(depths.len() - 1) {
+            let (d0, d1) = (depths[k], depths[k + 1]);
+            let dx = dem.x[k + 1] - dem.x[k];
+            if d0 >= 0. && d1 >= 0. {
+                if d0 > 0. || d1 > 0. {
+                    mass.length += dx;
+                }
+                mass.area += (d0 + d1) / 2. * dx;
+            } else if d0 > 0. || d1 > 0. {
+                // The surface crosses the DEM within the step
+                let (inside, outside) = if d0 > 0. { (d0, d1) } else { (d1, d0) };
+                let ratio = inside / (inside - outside);
+                mass.length += ratio * dx;
+                mass.area += inside * ratio * dx / 2.;
+            }
+        }
+        Ok(mass)
+    }
+
+    /// Volume of the mass from its lateral extent across the section
+    pub fn volume(&self, extent: LateralExtent) -> f32 {
+        match extent {
+            LateralExtent::Width(width) => self.area * width,
+            LateralExtent::ShapeFactor(factor) => self.area * factor * self.length,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_sliding_mass() {
+        let x: Vec<f32> = (0..7).map(|k| 10. * k as f32).collect();
+        let dem = Dem1D::new(x, vec![0.; 7]).unwrap();
+        // The surface crosses the DEM halfway between 30 and 40
+        let surface = Surface1D::new(vec![0., -10., -20., -10., 10., 0., 0.]);
+        let mass = SlidingMass::new(&surface, &dem).unwrap();
+
+        assert_eq!(mass.vertical_thickness, vec![0., 10., 20., 10., 0., 0., 0.]);
+        // Normal to a flat DEM
+        for (normal, vertical) in mass
+            .normal_thickness
+            .iter()
+            .zip(mass.vertical_thickness.iter())
+        {
+            assert_approx_eq!(normal, vertical, 1e-4);
+        }
+        assert_eq!(mass.max_depth, 20.);
+        assert_eq!(mass.max_depth_x, 20.);
+        assert_approx_eq!(mass.length, 35.);
+        assert_approx_eq!(mass.area, 375.);
+
+        assert_approx_eq!(mass.volume(LateralExtent::Width(10.)), 3750.);
+        assert_approx_eq!(mass.volume(LateralExtent::ShapeFactor(0.5)), 375. * 17.5);
+        assert!(SlidingMass::new(&Surface1D::new(vec![0.; 3]), &dem).is_err());
+    }
+}
@@ -99,11 +99,12 @@ pub(super) fn amplitude_gradient(
     target: &Vec<f32>,
     gradient_weights: &Vec<(usize, f32)>,
 ) -> Vec<f32> {
-    let gradient_vector = interpol_linear(
-        &gradient_weights.iter().map(|(a, _)| *a as f32).collect(),
-        &gradient_weights.iter().map(|(_, b)| *b).collect(),
-        &(0..target.len()).map(|i| i as f32).collect(),
-    );
+    let (gradient_x, gradient_amp): (Vec<f32>, Vec<f32>) = gradient_weights
+        .iter()
+        .map(|(a, b)| (*a as f32, *b))
+        .unzip();
+    let target_x: Vec<f32> = (0..target.len()).map(|i| i as f32).collect();
+    let gradient_vector = interpol_linear(&gradient_x, &gradient_amp, &target_x);
     (0..target.len())
         .map(|k| target[k] * gradient_vector[k])
         .collect()
@@ -1,4 +1,8 @@
-use crate::{data::vec_proj::Vector2Rep, data::ComposedModel, types::*};
+use crate::{
+    data::vec_proj::Vector2Rep,
+    data::{Calibration, CalibrationSettings, ComposedModel},
+    types::*,
+};
 pub mod disp;
 use disp::*;
 
@@ -42,8 +46,8 @@ impl DispProfile {
     pub fn interpolate_n_vec(&mut self, n_vec: usize) -> &Self {
         let x_step =
             (self.origins.last().unwrap()[0] - self.origins.first().unwrap()[0]) / (n_vec as f32);
-        let old_x = self.origins.iter().map(|[x, _y]| *x).collect();
-        let old_z = self.origins.iter().map(|[_x, y]| *y).collect();
+        let old_x: Vec<f32> = self.origins.iter().map(|[x, _y]| *x).collect();
+        let old_z: Vec<f32> = self.origins.iter().map(|[_x, y]| *y).collect();
         let new_x: Vec<f32> = (0..n_vec)
             .map(|k| self.origins.last().unwrap()[0] + x_step * (k as f32))
             .collect();
@@ -152,77 +156,128 @@ impl DispProfile {
         DispProfile::new(vecs, regul_origins)
     }
 
+    /// Create a new profile by calibrating the weights of the surfaces against the data
+    ///
+    /// Surfaces whose gradient is calibrated are split into one column per control point,
+    /// each column being the unit profile under the hat function of this control point,
+    /// so that the prediction stays linear in the calibrated parameters.
+    ///
+    /// # Errors
+    /// * the settings are not consistent with the surfaces
+    /// * a calibrated gradient has less than two control points
+    /// * the calibration fails
     pub fn from_solver(
         dem: &Dem1D,
         surfaces: &Vec<Surface1D>,
         boundaries: &Vec<[usize; 2]>,
         gradient: &Vec<Vec<(usize, f32)>>,
-        disp_data: &DispData,
+        los_datasets: &[(Orientation, DispData)],
         section_orientation: &Orientation,
-        los_orientation: &Orientation,
-    ) -> Result<(Self, Vec<f32>), VectorInputError> {
+        settings: &CalibrationSettings,
+    ) -> Result<(Self, Calibration), VectorInputError> {
+        settings.check(surfaces.len())?;
         let regul_origins: Vec<[f32; 2]> = (0..dem.x.len())
             .map(|k| [dem.x[k], dem.surface.z[k]])
             .collect();
 
-        let mut sum_vx = vec![0.; regul_origins.len()];
-        let mut sum_vz = vec![0.; regul_origins.len()];
-
-        let mut updated_profiles = vec![];
+        let mut columns = vec![];
+        let mut column_surfaces = vec![];
+        let mut gradient_columns = vec![];
 
         for surf in 0..surfaces.len() {
             // Create the profile from surface
-            let mut current_unit_profile = DispProfile::from_surface_with_slope(
+            let unit_profile = DispProfile::from_surface_with_slope(
                 &surfaces[surf],
                 dem,
                 boundaries[surf][0],
                 boundaries[surf][1],
             )?;
-            // Apply the gradient to the unit profile
-            if !gradient[surf].is_empty() {
-                current_unit_profile.apply_amplitude_gradient(&gradient[surf]);
+            if settings.calibrates_gradient(surf) {
+                if gradient[surf].len() < 2 {
+                    return Err(VectorInputError::InvalidGradient);
+                }
+                // One column per control point, under its hat function
+                let first_column = columns.len();
+                for point in 0..gradient[surf].len() {
+                    let hat = gradient[surf]
+                        .iter()
+                        .enumerate()
+                        .map(|(k, (index, _))| (*index, if k == point { 1. } else { 0. }))
+                        .collect();
+                    let mut hat_profile = unit_profile.clone();
+                    hat_profile.apply_amplitude_gradient(&hat);
+                    hat_profile.interpolate_on_origins(&regul_origins);
+                    columns.push(hat_profile);
+                    column_surfaces.push(surf);
+                }
+                gradient_columns.push(first_column..columns.len());
+            } else {
+                let mut current_unit_profile = unit_profile;
+                // Apply the gradient to the unit profile
+                if !gradient[surf].is_empty() {
+                    current_unit_profile.apply_amplitude_gradient(&gradient[surf]);
+                }
+                // Interpolate vectors on common regulate origins
+                current_unit_profile.interpolate_on_origins(&regul_origins);
+                columns.push(current_unit_profile);
+                column_surfaces.push(surf);
             }
-            // Interpolate vectors on common regulate origins
-            current_unit_profile.interpolate_on_origins(&regul_origins);
-            updated_profiles.push(current_unit_profile);
         }
 
         let composed_model = ComposedModel::new(
             dem,
-            &updated_profiles,
+            &columns,
             section_orientation,
-            los_orientation,
-            disp_data,
-        );
-        match composed_model.fit_disp() {
-            Ok(weights) => {
-                #[cfg(test)]
-                dbg!(&weights);
-
-                for p in 0..updated_profiles.len() {
-                    updated_profiles[p].weight_disp(weights[p]);
-                    for k in 0..sum_vx.len() {
-                        sum_vx[k] += updated_profiles[p].vecs[k].coords().0;
-                        sum_vz[k] += updated_profiles[p].vecs[k].coords().1;
-                    }
-                }
-
-                let vecs = (0..regul_origins.len())
-                    .map(|k| Vector2Rep::new(sum_vx[k], sum_vz[k]))
-                    .collect();
-
-                match DispProfile::new(vecs, regul_origins) {
-                    Err(e) => Err(e),
-                    Ok(p) => Ok((p, weights)),
-                }
+            los_datasets,
+            settings.footprint_average,
+        )
+        .with_smoothing(&gradient_columns, settings.gradient_smoothing)
+        .with_nuisance(settings.fit_offset, settings.fit_ramp);
+        let calibration = composed_model
+            .calibrate(&settings.expand(&column_surfaces))?
+            .collapse(&column_surfaces, gradient);
+
+        // Sum the weighted columns, hat profiles already hold their calibrated gradient
+        let column_calibration = composed_model_weights(&calibration, &column_surfaces);
+        let mut sum_vx = vec![0.; regul_origins.len()];
+        let mut sum_vz = vec![0.; regul_origins.len()];
+        for (mut profile, weight) in columns.into_iter().zip(column_calibration) {
+            profile.weight_disp(weight);
+            for k in 0..sum_vx.len() {
+                sum_vx[k] += profile.vecs[k].coords().0;
+                sum_vz[k] += profile.vecs[k].coords().1;
             }
-            Err(e) => Err(VectorInputError::SolverError(e)),
         }
+
+        let vecs = (0..regul_origins.len())
+            .map(|k| Vector2Rep::new(sum_vx[k], sum_vz[k]))
+            .collect();
+
+        DispProfile::new(vecs, regul_origins).map(|p| (p, calibration))
     }
 }
 
+/// Weight of each column of a composed model from the calibration of the surfaces
+///
+/// Columns of a calibrated gradient are weighted by the value of their control point.
+fn composed_model_weights(calibration: &Calibration, column_surfaces: &[usize]) -> Vec<f32> {
+    let mut next_point = vec![0; calibration.weights.len()];
+    column_surfaces
+        .iter()
+        .map(|surf| {
+            let weight = calibration.weights[*surf];
+            if column_surfaces.iter().filter(|s| *s == surf).count() > 1 {
+                next_point[*surf] += 1;
+                weight * calibration.gradients[*surf][next_point[*surf] - 1].1
+            } else {
+                weight
+            }
+        })
+        .collect()
+}
+
 /// Direct linear interpolation between an old sampling to a new one
-pub(crate) fn interpol_linear(x_old: &Vec<f32>, y_old: &Vec<f32>, x_new: &Vec<f32>) -> Vec<f32> {
+pub(crate) fn interpol_linear(x_old: &[f32], y_old: &[f32], x_new: &[f32]) -> Vec<f32> {
     let length = x_old.len();
     assert_eq!(x_old.len(), y_old.len());
 
@@ -259,6 +314,70 @@ pub(crate) fn interpol_linear(x_old: &Vec<f32>, y_old: &Vec<f32>, x_new: &Vec<f3
     y_new
 }
 
+/// Monotone piecewise cubic interpolation (Fritsch-Carlson), constant outside the range
+///
+/// Smoother than the linear interpolation, without overshooting the values where the data
+/// are locally monotone. Falls back to the linear interpolation below three points.
+pub(crate) fn interpol_monotone_cubic(x_old: &[f32], y_old: &[f32], x_new: &[f32]) -> Vec<f32> {
+    let length = x_old.len();
+    assert_eq!(x_old.len(), y_old.len());
+    if length < 3 {
+        return interpol_linear(x_old, y_old, x_new);
+    }
+
+    let h: Vec<f32> = (0..length - 1).map(|k| x_old[k + 1] - x_old[k]).collect();
+    let delta: Vec<f32> = (0..length - 1)
+        .map(|k| (y_old[k + 1] - y_old[k]) / h[k])
+        .collect();
+    // Derivatives at the data points, null at the local extrema
+    let mut d = vec![0.; length];
+    for k in 1..length - 1 {
+        if delta[k - 1] * delta[k] > 0. {
+            let w1 = 2. * h[k] + h[k - 1];
+            let w2 = h[k] + 2. * h[k - 1];
+            d[k] = (w1 + w2) / (w1 / delta[k - 1] + w2 / delta[k]);
+        }
+    }
+    d[0] = monotone_end_slope(h[0], h[1], delta[0], delta[1]);
+    d[length - 1] = monotone_end_slope(
+        h[length - 2],
+        h[length - 3],
+        delta[length - 2],
+        delta[length - 3],
+    );
+
+    x_new
+        .iter()
+        .map(|x| {
+            if *x <= x_old[0] {
+                return y_old[0];
+            }
+            if *x >= x_old[length - 1] {
+                return y_old[length - 1];
+            }
+            let k = x_old.partition_point(|xk| xk <= x) - 1;
+            let t = (x - x_old[k]) / h[k];
+            let (t2, t3) = (t * t, t * t * t);
+            (2. * t3 - 3. * t2 + 1.) * y_old[k]
+                + (t3 - 2. * t2 + t) * h[k] * d[k]
+                + (3. * t2 - 2. * t3) * y_old[k + 1]
+                + (t3 - t2) * h[k] * d[k + 1]
+        })
+        .collect()
+}
+
+/// Derivative at an end point from its two adjacent intervals, preserving the monotony
+fn monotone_end_slope(h0: f32, h1: f32, delta0: f32, delta1: f32) -> f32 {
+    let d = ((2. * h0 + h1) * delta0 - h0 * delta1) / (h0 + h1);
+    if d * delta0 <= 0. {
+        0.
+    } else if delta0 * delta1 < 0. && d.abs() > 3. * delta0.abs() {
+        3. * delta0
+    } else {
+        d
+    }
+}
+
 /// Direct local linear interpolation between two points
 fn interpol_linear_local(x1: f32, x2: f32, y1: f32, y2: f32, xn: f32) -> f32 {
     y1 + (xn - x1) * (y2 - y1) / (x2 - x1)
@@ -294,11 +413,70 @@ mod tests {
         println!("result: {:?}\nexpect: {:?}", result, expect);
         (0..result.len()).for_each(|k| assert_approx_eq!(result[k], expect[k]));
     }
+
+    #[test]
+    fn test_monotone_cubic_interp() {
+        // Linear data are kept linear
+        let x: Vec<f32> = vec![0., 1., 3., 4.];
+        let y: Vec<f32> = vec![1., 3., 7., 9.];
+        let result = interpol_monotone_cubic(&x, &y, &vec![-1., 0.5, 2., 3.5, 5.]);
+        let expect: Vec<f32> = vec![1., 2., 5., 8., 9.];
+        (0..result.len()).for_each(|k| assert_approx_eq!(result[k], expect[k], 1e-5));
+
+        // No overshoot around a step
+        let x: Vec<f32> = vec![0., 1., 2., 3., 4.];
+        let y: Vec<f32> = vec![0., 0., 10., 10., 10.];
+        let x_new: Vec<f32> = (0..=40).map(|k| k as f32 / 10.).collect();
+        let result = interpol_monotone_cubic(&x, &y, &x_new);
+        assert!(result.iter().all(|y| (0. ..=10.).contains(y)));
+        assert!(result.windows(2).all(|w| w[1] >= w[0]));
+        assert_approx_eq!(result[15], 5.);
+    }
 }
 
 #[cfg(test)]
-mod test_fitter {
+pub(crate) mod test_fitter {
     use super::*;
+    use crate::data::vec_proj::Vector3Rep;
+    use crate::data::Solver;
+    use assert_approx_eq::assert_approx_eq;
+
+    /// DEM, two surfaces with their slope, their boundaries and the weights used to generate
+    /// the synthetic data of the calibration tests
+    pub(crate) fn synthetic_model() -> (Dem1D, Vec<Surface1D>, Vec<[usize; 2]>, Vec<f32>) {
+        let x: Vec<f32> = (0..=10).map(|k| 2. * k as f32).collect();
+        let dem = Dem1D {
+            x,
+            surface: Surface1D::new(vec![10., 9., 9., 8., 7., 6., 5., 4., 3., 2., 2.]),
+        };
+        let mut surf1 = Surface1D::new(vec![10., 9., 8., 7., 6., 5., 4., 3., 3., 2., 2.]);
+        let mut surf2 = Surface1D::new(vec![10., 9., 7., 5., 4., 3., 3., 3., 3., 2., 2.]);
+        surf1.get_slope(&dem);
+        surf2.get_slope(&dem);
+        (
+            dem,
+            vec![surf1, surf2],
+            vec![[1, 8], [1, 6]],
+            vec![2.5, 0.75],
+        )
+    }
+
+    /// Profile of the surfaces combined with `weights`, interpolated at the data points
+    pub(crate) fn synthetic_truth(
+        dem: &Dem1D,
+        surfaces: &[Surface1D],
+        boundaries: &Vec<[usize; 2]>,
+        gradients: &Vec<Vec<(usize, f32)>>,
+        weights: &Vec<f32>,
+        data_x: &Vec<f32>,
+    ) -> DispProfile {
+        let data_z = dem.interpolate_elevation_on_x(data_x);
+        let mut truth =
+            DispProfile::from_surfaces(dem, &mut surfaces.to_vec(), boundaries, gradients, weights)
+                .unwrap();
+        truth.interpolate_on_origins(&(0..data_x.len()).map(|k| [data_x[k], data_z[k]]).collect());
+        truth
+    }
 
     #[test]
     fn test_fitter() {
@@ -326,9 +504,12 @@ mod test_fitter {
             &mut vec![surf1, surf2],
             &vec![[2, 5], [2, 9]],
             &vec![vec![], vec![]],
-            &disp_data,
+            &[(los_orientation, disp_data)],
             &section_orientation,
-            &los_orientation,
+            &CalibrationSettings {
+                solver: Solver::ParticleSwarm,
+                ..CalibrationSettings::new(2)
+            },
         )
         .unwrap();
         // for k in 0..profile.vecs.len() {
@@ -336,4 +517,352 @@ mod test_fitter {
         //     dbg!(profile.vecs[k].amplitude());
         // }
     }
+
+    #[test]
+    fn test_fitter_linear() {
+        let section_orientation = Orientation::from_deg(260., 90.).unwrap();
+        let los_orientation = Orientation::from_deg(286., 35.).unwrap();
+        let (dem, surfaces, boundaries, expected) = synthetic_model();
+        let (surf1, surf2) = (surfaces[0].clone(), surfaces[1].clone());
+
+        // Synthetic data generated from known weights
+        let data_x = vec![3., 5., 7., 9., 11., 13., 15.];
+        let truth = synthetic_truth(
+            &dem,
+            &surfaces,
+            &boundaries,
+            &vec![vec![], vec![]],
+            &expected,
+            &data_x,
+        );
+        let amplitude =
+            truth.projected_amplitude_onto(los_orientation.clone(), section_orientation.clone());
+        let disp_data = DispData::new(data_x, amplitude).unwrap();
+
+        for solver in [
+            Solver::NonNegativeLinear,
+            Solver::Linear,
+            Solver::BoundedLinear,
+        ] {
+            let (_profile, calibration) = DispProfile::from_solver(
+                &dem,
+                &vec![surf1.clone(), surf2.clone()],
+                &boundaries,
+                &vec![vec![], vec![]],
+                &[(los_orientation.clone(), disp_data.clone())],
+                &section_orientation,
+                &CalibrationSettings {
+                    solver,
+                    ..CalibrationSettings::new(2)
+                },
+            )
+            .unwrap();
+            for (weight, expect) in calibration.weights.iter().zip(expected.iter()) {
+                assert_approx_eq::assert_approx_eq!(weight, expect, 1e-3);
+            }
+            // Noise free data, the weights are perfectly constrained
+            let uncertainty = calibration.uncertainty.unwrap();
+            assert!(uncertainty.std_errors.iter().all(|se| *se < 1e-2));
+        }
+
+        // An outlier with a large standard deviation barely influences the weighted fit
+        let mut corrupted = disp_data.amplitude.clone();
+        corrupted[3] += 5.;
+        let mut sigma = vec![0.01; corrupted.len()];
+        sigma[3] = 100.;
+        let weighted_data = DispData::new(disp_data.x.clone(), corrupted)
+            .unwrap()
+            .with_sigma(sigma)
+            .unwrap();
+        let (_profile, calibration) = DispProfile::from_solver(
+            &dem,
+            &vec![surf1.clone(), surf2.clone()],
+            &boundaries,
+            &vec![vec![], vec![]],
+            &[(los_orientation.clone(), weighted_data)],
+            &section_orientation,
+            &CalibrationSettings {
+                solver: Solver::Linear,
+                ..CalibrationSettings::new(2)
+            },
+        )
+        .unwrap();
+        for (weight, expect) in calibration.weights.iter().zip(expected.iter()) {
+            assert_approx_eq::assert_approx_eq!(weight, expect, 1e-2);
+        }
+
+        // Joint calibration with a second acquisition geometry
+        let other_orientation = Orientation::from_deg(70., 40.).unwrap();
+        let other_amplitude =
+            truth.projected_amplitude_onto(other_orientation.clone(), section_orientation.clone());
+        let other_data = DispData::new(disp_data.x.clone(), other_amplitude).unwrap();
+        let (_profile, calibration) = DispProfile::from_solver(
+            &dem,
+            &vec![surf1.clone(), surf2.clone()],
+            &boundaries,
+            &vec![vec![], vec![]],
+            &[
+                (los_orientation.clone(), disp_data.clone()),
+                (other_orientation, other_data),
+            ],
+            &section_orientation,
+            &CalibrationSettings {
+                bootstrap_samples: 50,
+                ..CalibrationSettings::new(2)
+            },
+        )
+        .unwrap();
+        for (weight, expect) in calibration.weights.iter().zip(expected.iter()) {
+            assert_approx_eq::assert_approx_eq!(weight, expect, 1e-3);
+        }
+        assert_eq!(calibration.misfits.len(), 2);
+        assert!(calibration.misfits.iter().all(|m| *m < 1e-3));
+        let uncertainty = calibration.uncertainty.unwrap();
+        assert_eq!(uncertainty.bootstrap_samples, 50);
+        assert_eq!(uncertainty.confidence_95.len(), 2);
+
+        // Fixed first weight and bounded second weight
+        let settings = CalibrationSettings {
+            solver: Solver::BoundedLinear,
+            lower_bounds: vec![0., 0.],
+            upper_bounds: vec![1000., 0.5],
+            fixed_weights: vec![Some(2.5), None],
+            ..CalibrationSettings::new(2)
+        };
+        let (_profile, calibration) = DispProfile::from_solver(
+            &dem,
+            &vec![surf1.clone(), surf2.clone()],
+            &boundaries,
+            &vec![vec![], vec![]],
+            &[(los_orientation.clone(), disp_data.clone())],
+            &section_orientation,
+            &settings,
+        )
+        .unwrap();
+        assert_eq!(calibration.weights[0], 2.5);
+        assert_approx_eq::assert_approx_eq!(calibration.weights[1], 0.5, 1e-4);
+        let uncertainty = calibration.uncertainty.unwrap();
+        assert_eq!(uncertainty.std_errors[0], 0.);
+        assert_eq!(uncertainty.confidence_95[0], [2.5, 2.5]);
+
+        // Inconsistent settings are rejected
+        let result = DispProfile::from_solver(
+            &dem,
+            &vec![surf1, surf2],
+            &boundaries,
+            &vec![vec![], vec![]],
+            &[(los_orientation, disp_data)],
+            &section_orientation,
+            &CalibrationSettings::new(3),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_fitter_gradient() {
+        let section_orientation = Orientation::from_deg(260., 90.).unwrap();
+        let los_orientation = Orientation::from_deg(286., 35.).unwrap();
+        let (dem, surfaces, boundaries, _) = synthetic_model();
+        let surf = surfaces[0].clone();
+        let boundaries = vec![boundaries[0]];
+        let true_gradient = vec![(1, 1.), (5, 0.4), (8, 0.1)];
+
+        // Synthetic data of a velocity decaying along the slope
+        let data_x: Vec<f32> = (2..=16).map(|k| k as f32).collect();
+        let truth = synthetic_truth(
+            &dem,
+            &[surf.clone()],
+            &boundaries,
+            &vec![true_gradient.clone()],
+            &vec![2.],
+            &data_x,
+        );
+        let amplitude =
+            truth.projected_amplitude_onto(los_orientation.clone(), section_orientation.clone());
+        let disp_data = DispData::new(data_x, amplitude).unwrap();
+
+        // The control points start from a flat guess and recover the decay
+        let guess = vec![vec![(1, 1.), (5, 1.), (8, 1.)]];
+        let settings = CalibrationSettings {
+            solver: Solver::Linear,
+            free_gradients: vec![true],
+            ..CalibrationSettings::new(1)
+        };
+        let (_profile, calibration) = DispProfile::from_solver(
+            &dem,
+            &vec![surf.clone()],
+            &boundaries,
+            &guess,
+            &[(los_orientation.clone(), disp_data.clone())],
+            &section_orientation,
+            &settings,
+        )
+        .unwrap();
+        assert_approx_eq!(calibration.weights[0], 2., 1e-3);
+        for (point, expect) in calibration.gradients[0].iter().zip(true_gradient.iter()) {
+            assert_eq!(point.0, expect.0);
+            assert_approx_eq!(point.1, expect.1, 1e-3);
+        }
+        assert!(calibration.misfits[0] < 1e-3);
+        assert_eq!(calibration.uncertainty.unwrap().std_errors.len(), 1);
+
+        // A strong smoothing straightens the gradient
+        let (_profile, calibration) = DispProfile::from_solver(
+            &dem,
+            &vec![surf.clone()],
+            &boundaries,
+            &guess,
+            &[(los_orientation.clone(), disp_data.clone())],
+            &section_orientation,
+            &CalibrationSettings {
+                gradient_smoothing: 1e3,
+                ..settings.clone()
+            },
+        )
+        .unwrap();
+        let values: Vec<f32> = calibration.gradients[0].iter().map(|p| p.1).collect();
+        assert_approx_eq!(values[0] - 2. * values[1] + values[2], 0., 1e-3);
+
+        // A single control point cannot be calibrated
+        let result = DispProfile::from_solver(
+            &dem,
+            &vec![surf],
+            &boundaries,
+            &vec![vec![(3, 1.)]],
+            &[(los_orientation, disp_data)],
+            &section_orientation,
+            &settings,
+        );
+        assert!(matches!(result, Err(VectorInputError::InvalidGradient)));
+    }
+
+    #[test]
+    fn test_fitter_per_point_los() {
+        let section_orientation = Orientation::from_deg(260., 90.).unwrap();
+        let (dem, surfaces, boundaries, expected) = synthetic_model();
+
+        // The incidence increases across the swath
+        let data_x = vec![3., 5., 7., 9., 11., 13., 15.];
+        let los: Vec<Vector3Rep> = (0..data_x.len())
+            .map(|k| {
+                Orientation::from_deg(286., 25. + 3. * k as f32)
+                    .unwrap()
+                    .into()
+            })
+            .collect();
+        let truth = synthetic_truth(
+            &dem,
+            &surfaces,
+            &boundaries,
+            &vec![vec![], vec![]],
+            &expected,
+            &data_x,
+        );
+        let amplitude = truth.projected_amplitude_onto_each(&los, &section_orientation);
+        let disp_data = DispData::new(data_x, amplitude)
+            .unwrap()
+            .with_los(los)
+            .unwrap();
+
+        // The acquisition orientation is overridden by the geometry of each point
+        let (_profile, calibration) = DispProfile::from_solver(
+            &dem,
+            &surfaces,
+            &boundaries,
+            &vec![vec![], vec![]],
+            &[(Orientation::from_deg(100., 60.).unwrap(), disp_data)],
+            &section_orientation,
+            &CalibrationSettings::new(2),
+        )
+        .unwrap();
+        for (weight, expect) in calibration.weights.iter().zip(expected.iter()) {
+            assert_approx_eq::assert_approx_eq!(weight, expect, 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_fitter_nuisance() {
+        let section_orientation = Orientation::from_deg(260., 90.).unwrap();
+        let los_orientation = Orientation::from_deg(286., 35.).unwrap();
+        let other_orientation = Orientation::from_deg(70., 40.).unwrap();
+        let (dem, surfaces, boundaries, expected) = synthetic_model();
+
+        // Each data set is shifted by its own reference and tilted by its own ramp
+        let data_x: Vec<f32> = (2..=16).map(|k| k as f32).collect();
+        let truth = synthetic_truth(
+            &dem,
+            &surfaces,
+            &boundaries,
+            &vec![vec![], vec![]],
+            &expected,
+            &data_x,
+        );
+        let nuisance = [(-1.5_f32, 0.05_f32), (0.8, -0.02)];
+        let datasets: Vec<(Orientation, DispData)> = [&los_orientation, &other_orientation]
+            .iter()
+            .zip(nuisance.iter())
+            .map(|(orientation, (offset, ramp))| {
+                let amplitude = truth
+                    .projected_amplitude_onto((*orientation).clone(), section_orientation.clone())
+                    .iter()
+                    .zip(data_x.iter())
+                    .map(|(a, x)| a + offset + ramp * x)
+                    .collect();
+                (
+                    (*orientation).clone(),
+                    DispData::new(data_x.clone(), amplitude).unwrap(),
+                )
+            })
+            .collect();
+
+        // Nuisance terms are free of sign even with non-negative weights
+        let (_profile, calibration) = DispProfile::from_solver(
+            &dem,
+            &surfaces,
+            &boundaries,
+            &vec![vec![], vec![]],
+            &datasets,
+            &section_orientation,
+            &CalibrationSettings {
+                fit_offset: true,
+                fit_ramp: true,
+                ..CalibrationSettings::new(2)
+            },
+        )
+        .unwrap();
+        for (weight, expect) in calibration.weights.iter().zip(expected.iter()) {
+            assert_approx_eq!(weight, expect, 1e-2);
+        }
+        for (d, (offset, ramp)) in nuisance.iter().enumerate() {
+            assert_approx_eq!(calibration.offsets[d], offset, 1e-2);
+            assert_approx_eq!(calibration.ramps[d], ramp, 1e-3);
+        }
+        assert!(calibration.misfits.iter().all(|m| *m < 1e-2));
+        let corrected: Vec<f32> = datasets[0]
+            .1
+            .amplitude
+            .iter()
+            .zip(calibration.nuisance(0, &data_x))
+            .map(|(a, n)| a - n)
+            .collect();
+        let model = truth.projected_amplitude_onto(los_orientation, section_orientation.clone());
+        for (c, m) in corrected.iter().zip(model.iter()) {
+            assert_approx_eq!(c, m, 1e-2);
+        }
+
+        // Without nuisance terms the offsets are reported null
+        let (_profile, calibration) = DispProfile::from_solver(
+            &dem,
+            &surfaces,
+            &boundaries,
+            &vec![vec![], vec![]],
+            &datasets,
+            &section_orientation,
+            &CalibrationSettings::new(2),
+        )
+        .unwrap();
+        assert_eq!(calibration.offsets, vec![0., 0.]);
+        assert_eq!(calibration.ramps, vec![0., 0.]);
+        assert!(calibration.misfits.iter().any(|m| *m > 1e-2));
+    }
 }
@@ -2,6 +2,7 @@ use crate::{
     prelude::{rad2deg, Vector2Rep},
     types::*,
 };
+pub mod search;
 #[allow(unused_imports)] // actually used but raises unused import
 use assert_approx_eq::assert_approx_eq;
 
@@ -208,7 +209,7 @@ impl Surface1D {
 }
 
 /// Computes the slope of a property along the section and the given DEM
-pub(super) fn slope1d(x: &Vec<f32>, z: &Vec<f32>) -> Vec<f32> {
+pub(crate) fn slope1d(x: &Vec<f32>, z: &Vec<f32>) -> Vec<f32> {
     assert_eq!(x.len(), z.len());
     let len = x.len();
     let mut slope_v: Vec<f32> = vec![];
@@ -0,0 +1,210 @@
+//! Search of the SLBL parameters that best explain the displacement data.
+//!
+//! Each candidate combination of boundaries and tolerance generates an exact SLBL surface,
+//! whose unit displacement profile is calibrated against the data. Candidates are ranked
+//! by the misfit of their calibrated profile.
+
+use crate::data::CalibrationSettings;
+use crate::types::*;
+
+/// Range of SLBL parameters explored by the search
+///
+/// Boundaries are DEM indices, both ranges are inclusive and sampled every `point_step`
+/// points. Tolerances are sampled linearly with `nb_tol` values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlblSearch {
+    /// Smallest and largest first point of the SLBL
+    pub first_pnt: [usize; 2],
+    /// Smallest and largest last point of the SLBL
+    pub last_pnt: [usize; 2],
+    /// Step between two sampled boundaries
+    pub point_step: usize,
+    /// Smallest and largest tolerance of the SLBL
+    pub tol: [f32; 2],
+    /// Number of sampled tolerances
+    pub nb_tol: usize,
+}
+
+impl SlblSearch {
+    /// Sampled tolerances of the search
+    pub fn tolerances(&self) -> Vec<f32> {
+        match self.nb_tol {
+            0 => vec![],
+            1 => vec![self.tol[0]],
+            n => (0..n)
+                .map(|k| self.tol[0] + (self.tol[1] - self.tol[0]) * k as f32 / (n - 1) as f32)
+                .collect(),
+        }
+    }
+
+    /// Number of candidates to evaluate, including invalid boundaries
+    pub fn nb_candidates(&self) -> usize {
+        let step = self.point_step.max(1);
+        let count = |[min, max]: [usize; 2]| {
+            if max < min {
+                0
+            } else {
+                (max - min) / step + 1
+            }
+        };
+        count(self.first_pnt) * count(self.last_pnt) * self.nb_tol
+    }
+}
+
+/// Candidate SLBL surface of the search, with its calibration against the data
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlblCandidate {
+    pub first_pnt: usize,
+    pub last_pnt: usize,
+    pub tol: f32,
+    /// Calibrated weight of the unit profile
+    pub weight: f32,
+    /// RMSE of the calibrated profile over all the data points
+    pub misfit: f32,
+}
+
+/// Evaluate every candidate of the search and rank them by increasing misfit
+///
+/// Candidates whose boundaries are too close, out of the DEM, or whose profile cannot be
+/// built or calibrated are left out of the ranking. The settings apply to the single
+/// surface of each candidate.
+///
+/// # Errors
+/// * the search or the data sets are empty
+/// * the settings are not consistent with a single surface
+pub fn search_slbl(
+    dem: &Dem1D,
+    search: &SlblSearch,
+    los_datasets: &[(Orientation, DispData)],
+    section_orientation: &Orientation,
+    settings: &CalibrationSettings,
+) -> Result<Vec<SlblCandidate>, VectorInputError> {
+    if search.nb_candidates() == 0 || los_datasets.is_empty() {
+        return Err(VectorInputError::EmptyVecs);
+    }
+    settings.check(1)?;
+    let nb_points: Vec<f32> = los_datasets.iter().map(|(_, d)| d.x.len() as f32).collect();
+    let total_points: f32 = nb_points.iter().sum();
+    let step = search.point_step.max(1);
+    let tolerances = search.tolerances();
+
+    let mut candidates = vec![];
+    for first_pnt in (search.first_pnt[0]..=search.first_pnt[1]).step_by(step) {
+        for last_pnt in (search.last_pnt[0]..=search.last_pnt[1]).step_by(step) {
+            // The exact SLBL needs at least two points between its boundaries
+            if last_pnt < first_pnt + 3 || last_pnt >= dem.x.len() {
+                continue;
+            }
+            for tol in tolerances.iter() {
+                let mut surface = Surface1D::from_slbl_exact(dem, first_pnt, last_pnt, *tol);
+                surface.get_slope(dem);
+                let Ok((_, calibration)) = DispProfile::from_solver(
+                    dem,
+                    &vec![surface],
+                    &vec![[first_pnt, last_pnt]],
+                    &vec![vec![]],
+                    los_datasets,
+                    section_orientation,
+                    settings,
+                ) else {
+                    continue;
+                };
+                // Combine the RMSE of each data set over all the data points
+                let misfit = (calibration
+                    .misfits
+                    .iter()
+                    .zip(nb_points.iter())
+                    .map(|(m, n)| m * m * n)
+                    .sum::<f32>()
+                    / total_points)
+                    .sqrt();
+                if !misfit.is_finite() {
+                    continue;
+                }
+                candidates.push(SlblCandidate {
+                    first_pnt,
+                    last_pnt,
+                    tol: *tol,
+                    weight: calibration.weights[0],
+                    misfit,
+                });
+            }
+        }
+    }
+    candidates.sort_by(|a, b| a.misfit.total_cmp(&b.misfit));
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_search_slbl() {
+        let x: Vec<f32> = (0..=20).map(|k| 5. * k as f32).collect();
+        let z: Vec<f32> = x
+            .iter()
+            .map(|x| 100. - 0.5 * x + 3. * (x / 17.).sin())
+            .collect();
+        let dem = Dem1D {
+            x,
+            surface: Surface1D::new(z),
+        };
+        let section_orientation = Orientation::from_deg(90., 90.).unwrap();
+        let los_orientation = Orientation::from_deg(100., 40.).unwrap();
+
+        // Synthetic data generated by a known SLBL surface
+        let mut truth_surface = Surface1D::from_slbl_exact(&dem, 4, 14, 0.5);
+        let mut truth = DispProfile::from_surface(&mut truth_surface, &dem, 4, 14).unwrap();
+        truth.weight_disp(3.);
+        let data_x: Vec<f32> = (8..=90).step_by(4).map(|k| k as f32).collect();
+        let data_z = dem.interpolate_elevation_on_x(&data_x);
+        truth.interpolate_on_origins(&(0..data_x.len()).map(|k| [data_x[k], data_z[k]]).collect());
+        let amplitude =
+            truth.projected_amplitude_onto(los_orientation.clone(), section_orientation.clone());
+        let disp_data = DispData::new(data_x, amplitude).unwrap();
+
+        let search = SlblSearch {
+            first_pnt: [2, 6],
+            last_pnt: [12, 16],
+            point_step: 2,
+            tol: [0.25, 1.],
+            nb_tol: 4,
+        };
+        assert_eq!(search.tolerances(), vec![0.25, 0.5, 0.75, 1.]);
+        assert_eq!(search.nb_candidates(), 36);
+
+        let candidates = search_slbl(
+            &dem,
+            &search,
+            &[(los_orientation, disp_data)],
+            &section_orientation,
+            &CalibrationSettings::new(1),
+        )
+        .unwrap();
+        assert!(!candidates.is_empty());
+        assert!(candidates.windows(2).all(|c| c[0].misfit <= c[1].misfit));
+        let best = &candidates[0];
+        assert_eq!((best.first_pnt, best.last_pnt), (4, 14));
+        assert_approx_eq!(best.tol, 0.5);
+        assert_approx_eq!(best.weight, 3., 1e-2);
+        // The true surface stands out, up to the interpolation of the synthetic profile
+        assert!(best.misfit < 0.1);
+        assert!(best.misfit < candidates[1].misfit / 2.);
+
+        // An empty search is rejected
+        let empty = SlblSearch {
+            nb_tol: 0,
+            ..search
+        };
+        assert!(search_slbl(
+            &dem,
+            &empty,
+            &[],
+            &Orientation::from_deg(90., 90.).unwrap(),
+            &CalibrationSettings::new(1)
+        )
+        .is_err());
+    }
+}
@@ -1,8 +1,8 @@
 //! This module defines the types used to hold the information in this project.
 
 use crate::{
-    data::vec_proj::{deg2rad, Vector2Rep, Vector3Rep},
-    profile::interpol_linear,
+    data::vec_proj::{deg2rad, rad2deg, Vector2Rep, Vector3Rep},
+    profile::{interpol_linear, interpol_monotone_cubic},
 };
 use eqsolver::SolverError;
 use std::f32::consts::PI;
@@ -33,6 +33,30 @@ pub enum VectorInputError {
     PillarError,
     #[error("Error in solver eqsolver")]
     SolverError(SolverError),
+    #[error("The least squares system could not be solved")]
+    LeastSquaresError,
+    #[error("Standard deviations must be strictly positive")]
+    InvalidSigma,
+    #[error("A lower bound is greater than its upper bound")]
+    InvalidBounds,
+    #[error("A calibrated gradient needs at least two control points")]
+    InvalidGradient,
+    #[error("The smoothing of the gradients must be positive")]
+    InvalidSmoothing,
+    #[error("Footprints must be positive")]
+    InvalidFootprint,
+    #[error("The width of the bins must be strictly positive")]
+    InvalidBinWidth,
+    #[error("Line of sight vectors must be finite and not null")]
+    InvalidLos,
+    #[error("The DEM must be sorted, without duplicated or missing values")]
+    InvalidDem,
+    #[error("The spacing of the sampling must be strictly positive")]
+    InvalidSpacing,
+    #[error("At least two samples with finite and distinct x are needed")]
+    InvalidSamples,
+    #[error("Depths must be finite and positive")]
+    InvalidDepth,
 }
 
 impl Dem1D {
@@ -58,6 +82,42 @@ impl Dem1D {
     pub fn interpolate_elevation_on_x(&self, new_x: &Vec<f32>) -> Vec<f32> {
         interpol_linear(&self.x, &self.surface.z, &new_x)
     }
+
+    /// Resample the DEM with a regular spacing by linear interpolation
+    ///
+    /// The sampling starts from the first bound of `range`, or else from the first point of the
+    /// DEM, and ends at the last multiple of the spacing within the second bound or the DEM.
+    ///
+    /// # Errors
+    /// * the spacing is not strictly positive
+    /// * the range is not increasing or not within the DEM
+    pub fn resample(
+        &self,
+        spacing: f32,
+        range: Option<[f32; 2]>,
+    ) -> Result<Self, VectorInputError> {
+        if !(spacing.is_finite() && spacing > 0.) {
+            return Err(VectorInputError::InvalidSpacing);
+        }
+        let [first, last] = range.unwrap_or([self.x[0], self.x[self.x.len() - 1]]);
+        if !(first < last && first >= self.x[0] && last <= self.x[self.x.len() - 1]) {
+            return Err(VectorInputError::InvalidBounds);
+        }
+        let nb_steps = ((last - first) / spacing + 1e-3).floor() as usize;
+        let x: Vec<f32> = (0..=nb_steps).map(|k| first + k as f32 * spacing).collect();
+        let z = self.interpolate_elevation_on_x(&x);
+        Dem1D::new(x, z)
+    }
+
+    /// Index of the sampling point closest to `x`
+    pub fn nearest_index(&self, x: f32) -> usize {
+        let k = self.x.partition_point(|xk| *xk < x);
+        if k == self.x.len() || (k > 0 && x - self.x[k - 1] <= self.x[k] - x) {
+            k.saturating_sub(1)
+        } else {
+            k
+        }
+    }
 }
 
 /// A 1D surface layer defining a surface along a dem sampling on a 2D section.
@@ -76,6 +136,103 @@ impl Surface1D {
     pub fn new(z: Vec<f32>) -> Self {
         Surface1D { z, slope: None }
     }
+
+    /// Linear interpolation of the surface sampled on `x_old` onto a new sampling `x_new`
+    ///
+    /// The slope is left to be computed on the new sampling.
+    pub fn interpolate_on_x(&self, x_old: &[f32], x_new: &[f32]) -> Self {
+        Surface1D::new(interpol_linear(x_old, &self.z, x_new))
+    }
+
+    /// Construct a surface on the DEM sampling from samples at other positions
+    ///
+    /// Outside the range of the samples, the surface falls back to the DEM elevation.
+    ///
+    /// # Errors
+    /// * the length of the x and z vectors differ
+    /// * there are less than two samples, or their x are not finite or duplicated
+    pub fn from_samples(
+        x: &[f32],
+        z: &[f32],
+        dem: &Dem1D,
+        method: SurfaceInterpolation,
+    ) -> Result<Self, VectorInputError> {
+        let z = interpol_samples(x, z, &dem.x, &dem.surface.z, method)?;
+        Ok(Surface1D::new(z))
+    }
+}
+
+/// Interpolate samples at any positions onto the sampling `x_new`, taking the `fallback`
+/// values outside the range of the samples
+///
+/// # Errors
+/// * the length of the x and z vectors differ
+/// * there are less than two samples, or their x are not finite or duplicated
+pub(crate) fn interpol_samples(
+    x: &[f32],
+    z: &[f32],
+    x_new: &Vec<f32>,
+    fallback: &[f32],
+    method: SurfaceInterpolation,
+) -> Result<Vec<f32>, VectorInputError> {
+    if x.len() != z.len() {
+        return Err(VectorInputError::InconsistentLen {
+            vec1: x.len(),
+            vec2: z.len(),
+        });
+    }
+    let mut samples: Vec<(f32, f32)> = x.iter().copied().zip(z.iter().copied()).collect();
+    samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+    if samples.len() < 2
+        || samples
+            .iter()
+            .any(|(x, z)| !(x.is_finite() && z.is_finite()))
+        || samples.windows(2).any(|w| w[0].0 == w[1].0)
+    {
+        return Err(VectorInputError::InvalidSamples);
+    }
+    let (x, z): (Vec<f32>, Vec<f32>) = samples.into_iter().unzip();
+    let interpolated = match method {
+        SurfaceInterpolation::Linear => interpol_linear(&x, &z, x_new),
+        SurfaceInterpolation::MonotoneCubic => interpol_monotone_cubic(&x, &z, x_new),
+    };
+    let (first, last) = (x[0], x[x.len() - 1]);
+    Ok(x_new
+        .iter()
+        .zip(interpolated.iter().zip(fallback.iter()))
+        .map(|(x, (value, fallback))| {
+            if *x < first || *x > last {
+                *fallback
+            } else {
+                *value
+            }
+        })
+        .collect())
+}
+
+/// Interpolation of a surface sampled differently from the DEM
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SurfaceInterpolation {
+    #[default]
+    Linear,
+    /// Monotone piecewise cubic, smooth without overshooting the samples
+    MonotoneCubic,
+}
+
+impl SurfaceInterpolation {
+    /// All the available interpolations
+    pub const ALL: [SurfaceInterpolation; 2] = [
+        SurfaceInterpolation::Linear,
+        SurfaceInterpolation::MonotoneCubic,
+    ];
+
+    /// Short name of the interpolation
+    pub fn name(&self) -> &'static str {
+        match self {
+            SurfaceInterpolation::Linear => "Linear",
+            SurfaceInterpolation::MonotoneCubic => "Monotone cubic",
+        }
+    }
 }
 
 /// A 1D profile defining vectors and their positions on the 2D section
@@ -143,11 +300,29 @@ pub struct DispData {
     pub x: Vec<f32>,
     /// amplitude of the displacement
     pub amplitude: Vec<f32>,
+    /// standard deviation of the amplitude at each point
+    ///
+    /// Used to weight the points during the calibration. None if unknown
+    pub sigma: Option<Vec<f32>>,
+    /// width along the section of the pixel of each point
+    ///
+    /// Used to average the model over each point. None if the points are punctual
+    pub footprint: Option<Vec<f32>>,
+    /// unit vector of the line of sight of each point
+    ///
+    /// Same convention as the vector of an `Orientation`, pointing from the satellite to the
+    /// ground, used instead of the acquisition orientation when the geometry varies along the
+    /// section. None if constant
+    pub los: Option<Vec<Vector3Rep>>,
     /// projection of the recorded displacement into a section
     ///
     /// Can be used to display the displacement into a 2D section.
     /// Not projected if empty
     pub projected_vecs: Vec<Vector2Rep>,
+    /// True where the projection into the section is ill-conditioned
+    ///
+    /// Same length as `projected_vecs`, the corresponding vectors are null
+    pub ill_conditioned: Vec<bool>,
 }
 
 impl DispData {
@@ -164,8 +339,180 @@ impl DispData {
             Ok(DispData {
                 x,
                 amplitude,
+                sigma: None,
+                footprint: None,
+                los: None,
                 projected_vecs: Vec::<Vector2Rep>::new(),
+                ill_conditioned: Vec::<bool>::new(),
+            })
+        }
+    }
+
+    /// Associate a standard deviation to each amplitude value
+    ///
+    /// # Errors
+    /// * the length of the sigma and amplitude vectors differ
+    /// * a standard deviation is not strictly positive
+    pub fn with_sigma(mut self, sigma: Vec<f32>) -> Result<Self, VectorInputError> {
+        if sigma.len() != self.amplitude.len() {
+            Err(VectorInputError::InconsistentLen {
+                vec1: self.amplitude.len(),
+                vec2: sigma.len(),
+            })
+        } else if !sigma.iter().all(|s| s.is_finite() && *s > 0.) {
+            Err(VectorInputError::InvalidSigma)
+        } else {
+            self.sigma = Some(sigma);
+            Ok(self)
+        }
+    }
+
+    /// Associate the width of its pixel along the section to each point
+    ///
+    /// # Errors
+    /// * the length of the footprint and x vectors differ
+    /// * a footprint is negative
+    pub fn with_footprint(mut self, footprint: Vec<f32>) -> Result<Self, VectorInputError> {
+        if footprint.len() != self.x.len() {
+            Err(VectorInputError::InconsistentLen {
+                vec1: self.x.len(),
+                vec2: footprint.len(),
+            })
+        } else if !footprint.iter().all(|f| f.is_finite() && *f >= 0.) {
+            Err(VectorInputError::InvalidFootprint)
+        } else {
+            self.footprint = Some(footprint);
+            Ok(self)
+        }
+    }
+
+    /// Associate its own line of sight to each point, the vectors being normalized
+    ///
+    /// The vectors point from the satellite to the ground, with a negative up component.
+    /// Vectors pointing upward, from the ground to the satellite as in most products, are
+    /// reversed.
+    ///
+    /// # Errors
+    /// * the length of the los and x vectors differ
+    /// * a vector is null or not finite
+    pub fn with_los(mut self, mut los: Vec<Vector3Rep>) -> Result<Self, VectorInputError> {
+        if los.len() != self.x.len() {
+            return Err(VectorInputError::InconsistentLen {
+                vec1: self.x.len(),
+                vec2: los.len(),
+            });
+        }
+        if !los
+            .iter()
+            .all(|v| v.amplitude().is_finite() && v.amplitude() > 0.)
+        {
+            return Err(VectorInputError::InvalidLos);
+        }
+        los.iter_mut().for_each(|v| {
+            v.unit();
+            let (x, y, z) = v.coords();
+            if z > 0. {
+                v.with_coords(-x, -y, -z);
+            }
+        });
+        self.los = Some(los);
+        Ok(self)
+    }
+
+    /// Line of sight of each point, the acquisition orientation if no per point geometry
+    pub fn los_vectors(&self, orientation: &Orientation) -> Vec<Vector3Rep> {
+        match &self.los {
+            Some(los) => los.clone(),
+            None => vec![orientation.to_owned().into(); self.x.len()],
+        }
+    }
+}
+
+/// Displacement time series recorded along the section
+///
+/// Must be associated with the acquisition orientation. Each epoch holds the displacement
+/// of every point at one acquisition date, usually cumulated since the first date.
+#[derive(Default, Debug, Clone)]
+pub struct DispSeries {
+    /// x samples where displacement data were recorded
+    pub x: Vec<f32>,
+    /// label of each acquisition date
+    pub dates: Vec<String>,
+    /// amplitude of the displacement at each date, one vector of the x length per date
+    pub amplitudes: Vec<Vec<f32>>,
+    /// standard deviation of the amplitude at each point, shared by all dates
+    pub sigma: Option<Vec<f32>>,
+}
+
+impl DispSeries {
+    /// Construct a new displacement time series from x / dates / amplitude values
+    ///
+    /// # Errors
+    /// * the number of dates and epochs differ
+    /// * the length of an epoch differs from the x length
+    /// * the x or dates vectors are empty
+    pub fn new(
+        x: Vec<f32>,
+        dates: Vec<String>,
+        amplitudes: Vec<Vec<f32>>,
+    ) -> Result<Self, VectorInputError> {
+        if dates.len() != amplitudes.len() {
+            return Err(VectorInputError::InconsistentLen {
+                vec1: dates.len(),
+                vec2: amplitudes.len(),
+            });
+        }
+        if x.is_empty() || dates.is_empty() {
+            return Err(VectorInputError::EmptyVecs);
+        }
+        if let Some(epoch) = amplitudes.iter().find(|a| a.len() != x.len()) {
+            return Err(VectorInputError::InconsistentLen {
+                vec1: x.len(),
+                vec2: epoch.len(),
+            });
+        }
+        Ok(DispSeries {
+            x,
+            dates,
+            amplitudes,
+            sigma: None,
+        })
+    }
+
+    /// Associate a standard deviation to each point of the series
+    ///
+    /// # Errors
+    /// * the length of the sigma and x vectors differ
+    /// * a standard deviation is not strictly positive
+    pub fn with_sigma(mut self, sigma: Vec<f32>) -> Result<Self, VectorInputError> {
+        if sigma.len() != self.x.len() {
+            Err(VectorInputError::InconsistentLen {
+                vec1: self.x.len(),
+                vec2: sigma.len(),
             })
+        } else if !sigma.iter().all(|s| s.is_finite() && *s > 0.) {
+            Err(VectorInputError::InvalidSigma)
+        } else {
+            self.sigma = Some(sigma);
+            Ok(self)
+        }
+    }
+
+    /// Number of acquisition dates
+    pub fn nb_epochs(&self) -> usize {
+        self.dates.len()
+    }
+
+    /// Displacement data of a single epoch
+    pub fn epoch(&self, epoch: usize) -> DispData {
+        DispData {
+            x: self.x.clone(),
+            amplitude: self.amplitudes[epoch].clone(),
+            sigma: self.sigma.clone(),
+            footprint: None,
+            los: None,
+            projected_vecs: Vec::<Vector2Rep>::new(),
+            ill_conditioned: Vec::<bool>::new(),
         }
     }
 }
@@ -219,6 +566,176 @@ impl Orientation {
         let incidence_rad = deg2rad(incidence);
         Self::new(azimuth_rad, incidence_rad)
     }
+
+    /// Construct an orientation from the heading of the platform in radians
+    ///
+    /// The sensor looks perpendicular to the flight direction, on the given side.
+    ///
+    /// # Errors
+    /// * the incidence value is out of range
+    pub fn from_heading(
+        heading: f32,
+        incidence: f32,
+        side: LookSide,
+    ) -> Result<Self, OrientationError> {
+        let azimuth = match side {
+            LookSide::Right => heading + PI / 2.,
+            LookSide::Left => heading - PI / 2.,
+        };
+        Self::new(wrap_azimuth(azimuth), incidence)
+    }
+
+    /// Construct an orientation from the azimuth of the line of sight pointing from the
+    /// ground toward the satellite, in radians
+    ///
+    /// # Errors
+    /// * the incidence value is out of range
+    pub fn from_toward_satellite(azimuth: f32, incidence: f32) -> Result<Self, OrientationError> {
+        Self::new(wrap_azimuth(azimuth + PI), incidence)
+    }
+}
+
+/// Side toward which a sensor looks, relative to its flight direction
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LookSide {
+    #[default]
+    Right,
+    Left,
+}
+
+/// Reference of the azimuth given to define an acquisition geometry
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum AzimuthConvention {
+    /// Azimuth of the look direction, pointing away from the satellite
+    #[default]
+    LookDirection,
+    /// Azimuth of the line of sight pointing toward the satellite
+    TowardSatellite,
+    /// Heading of the platform, with the side the sensor looks to
+    Heading(LookSide),
+}
+
+impl AzimuthConvention {
+    /// All the available conventions
+    pub const ALL: [AzimuthConvention; 4] = [
+        AzimuthConvention::LookDirection,
+        AzimuthConvention::TowardSatellite,
+        AzimuthConvention::Heading(LookSide::Right),
+        AzimuthConvention::Heading(LookSide::Left),
+    ];
+
+    /// Short name of the convention
+    pub fn name(&self) -> &'static str {
+        match self {
+            AzimuthConvention::LookDirection => "LOS azimuth (away from satellite)",
+            AzimuthConvention::TowardSatellite => "LOS azimuth (toward satellite)",
+            AzimuthConvention::Heading(LookSide::Right) => "Heading, right-looking",
+            AzimuthConvention::Heading(LookSide::Left) => "Heading, left-looking",
+        }
+    }
+}
+
+/// Unit of the angles given to define an acquisition geometry
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum AngleUnit {
+    #[default]
+    Degrees,
+    Radians,
+}
+
+impl AngleUnit {
+    /// All the available units
+    pub const ALL: [AngleUnit; 2] = [AngleUnit::Degrees, AngleUnit::Radians];
+
+    /// Short name of the unit
+    pub fn name(&self) -> &'static str {
+        match self {
+            AngleUnit::Degrees => "Degrees",
+            AngleUnit::Radians => "Radians",
+        }
+    }
+
+    /// Symbol of the unit
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            AngleUnit::Degrees => "°",
+            AngleUnit::Radians => " rad",
+        }
+    }
+
+    /// Value in radians of an angle given in this unit
+    pub fn to_rad(&self, angle: f32) -> f32 {
+        match self {
+            AngleUnit::Degrees => deg2rad(angle),
+            AngleUnit::Radians => angle,
+        }
+    }
+
+    /// Value in this unit of an angle given in radians
+    pub fn from_rad(&self, angle: f32) -> f32 {
+        match self {
+            AngleUnit::Degrees => rad2deg(angle),
+            AngleUnit::Radians => angle,
+        }
+    }
+}
+
+/// Convention used to enter and display the acquisition geometries
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GeometryConvention {
+    pub azimuth: AzimuthConvention,
+    pub unit: AngleUnit,
+}
+
+impl GeometryConvention {
+    /// Construct the orientation from an azimuth and an incidence in this convention
+    ///
+    /// # Errors
+    /// * the incidence value is out of range
+    pub fn orientation(
+        &self,
+        azimuth: f32,
+        incidence: f32,
+    ) -> Result<Orientation, OrientationError> {
+        let azimuth = self.unit.to_rad(azimuth);
+        let incidence = self.unit.to_rad(incidence);
+        match self.azimuth {
+            AzimuthConvention::LookDirection => Orientation::new(wrap_azimuth(azimuth), incidence),
+            AzimuthConvention::TowardSatellite => {
+                Orientation::from_toward_satellite(azimuth, incidence)
+            }
+            AzimuthConvention::Heading(side) => Orientation::from_heading(azimuth, incidence, side),
+        }
+    }
+
+    /// Azimuth and incidence of an orientation in this convention
+    pub fn angles(&self, orientation: &Orientation) -> (f32, f32) {
+        let azimuth = match self.azimuth {
+            AzimuthConvention::LookDirection => orientation.azimuth,
+            AzimuthConvention::TowardSatellite => wrap_azimuth(orientation.azimuth + PI),
+            AzimuthConvention::Heading(LookSide::Right) => {
+                wrap_azimuth(orientation.azimuth - PI / 2.)
+            }
+            AzimuthConvention::Heading(LookSide::Left) => {
+                wrap_azimuth(orientation.azimuth + PI / 2.)
+            }
+        };
+        (
+            self.unit.from_rad(azimuth),
+            self.unit.from_rad(orientation.incidence),
+        )
+    }
+}
+
+/// Bring an azimuth in radians into [0, 2π)
+fn wrap_azimuth(azimuth: f32) -> f32 {
+    let wrapped = azimuth.rem_euclid(2. * PI);
+    // Rounding can give 2π for small negative values
+    if wrapped >= 2. * PI {
+        0.
+    } else {
+        wrapped
+    }
 }
 
 /// Check the range of the azimuth value.
@@ -242,3 +759,74 @@ fn check_incidence_range(incidence: f32) -> bool {
         _ => true,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_geometry_conventions() {
+        // Descending track flying south-southwest, looking west
+        let expected = Orientation::from_deg(280., 35.).unwrap();
+        let conventions = [
+            (AzimuthConvention::LookDirection, 280.),
+            (AzimuthConvention::TowardSatellite, 100.),
+            (AzimuthConvention::Heading(LookSide::Right), 190.),
+            (AzimuthConvention::Heading(LookSide::Left), 10.),
+        ];
+        for (azimuth, angle) in conventions {
+            for unit in AngleUnit::ALL {
+                let convention = GeometryConvention { azimuth, unit };
+                let orientation = convention
+                    .orientation(unit.from_rad(deg2rad(angle)), unit.from_rad(deg2rad(35.)))
+                    .unwrap();
+                assert_approx_eq!(orientation.azimuth, expected.azimuth, 1e-5);
+                assert_approx_eq!(orientation.incidence, expected.incidence, 1e-5);
+                let (back, incidence) = convention.angles(&orientation);
+                assert_approx_eq!(back, unit.from_rad(deg2rad(angle)), 1e-3);
+                assert_approx_eq!(incidence, unit.from_rad(deg2rad(35.)), 1e-3);
+            }
+        }
+        assert_eq!(wrap_azimuth(-1e-9), 0.);
+    }
+
+    #[test]
+    fn test_dem_resample() {
+        let dem = Dem1D::new(vec![0., 10., 20., 30.], vec![0., 10., 30., 60.]).unwrap();
+        let resampled = dem.resample(4., Some([5., 27.])).unwrap();
+        assert_eq!(resampled.x, vec![5., 9., 13., 17., 21., 25.]);
+        assert_approx_eq!(resampled.surface.z[2], 16.);
+        assert!(dem.resample(4., Some([5., 40.])).is_err());
+        assert!(dem.resample(0., None).is_err());
+
+        assert_eq!(resampled.nearest_index(0.), 0);
+        assert_eq!(resampled.nearest_index(11.5), 2);
+        assert_eq!(resampled.nearest_index(100.), 5);
+
+        let surface = Surface1D::new(vec![0., 5., 20., 45.]);
+        let surface = surface.interpolate_on_x(&dem.x, &resampled.x);
+        assert_approx_eq!(surface.z[0], 2.5);
+        assert!(surface.slope.is_none());
+    }
+
+    #[test]
+    fn test_surface_from_samples() {
+        let dem = Dem1D::new(vec![0., 10., 20., 30., 40.], vec![50.; 5]).unwrap();
+        // Unsorted samples covering part of the DEM
+        let x = vec![25., 5., 15.];
+        let z = vec![45., 45., 35.];
+        for method in SurfaceInterpolation::ALL {
+            let surface = Surface1D::from_samples(&x, &z, &dem, method).unwrap();
+            // Falls back to the DEM outside the samples
+            assert_eq!(surface.z[0], 50.);
+            assert_eq!(surface.z[4], 50.);
+            assert_approx_eq!(surface.z[1], surface.z[2]);
+            assert!((35. ..=45.).contains(&surface.z[1]));
+        }
+        let surface = Surface1D::from_samples(&x, &z, &dem, SurfaceInterpolation::Linear).unwrap();
+        assert_approx_eq!(surface.z[1], 40.);
+        assert!(Surface1D::from_samples(&[5., 5.], &[1., 2.], &dem, Default::default()).is_err());
+        assert!(Surface1D::from_samples(&[5.], &[1.], &dem, Default::default()).is_err());
+    }
+}
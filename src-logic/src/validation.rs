@@ -0,0 +1,293 @@
+//! Validation and repair of the DEM sampling.
+//!
+//! Every computation of the section assumes increasing x with finite elevations, and the
+//! linear interpolation or the pillar method silently give wrong results otherwise. A DEM
+//! read from a file is checked first, and the detected problems can be repaired before using
+//! it in the project.
+
+use crate::data::sampling::median;
+use crate::profile::interpol_linear;
+use crate::types::*;
+
+/// Relative deviation of a step from the median spacing above which the sampling is irregular
+pub const SPACING_TOLERANCE: f32 = 0.01;
+/// Ratio between a step and the median spacing above which the step is a gap
+pub const GAP_RATIO: f32 = 2.;
+
+/// Problems detected in the sampling of a DEM
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DemReport {
+    pub nb_points: usize,
+    /// Indices of the points whose x is lower than the one of the previous point
+    pub unsorted: Vec<usize>,
+    /// Indices of the points whose x is the same as the one of a previous point
+    pub duplicated: Vec<usize>,
+    /// Indices of the points whose x or elevation is missing or not finite
+    pub missing: Vec<usize>,
+    /// Median step between the sorted points, if there are at least two distinct points
+    pub spacing: Option<f32>,
+    /// Number of steps deviating from the median spacing by more than [`SPACING_TOLERANCE`]
+    pub nb_irregular: usize,
+    /// Bounds of the steps larger than [`GAP_RATIO`] times the median spacing
+    pub gaps: Vec<[f32; 2]>,
+}
+
+impl DemReport {
+    /// Check the sampling `x` and the elevations `z` of a DEM
+    ///
+    /// The spacing, the irregular steps and the gaps are computed on the sorted and distinct
+    /// points with a finite x.
+    ///
+    /// # Errors
+    /// * the length of the x and z vectors differ
+    pub fn new(x: &[f32], z: &[f32]) -> Result<Self, VectorInputError> {
+        if x.len() != z.len() {
+            return Err(VectorInputError::InconsistentLen {
+                vec1: x.len(),
+                vec2: z.len(),
+            });
+        }
+        let mut report = DemReport {
+            nb_points: x.len(),
+            ..Default::default()
+        };
+        for k in 0..x.len() {
+            if !(x[k].is_finite() && z[k].is_finite()) {
+                report.missing.push(k);
+            }
+            if k > 0 && x[k] < x[k - 1] {
+                report.unsorted.push(k);
+            }
+        }
+
+        let mut order: Vec<usize> = (0..x.len()).filter(|k| x[*k].is_finite()).collect();
+        order.sort_by(|a, b| x[*a].total_cmp(&x[*b]).then(a.cmp(b)));
+        for pair in order.windows(2) {
+            if x[pair[1]] == x[pair[0]] {
+                report.duplicated.push(pair[1]);
+            }
+        }
+        report.duplicated.sort();
+
+        let mut sorted_x: Vec<f32> = order.iter().map(|k| x[*k]).collect();
+        sorted_x.dedup();
+        let steps: Vec<f32> = sorted_x.windows(2).map(|w| w[1] - w[0]).collect();
+        if !steps.is_empty() {
+            let spacing = median(&steps);
+            report.spacing = Some(spacing);
+            report.nb_irregular = steps
+                .iter()
+                .filter(|s| (*s - spacing).abs() > SPACING_TOLERANCE * spacing)
+                .count();
+            report.gaps = sorted_x
+                .windows(2)
+                .filter(|w| w[1] - w[0] > GAP_RATIO * spacing)
+                .map(|w| [w[0], w[1]])
+                .collect();
+        }
+        Ok(report)
+    }
+
+    /// True if the DEM can be used: it is not empty, sorted, without duplicates or missing
+    /// values
+    pub fn is_valid(&self) -> bool {
+        self.nb_points > 0
+            && self.unsorted.is_empty()
+            && self.duplicated.is_empty()
+            && self.missing.is_empty()
+    }
+
+    /// True if the spacing of the DEM is regular and without gap
+    pub fn is_regular(&self) -> bool {
+        self.nb_irregular == 0 && self.gaps.is_empty()
+    }
+
+    /// Description of each detected problem
+    pub fn messages(&self) -> Vec<String> {
+        let mut messages = vec![];
+        if self.nb_points == 0 {
+            messages.push(String::from("The DEM is empty"));
+        }
+        if !self.unsorted.is_empty() {
+            messages.push(format!(
+                "{} points have a x lower than the previous point",
+                self.unsorted.len()
+            ));
+        }
+        if !self.duplicated.is_empty() {
+            messages.push(format!(
+                "{} points have a duplicated x",
+                self.duplicated.len()
+            ));
+        }
+        if !self.missing.is_empty() {
+            messages.push(format!(
+                "{} points have a missing x or elevation",
+                self.missing.len()
+            ));
+        }
+        if self.nb_irregular > 0 {
+            messages.push(format!(
+                "{} steps differ from the median spacing of {}",
+                self.nb_irregular,
+                self.spacing.unwrap_or_default()
+            ));
+        }
+        for gap in self.gaps.iter() {
+            messages.push(format!("Gap between x = {} and x = {}", gap[0], gap[1]));
+        }
+        messages
+    }
+}
+
+/// Repair operations applied to the sampling of a DEM, in the order of the fields
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct DemRepair {
+    /// Sort the points by increasing x
+    pub sort: bool,
+    /// Merge the points with the same x into one point at their mean elevation
+    pub deduplicate: bool,
+    /// Remove the points without x and interpolate the missing elevations
+    pub fill_missing: bool,
+    /// Insert points at the median spacing in the gaps, interpolating their elevation
+    pub fill_gaps: bool,
+    /// Resample the whole DEM with this regular spacing from its first point
+    pub resample: Option<f32>,
+}
+
+impl DemRepair {
+    /// Repair the sampling `x` and the elevations `z` into a valid DEM
+    ///
+    /// # Errors
+    /// * the length of the x and z vectors differ
+    /// * the resampling spacing is not strictly positive
+    /// * the repaired DEM is still not valid
+    pub fn apply(&self, x: &[f32], z: &[f32]) -> Result<Dem1D, VectorInputError> {
+        if x.len() != z.len() {
+            return Err(VectorInputError::InconsistentLen {
+                vec1: x.len(),
+                vec2: z.len(),
+            });
+        }
+        let mut points: Vec<(f32, f32)> = x.iter().copied().zip(z.iter().copied()).collect();
+
+        if self.fill_missing {
+            points.retain(|(x, _)| x.is_finite());
+        }
+        if self.sort {
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+        if self.deduplicate {
+            let mut merged: Vec<(f32, f32)> = Vec::with_capacity(points.len());
+            let mut k = 0;
+            while k < points.len() {
+                let mut next = k + 1;
+                while next < points.len() && points[next].0 == points[k].0 {
+                    next += 1;
+                }
+                let finite: Vec<f32> = points[k..next]
+                    .iter()
+                    .map(|p| p.1)
+                    .filter(|z| z.is_finite())
+                    .collect();
+                let z = if finite.is_empty() {
+                    f32::NAN
+                } else {
+                    finite.iter().sum::<f32>() / finite.len() as f32
+                };
+                merged.push((points[k].0, z));
+                k = next;
+            }
+            points = merged;
+        }
+        let is_sorted = points.windows(2).all(|w| w[0].0 <= w[1].0);
+        if self.fill_missing && is_sorted {
+            let (known_x, known_z): (Vec<f32>, Vec<f32>) =
+                points.iter().filter(|p| p.1.is_finite()).copied().unzip();
+            if !known_x.is_empty() {
+                for p in points.iter_mut().filter(|p| !p.1.is_finite()) {
+                    p.1 = interpol_linear(&known_x, &known_z, &[p.0])[0];
+                }
+            }
+        }
+
+        let (mut x, mut z): (Vec<f32>, Vec<f32>) = points.into_iter().unzip();
+        if self.fill_gaps && is_sorted {
+            if let Some(spacing) = DemReport::new(&x, &z)?.spacing {
+                let mut new_x = vec![];
+                for (k, xk) in x.iter().enumerate() {
+                    new_x.push(*xk);
+                    if let Some(next) = x.get(k + 1) {
+                        if next - xk > GAP_RATIO * spacing {
+                            let nb_inserted = ((next - xk) / spacing).round() as usize;
+                            let step = (next - xk) / nb_inserted as f32;
+                            new_x.extend((1..nb_inserted).map(|i| xk + i as f32 * step));
+                        }
+                    }
+                }
+                z = interpol_linear(&x, &z, &new_x);
+                x = new_x;
+            }
+        }
+
+        if !DemReport::new(&x, &z)?.is_valid() {
+            return Err(VectorInputError::InvalidDem);
+        }
+        let dem = Dem1D::new(x, z)?;
+        match self.resample {
+            Some(spacing) => dem.resample(spacing, None),
+            None => Ok(dem),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_dem_report_repair() {
+        let x = vec![0., 10., 30., 20., 20., 40., 80., 90.];
+        let z = vec![0., 1., 3., 2., f32::NAN, 4., 8., 9.];
+        let report = DemReport::new(&x, &z).unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.unsorted, vec![3]);
+        assert_eq!(report.duplicated, vec![4]);
+        assert_eq!(report.missing, vec![4]);
+        assert_eq!(report.spacing, Some(10.));
+        assert_eq!(report.nb_irregular, 1);
+        assert_eq!(report.gaps, vec![[40., 80.]]);
+        assert_eq!(report.messages().len(), 5);
+
+        // Sorting alone leaves the duplicates
+        let repair = DemRepair {
+            sort: true,
+            ..Default::default()
+        };
+        assert!(repair.apply(&x, &z).is_err());
+
+        let repair = DemRepair {
+            sort: true,
+            deduplicate: true,
+            fill_missing: true,
+            fill_gaps: true,
+            resample: None,
+        };
+        let dem = repair.apply(&x, &z).unwrap();
+        assert_eq!(dem.x, vec![0., 10., 20., 30., 40., 50., 60., 70., 80., 90.]);
+        assert_approx_eq!(dem.surface.z[2], 2.);
+        assert_approx_eq!(dem.surface.z[5], 5.);
+        let report = DemReport::new(&dem.x, &dem.surface.z).unwrap();
+        assert!(report.is_valid() && report.is_regular());
+
+        let dem = DemRepair {
+            resample: Some(25.),
+            ..repair
+        }
+        .apply(&x, &z)
+        .unwrap();
+        assert_eq!(dem.x, vec![0., 25., 50., 75.]);
+        assert_approx_eq!(dem.surface.z[3], 7.5);
+    }
+}
@@ -56,10 +56,18 @@ impl AppDM {
                         self.open_command(ProjectCommand::OpenDem(OpenDem::default()));
                         ui.close_menu();
                     }
+                    if ui.button(Self::header("From raster / point cloud")).clicked() {
+                        self.open_command(ProjectCommand::OpenDemRaster(OpenDemRaster::default()));
+                        ui.close_menu();
+                    }
                     if ui.button(Self::header("Define Geometry")).clicked() {
                         self.open_command(ProjectCommand::DemGeometry(DemGeometry::default()));
                         ui.close_menu();
                     }
+                    if ui.button(Self::header("Resample")).clicked() {
+                        self.open_command(ProjectCommand::ResampleDem(ResampleDem::default()));
+                        ui.close_menu();
+                    }
                 });
             });
 
@@ -87,6 +95,10 @@ impl AppDM {
                         self.open_command(ProjectCommand::SlblRoutine(SlblRoutine::default()));
                         ui.close_menu();
                     }
+                    if ui.button(Self::header("SLBL search")).clicked() {
+                        self.open_command(ProjectCommand::SearchSlbl(SearchSlbl::default()));
+                        ui.close_menu();
+                    }
                 });
                 ui.menu_button(header_from_surfaces, |ui| {
                     if ui.button(Self::header("Minimum")).clicked() {
@@ -111,6 +123,8 @@ impl AppDM {
                     Self::header(Phosphor::ROWS_PLUS_TOP.to_string() + " New model");
                 let header_calibrate_model =
                     Self::header(Phosphor::TRAY_ARROW_DOWN.to_string() + " Calibrate model");
+                let header_calibrate_series =
+                    Self::header(Phosphor::CHART_LINE_UP.to_string() + " Calibrate over time");
                 let header_analysis =
                     Self::header(Phosphor::MAGNIFYING_GLASS_PLUS.to_string() + " Analyse & Export");
 
@@ -122,6 +136,10 @@ impl AppDM {
                     self.open_command(ProjectCommand::CalibrateModel(CalibrateModel::default()));
                     ui.close_menu();
                 }
+                if ui.button(header_calibrate_series).clicked() {
+                    self.open_command(ProjectCommand::CalibrateSeries(CalibrateSeries::default()));
+                    ui.close_menu();
+                }
                 if ui.button(header_analysis).clicked() {
                     self.open_command(ProjectCommand::ModelAnalysis(ModelAnalysis::default()));
                     ui.close_menu();
@@ -133,6 +151,8 @@ impl AppDM {
                 ui.set_max_width(200.);
                 let header_new_satellite_geometry =
                     Self::header(Phosphor::COMPASS_TOOL.to_string() + " New acquisition geometry");
+                let header_sensitivity =
+                    Self::header(Phosphor::EYE.to_string() + " Line of sight sensitivity");
                 let header_displacement_data =
                     Self::header(Phosphor::ARROWS_OUT_CARDINAL.to_string() + " Displacement data");
 
@@ -140,11 +160,30 @@ impl AppDM {
                     self.open_command(ProjectCommand::SatGeometry(SatGeometry::default()));
                     ui.close_menu();
                 }
+                if ui.button(header_sensitivity).clicked() {
+                    self.open_command(ProjectCommand::SarSensitivity(SarSensitivity::default()));
+                    ui.close_menu();
+                }
                 ui.menu_button(header_displacement_data, |ui| {
                     if ui.button(Self::header("From file")).clicked() {
                         self.open_command(ProjectCommand::OpenDisp(OpenDisp::default()));
                         ui.close_menu();
                     }
+                    if ui.button(Self::header("Project on section")).clicked() {
+                        self.open_command(ProjectCommand::ProjectDisp(ProjectDisp::default()));
+                        ui.close_menu();
+                    }
+                    if ui.button(Self::header("Bin along section")).clicked() {
+                        self.open_command(ProjectCommand::BinDisp(BinDisp::default()));
+                        ui.close_menu();
+                    }
+                    if ui
+                        .button(Self::header("Decompose two geometries"))
+                        .clicked()
+                    {
+                        self.open_command(ProjectCommand::DecomposeLos(DecomposeLos::default()));
+                        ui.close_menu();
+                    }
                 });
             });
 
@@ -1,7 +1,7 @@
 use super::{AppDM, ProjectCommand};
 use eframe::egui;
 use egui_phosphor::regular as Phosphor;
-use egui_plot::{Arrows, Line, Plot, PlotBounds, Points};
+use egui_plot::{Arrows, HLine, Line, Plot, PlotBounds, Points};
 
 impl AppDM {
     pub(super) fn ui_viewer(&mut self, ui: &mut egui::Ui) {
@@ -36,6 +36,8 @@ impl AppDM {
                 if button_properties.on_hover_text("Properties").clicked() {
                     match self.current_command {
                         ProjectCommand::ModelAnalysis(_) => self.is_viewer_properties = true,
+                        ProjectCommand::CalibrateSeries(_) => self.is_viewer_properties = true,
+                        ProjectCommand::SarSensitivity(_) => self.is_viewer_properties = true,
                         _ => (),
                     }
                 }
@@ -48,6 +50,7 @@ impl AppDM {
         let mut arrows: Vec<Arrows> = vec![];
         let mut dem_line: Vec<Line> = vec![];
         let mut pillar_lines: Vec<Line> = vec![];
+        let mut flagged_points: Vec<Points> = vec![];
         let x_len = self.project.dem.dem.x.len();
 
         if !self.project.surfaces.is_empty() {
@@ -129,6 +132,85 @@ impl AppDM {
                 }
             }
         }
+        // Plot displacement data projected on the section
+        for sar in &self.project.sars {
+            for bundle in &sar.disp_data {
+                let disp_data = &bundle.disp_data;
+                if disp_data.projected_vecs.is_empty() | !bundle.section_arrow {
+                    continue;
+                }
+                let z = self
+                    .project
+                    .dem
+                    .dem
+                    .interpolate_elevation_on_x(&disp_data.x);
+                let mut base = Vec::with_capacity(disp_data.x.len());
+                let mut tip = Vec::with_capacity(disp_data.x.len());
+                let mut flagged = vec![];
+                for (k, (x, z)) in disp_data.x.iter().zip(z.iter()).enumerate() {
+                    if disp_data.ill_conditioned[k] {
+                        flagged.push([*x as f64, *z as f64]);
+                        continue;
+                    }
+                    let coords = disp_data.projected_vecs[k].coords();
+                    base.push([*x as f64, *z as f64]);
+                    tip.push([
+                        (x + coords.0 * bundle.arrow_scaling_factor) as f64,
+                        (z + coords.1 * bundle.arrow_scaling_factor) as f64,
+                    ]);
+                }
+                let mut arrow = Arrows::new(base, tip).name("Projected Data");
+                if let Some(c) = bundle.color_arrow {
+                    arrow = arrow.color(egui::Color32::from_rgb(c[0], c[1], c[2]));
+                }
+                arrows.push(arrow);
+                if !flagged.is_empty() {
+                    flagged_points.push(
+                        Points::new(flagged)
+                            .shape(egui_plot::MarkerShape::Cross)
+                            .radius(4.)
+                            .color(egui::Color32::RED)
+                            .name("Ill-conditioned Projection"),
+                    );
+                }
+            }
+        }
+        // Plot displacement decomposed from two lines of sight
+        for bundle in &self.project.decompositions {
+            if !bundle.section_arrow {
+                continue;
+            }
+            let decomposition = &bundle.decomposition;
+            let mut base = Vec::with_capacity(decomposition.x.len());
+            let mut tip = Vec::with_capacity(decomposition.x.len());
+            let mut flagged = vec![];
+            for (k, origins) in decomposition.profile.origins.iter().enumerate() {
+                if decomposition.ill_conditioned[k] {
+                    flagged.push([origins[0] as f64, origins[1] as f64]);
+                    continue;
+                }
+                let coords = decomposition.profile.vecs[k].coords();
+                base.push([origins[0] as f64, origins[1] as f64]);
+                tip.push([
+                    (origins[0] + coords.0 * bundle.arrow_scaling_factor) as f64,
+                    (origins[1] + coords.1 * bundle.arrow_scaling_factor) as f64,
+                ]);
+            }
+            let mut arrow = Arrows::new(base, tip).name("Decomposed Data");
+            if let Some(c) = bundle.color_arrow {
+                arrow = arrow.color(egui::Color32::from_rgb(c[0], c[1], c[2]));
+            }
+            arrows.push(arrow);
+            if !flagged.is_empty() {
+                flagged_points.push(
+                    Points::new(flagged)
+                        .shape(egui_plot::MarkerShape::Cross)
+                        .radius(4.)
+                        .color(egui::Color32::RED)
+                        .name("Ill-conditioned Decomposition"),
+                );
+            }
+        }
         // Plot DEM elevation
         if !self.project.dem.dem.x.is_empty() & self.project.dem.section_surface {
             let line = Line::new({
@@ -200,6 +282,9 @@ impl AppDM {
                 for arrow in arrows {
                     plot_ui.arrows(arrow);
                 }
+                for points in flagged_points {
+                    plot_ui.points(points);
+                }
                 for line in surface_lines {
                     plot_ui.line(line);
                 }
@@ -212,6 +297,7 @@ impl AppDM {
     fn ui_viewer_properties(&mut self, ui: &mut egui::Ui) {
         let mut lines = vec![];
         let mut points = vec![];
+        let mut hlines = vec![];
 
         match &self.current_command {
             ProjectCommand::ModelAnalysis(data) => {
@@ -260,6 +346,94 @@ impl AppDM {
                     points.push(amp_points.name("Model Amplitude").radius(4.));
                     points.push(amp_los_points.name("Model in LOS").radius(4.));
                     points.push(data_points.name("Displacement Data").radius(5.));
+                    // Error bars of one standard deviation around the data, a single legend
+                    // entry for all of them
+                    for (k, ((x, amp), sigma)) in data_x
+                        .iter()
+                        .zip(data.amp_data.iter())
+                        .zip(data.sigma_data.iter())
+                        .enumerate()
+                    {
+                        let bar = Line::new(vec![
+                            [*x as f64, (amp - sigma) as f64],
+                            [*x as f64, (amp + sigma) as f64],
+                        ])
+                        .color(egui::Color32::GRAY);
+                        lines.push(match k {
+                            0 => bar.name("Data Standard Deviation"),
+                            _ => bar,
+                        });
+                    }
+
+                    let residual_points: Vec<[f64; 2]> = data_x
+                        .iter()
+                        .zip(data.residuals.iter())
+                        .map(|(a, b)| [*a as f64, *b as f64])
+                        .collect();
+                    lines.push(
+                        Line::new(residual_points.clone())
+                            .name("Residuals")
+                            .style(egui_plot::LineStyle::dashed_loose()),
+                    );
+                    points.push(
+                        Points::new(residual_points)
+                            .name("Residuals")
+                            .shape(egui_plot::MarkerShape::Diamond)
+                            .radius(4.),
+                    );
+                    hlines.push(HLine::new(0.).color(egui::Color32::DARK_GRAY).name("Zero"));
+                }
+            }
+            ProjectCommand::CalibrateSeries(data) => {
+                if let Some(fit) = &self.project.models[data.model].series_fit {
+                    // Weights of each surface against the dates
+                    let times = fit.weights.times();
+                    let nb_surfaces = fit.weights.weights.first().map_or(0, |w| w.len());
+                    for surface in 0..nb_surfaces {
+                        let weight_points: Vec<[f64; 2]> = times
+                            .iter()
+                            .zip(fit.weights.surface_weights(surface).iter())
+                            .map(|(a, b)| [*a as f64, *b as f64])
+                            .collect();
+                        let name = format!("Weight of surface {}", surface + 1);
+                        lines.push(Line::new(weight_points.clone()).name(&name));
+                        points.push(Points::new(weight_points).name(&name).radius(4.));
+                    }
+                }
+            }
+            ProjectCommand::SarSensitivity(data) => {
+                if let Some(sensitivity) = &data.sensitivity {
+                    let factor_points: Vec<[f64; 2]> = sensitivity
+                        .x
+                        .iter()
+                        .zip(sensitivity.factor.iter())
+                        .map(|(a, b)| [*a as f64, *b as f64])
+                        .collect();
+                    lines.push(Line::new(factor_points).name("LOS Sensitivity").width(2.));
+                    let blind_points: Vec<[f64; 2]> = sensitivity
+                        .x
+                        .iter()
+                        .zip(sensitivity.factor.iter())
+                        .zip(sensitivity.blind.iter())
+                        .filter(|(_, blind)| **blind)
+                        .map(|((a, b), _)| [*a as f64, *b as f64])
+                        .collect();
+                    points.push(
+                        Points::new(blind_points)
+                            .shape(egui_plot::MarkerShape::Cross)
+                            .radius(4.)
+                            .color(egui::Color32::RED)
+                            .name("Blind Zone"),
+                    );
+                    for threshold in [data.min_sensitivity, -data.min_sensitivity] {
+                        hlines.push(
+                            HLine::new(threshold)
+                                .color(egui::Color32::GRAY)
+                                .style(egui_plot::LineStyle::dashed_loose())
+                                .name("Minimum Sensitivity"),
+                        );
+                    }
+                    hlines.push(HLine::new(0.).color(egui::Color32::DARK_GRAY).name("Zero"));
                 }
             }
             _ => (),
@@ -276,6 +450,9 @@ impl AppDM {
                 for point in points {
                     plot_ui.points(point);
                 }
+                for hline in hlines {
+                    plot_ui.hline(hline);
+                }
             });
     }
 }
@@ -1,4 +1,7 @@
-use crate::{app::AppDM, project::BundleSar};
+use crate::{
+    app::AppDM,
+    project::{geometry_label, io::sidecar_path, read_profile_file, BundleSar},
+};
 use eframe::egui;
 use egui_phosphor::regular as Phosphor;
 use src_logic::prelude::*;
@@ -9,16 +12,24 @@ pub(crate) enum ProjectCommand {
     NoCommand,
     Note(Note),
     OpenDem(OpenDem),
+    OpenDemRaster(OpenDemRaster),
     DemGeometry(DemGeometry),
+    ResampleDem(ResampleDem),
     OpenSurface(OpenSurface),
     SlblExact(SlblExact),
     SlblRoutine(SlblRoutine),
+    SearchSlbl(SearchSlbl),
     SurfaceMin(SurfaceMin),
     SurfaceMax(SurfaceMax),
     ModelNew(ModelNew),
     SatGeometry(SatGeometry),
+    SarSensitivity(SarSensitivity),
     OpenDisp(OpenDisp),
+    ProjectDisp(ProjectDisp),
+    BinDisp(BinDisp),
+    DecomposeLos(DecomposeLos),
     CalibrateModel(CalibrateModel),
+    CalibrateSeries(CalibrateSeries),
     SurfaceExport(SurfaceExport),
     ModelAnalysis(ModelAnalysis),
 }
@@ -30,6 +41,7 @@ impl AppDM {
             ProjectCommand::NoCommand => self.ui_no_command(ui),
             ProjectCommand::Note(_) => self.ui_note(ui),
             ProjectCommand::OpenDem(_) => self.ui_open_dem(ui),
+            ProjectCommand::OpenDemRaster(_) => self.ui_open_dem_raster(ui),
             ProjectCommand::DemGeometry(_) => {
                 if dem_loaded {
                     self.ui_dem_geometry(ui)
@@ -37,6 +49,13 @@ impl AppDM {
                     self.ui_no_dem(ui)
                 }
             }
+            ProjectCommand::ResampleDem(_) => {
+                if dem_loaded {
+                    self.ui_resample_dem(ui)
+                } else {
+                    self.ui_no_dem(ui)
+                }
+            }
             ProjectCommand::OpenSurface(_) => {
                 if dem_loaded {
                     self.ui_open_surface(ui)
@@ -58,6 +77,13 @@ impl AppDM {
                     self.ui_no_dem(ui)
                 }
             }
+            ProjectCommand::SearchSlbl(_) => {
+                if dem_loaded {
+                    self.ui_search_slbl(ui)
+                } else {
+                    self.ui_no_dem(ui)
+                }
+            }
             ProjectCommand::SurfaceMin(_) => {
                 if dem_loaded {
                     self.ui_surface_min(ui)
@@ -86,6 +112,13 @@ impl AppDM {
                     self.ui_no_dem(ui)
                 }
             }
+            ProjectCommand::SarSensitivity(_) => {
+                if dem_loaded {
+                    self.ui_sar_sensitivity(ui)
+                } else {
+                    self.ui_no_dem(ui)
+                }
+            }
             ProjectCommand::OpenDisp(_) => {
                 if dem_loaded {
                     self.ui_open_disp(ui)
@@ -93,6 +126,27 @@ impl AppDM {
                     self.ui_no_dem(ui)
                 }
             }
+            ProjectCommand::ProjectDisp(_) => {
+                if dem_loaded {
+                    self.ui_project_disp(ui)
+                } else {
+                    self.ui_no_dem(ui)
+                }
+            }
+            ProjectCommand::BinDisp(_) => {
+                if dem_loaded {
+                    self.ui_bin_disp(ui)
+                } else {
+                    self.ui_no_dem(ui)
+                }
+            }
+            ProjectCommand::DecomposeLos(_) => {
+                if dem_loaded {
+                    self.ui_decompose_los(ui)
+                } else {
+                    self.ui_no_dem(ui)
+                }
+            }
             ProjectCommand::CalibrateModel(_) => {
                 if dem_loaded {
                     self.ui_calibrate_model(ui)
@@ -100,6 +154,13 @@ impl AppDM {
                     self.ui_no_dem(ui)
                 }
             }
+            ProjectCommand::CalibrateSeries(_) => {
+                if dem_loaded {
+                    self.ui_calibrate_series(ui)
+                } else {
+                    self.ui_no_dem(ui)
+                }
+            }
             ProjectCommand::SurfaceExport(_) => {
                 if dem_loaded {
                     self.ui_surface_export(ui)
@@ -143,16 +204,107 @@ pub enum CommandStatus {
 #[derive(Debug, Default, Clone)]
 pub struct Note {}
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct OpenDem {
     status: CommandStatus,
     file_path: Option<String>,
+    values: Option<(Vec<f32>, Vec<f32>)>,
+    report: Option<DemReport>,
+    repair: DemRepair,
+    resample: bool,
+    spacing: f32,
+}
+
+impl Default for OpenDem {
+    fn default() -> Self {
+        OpenDem {
+            status: CommandStatus::default(),
+            file_path: None,
+            values: None,
+            report: None,
+            repair: DemRepair::default(),
+            resample: false,
+            spacing: 10.,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenDemRaster {
+    status: CommandStatus,
+    file_path: Option<String>,
+    start: [f64; 2],
+    end: [f64; 2],
+    crs: String,
+    spacing: f32,
+    buffer: f32,
+    aggregation: PointAggregation,
+}
+
+impl Default for OpenDemRaster {
+    fn default() -> Self {
+        OpenDemRaster {
+            status: CommandStatus::default(),
+            file_path: None,
+            start: [0., 0.],
+            end: [0., 0.],
+            crs: String::new(),
+            spacing: 10.,
+            buffer: 5.,
+            aggregation: PointAggregation::default(),
+        }
+    }
+}
+
+/// Format of a DEM given in map coordinates, from the extension of its file
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MapDemFormat {
+    GeoTiff,
+    EsriAscii,
+    PointCloud,
+}
+
+impl MapDemFormat {
+    fn from_path(path: &str) -> Self {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        match extension.as_deref() {
+            Some("asc") => MapDemFormat::EsriAscii,
+            Some("xyz") | Some("txt") | Some("csv") => MapDemFormat::PointCloud,
+            _ => MapDemFormat::GeoTiff,
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct DemGeometry {
     status: CommandStatus,
     azimuth: f32,
+    from_map_points: bool,
+    start: [f64; 2],
+    end: [f64; 2],
+    crs: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResampleDem {
+    status: CommandStatus,
+    spacing: f32,
+    with_range: bool,
+    range: Option<[f32; 2]>,
+}
+
+impl Default for ResampleDem {
+    fn default() -> Self {
+        ResampleDem {
+            status: CommandStatus::default(),
+            spacing: 10.,
+            with_range: false,
+            range: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -160,6 +312,11 @@ pub struct OpenSurface {
     status: CommandStatus,
     file_path: Option<String>,
     surface_name: String,
+    samples: Option<(Vec<f32>, Vec<f32>)>,
+    interpolate: bool,
+    method: SurfaceInterpolation,
+    depth: bool,
+    depth_mode: DepthMode,
 }
 
 impl Default for OpenSurface {
@@ -168,6 +325,11 @@ impl Default for OpenSurface {
             status: CommandStatus::default(),
             file_path: None,
             surface_name: String::from("New surface"),
+            samples: None,
+            interpolate: false,
+            method: SurfaceInterpolation::default(),
+            depth: false,
+            depth_mode: DepthMode::default(),
         }
     }
 }
@@ -216,6 +378,35 @@ impl Default for SlblRoutine {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct SearchSlbl {
+    status: CommandStatus,
+    promote_status: CommandStatus,
+    datasets: Vec<(usize, usize)>,
+    search: SlblSearch,
+    candidates: Vec<SlblCandidate>,
+    selected: Vec<bool>,
+}
+
+impl Default for SearchSlbl {
+    fn default() -> Self {
+        SearchSlbl {
+            status: CommandStatus::default(),
+            promote_status: CommandStatus::default(),
+            datasets: vec![],
+            search: SlblSearch {
+                first_pnt: [0, 0],
+                last_pnt: [1, 1],
+                point_step: 1,
+                tol: [0.5, 2.],
+                nb_tol: 4,
+            },
+            candidates: vec![],
+            selected: vec![],
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SurfaceMin {
     status: CommandStatus,
@@ -279,14 +470,138 @@ pub struct OpenDisp {
     sar_index: usize,
     file_path: Option<String>,
     name: String,
+    time_series: bool,
 }
 
-#[derive(Debug, Default, Clone)]
-pub struct CalibrateModel {
+/// Displacement profile whose sensitivity to a line of sight is computed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SensitivitySource {
+    Surface(usize),
+    Model(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct SarSensitivity {
+    status: CommandStatus,
+    sar_geom: usize,
+    source: SensitivitySource,
+    pub(crate) min_sensitivity: f32,
+    pub(crate) sensitivity: Option<LosSensitivity>,
+}
+
+impl Default for SarSensitivity {
+    fn default() -> Self {
+        SarSensitivity {
+            status: CommandStatus::default(),
+            sar_geom: 0,
+            source: SensitivitySource::Surface(0),
+            min_sensitivity: 0.2,
+            sensitivity: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectDisp {
+    status: CommandStatus,
+    sar_geom: usize,
+    sar_data: usize,
+    surface: Option<usize>,
+    min_sensitivity: f32,
+}
+
+impl Default for ProjectDisp {
+    fn default() -> Self {
+        ProjectDisp {
+            status: CommandStatus::default(),
+            sar_geom: 0,
+            sar_data: 0,
+            surface: None,
+            min_sensitivity: 0.2,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BinDisp {
     status: CommandStatus,
-    model: usize,
     sar_geom: usize,
     sar_data: usize,
+    width: f32,
+    min_count: usize,
+}
+
+impl Default for BinDisp {
+    fn default() -> Self {
+        BinDisp {
+            status: CommandStatus::default(),
+            sar_geom: 0,
+            sar_data: 0,
+            width: 50.,
+            min_count: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DecomposeLos {
+    status: CommandStatus,
+    export_status: CommandStatus,
+    first: (usize, usize),
+    second: (usize, usize),
+    basis: DecompositionBasis,
+    min_determinant: f32,
+    name: String,
+    decomposition: Option<usize>,
+    file_path: Option<String>,
+}
+
+impl Default for DecomposeLos {
+    fn default() -> Self {
+        DecomposeLos {
+            status: CommandStatus::default(),
+            export_status: CommandStatus::default(),
+            first: (0, 0),
+            second: (0, 0),
+            basis: DecompositionBasis::default(),
+            min_determinant: 0.1,
+            name: String::from("decomposition"),
+            decomposition: None,
+            file_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CalibrateModel {
+    status: CommandStatus,
+    model: usize,
+    datasets: Vec<(usize, usize)>,
+    settings: CalibrationSettings,
+    bootstrap: bool,
+    bootstrap_samples: usize,
+}
+
+impl Default for CalibrateModel {
+    fn default() -> Self {
+        CalibrateModel {
+            status: CommandStatus::default(),
+            model: 0,
+            datasets: vec![],
+            settings: CalibrationSettings::new(0),
+            bootstrap: false,
+            bootstrap_samples: 200,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct CalibrateSeries {
+    status: CommandStatus,
+    pub(crate) model: usize,
+    sar: usize,
+    series: usize,
+    solver: Solver,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -308,8 +623,11 @@ pub struct ModelAnalysis {
     pub(crate) amp_in_los: Vec<f32>,
     pub(crate) full_amp_in_los: Vec<f32>,
     pub(crate) amp_data: Vec<f32>,
+    pub(crate) sigma_data: Vec<f32>,
     pub(crate) data_x: Vec<f32>,
-    rmse: f32,
+    pub(crate) residuals: Vec<f32>,
+    metrics: Option<FitMetrics>,
+    reduced_chi_square: Option<f32>,
     file_path: Option<String>,
 }
 
@@ -383,6 +701,7 @@ impl AppDM {
                 ui.label("Use this command to load a Digital Elevation Model (DEM) from an existing file.");
                 ui.label("The file should be a csv file with the headers 'x' for the sampling values and 'z' for the elevation values.");
                 ui.label("Note that the sampling values should be equally spaced.");
+                ui.label("The file is checked once selected, and the detected problems can be repaired before opening it.");
                 ui.add_space(5.);
                 ui.separator();
                 ui.add_space(15.);
@@ -391,7 +710,14 @@ impl AppDM {
                 }
                 if ui.button(egui::RichText::new("Select file").size(18.)).clicked() {
                     if let Some(path) = rfd::FileDialog::new().pick_file() {
-                        data.file_path = Some(path.display().to_string());
+                        let path = path.display().to_string();
+                        data.values = read_profile_file(path.clone(), "z").ok();
+                        data.report = data.values.as_ref().and_then(|(x, z)| DemReport::new(x, z).ok());
+                        data.status = match data.report {
+                            Some(_) => CommandStatus::Clean,
+                            None => CommandStatus::Error(CommandError::InvalidFile),
+                        };
+                        data.file_path = Some(path);
                     }
                 }
                 ui.add_space(5.);
@@ -403,6 +729,28 @@ impl AppDM {
                         });
                     });
                 }
+                if let Some(report) = &data.report {
+                    ui.add_space(5.);
+                    ui.label(format!("{} points, median spacing: {}", report.nb_points, report.spacing.unwrap_or_default()));
+                    let messages = report.messages();
+                    if messages.is_empty() {
+                        ui.label("No problem detected");
+                    }
+                    for message in messages {
+                        ui.label(Phosphor::WARNING.to_string() + " " + message.as_str());
+                    }
+                    ui.add_space(5.);
+                    ui.label("Repair:");
+                    ui.checkbox(&mut data.repair.sort, "Sort by increasing x");
+                    ui.checkbox(&mut data.repair.deduplicate, "Merge the duplicated x");
+                    ui.checkbox(&mut data.repair.fill_missing, "Interpolate the missing values");
+                    ui.checkbox(&mut data.repair.fill_gaps, "Interpolate the gaps");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut data.resample, "Resample with spacing");
+                        ui.add(egui::DragValue::new(&mut data.spacing).speed(0.1).range(0.01..=f32::MAX));
+                    });
+                    data.repair.resample = if data.resample { Some(data.spacing) } else { None };
+                }
             });
 
             ui.add_space(10.);
@@ -417,6 +765,10 @@ impl AppDM {
                         ui.label("Error: ");
                         ui.label("No file provided");
                     },
+                    CommandStatus::Error(CommandError::InputError) => {
+                        ui.label("Error: ");
+                        ui.label("The DEM is still unsorted, duplicated or with missing values after the repair");
+                    },
                     _ => (),
                 }
             });
@@ -433,13 +785,14 @@ impl AppDM {
                     if data.status != CommandStatus::Clean {
                         data.status = CommandStatus::Clean;
                     } else {
-                        match &data.file_path {
-                            Some(f) => {
-                                match self.project.open_dem_from_file(f.to_string()) {
-                                    Err(_) => data.status = CommandStatus::Error(CommandError::InvalidFile),
+                        match &data.values {
+                            Some((x, z)) => {
+                                match self.project.open_dem(x, z, &data.repair) {
+                                    Err(_) => data.status = CommandStatus::Error(CommandError::InputError),
                                     _ => data.status = CommandStatus::Complete,
                                 }
                             },
+                            None if data.file_path.is_some() => data.status = CommandStatus::Error(CommandError::InvalidFile),
                             None => data.status = CommandStatus::Error(CommandError::NoFile),
                         }
                     }
@@ -448,95 +801,41 @@ impl AppDM {
         });
     }
 
-    fn ui_dem_geometry(&mut self, ui: &mut egui::Ui) {
-        let title = egui::RichText::new("Set the Section Geometry").heading();
-        let ProjectCommand::DemGeometry(data) = &mut self.current_command else {
+    fn ui_open_dem_raster(&mut self, ui: &mut egui::Ui) {
+        let title = egui::RichText::new("Extract DEM from Raster or Point Cloud").heading();
+        let ProjectCommand::OpenDemRaster(data) = &mut self.current_command else {
             panic!("Wrong intern command assignation. Please report it if raised.")
             // Should never reach
         };
 
-        ui.with_layout(egui::Layout::top_down(egui::Align::Center).with_cross_justify(true), |ui| {
-            ui.vertical(|ui| {
-                ui.label(title);
-                ui.separator();
-                ui.add_space(10.);
-                ui.label("Use this command to configure the geometry of the 2D cross section.");
-                ui.label("The azimuth angle corresponds to the clockwise angle between the North and the increasing x-axis direction.");
-                ui.add_space(5.);
-                ui.separator();
-                ui.add_space(5.);
-                ui.add(egui::Slider::new(&mut data.azimuth, 0.0..=360.).text("Section azimuth"));
-            });
-        });
-
-        ui.add_space(10.);
-
-        ui.with_layout(
-            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
-            |ui| match &data.status {
-                CommandStatus::Error(CommandError::InvalidOrientation) => {
-                    ui.label("Invalid orientation");
-                }
-                _ => (),
-            },
-        );
-
-        ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
-            let apply_text = match data.status {
-                CommandStatus::Clean => egui::RichText::new("Apply"),
-                CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
-                CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
-            };
-            let apply_button = ui.button(apply_text.size(22.));
-
-            if apply_button.clicked() {
-                if data.status != CommandStatus::Clean {
-                    data.status = CommandStatus::Clean;
-                } else {
-                    let azimuth = if data.azimuth == 360. {
-                        0.
-                    } else {
-                        data.azimuth
-                    };
-                    match Orientation::from_deg(azimuth, 90.) {
-                        Err(_) => {
-                            data.status = CommandStatus::Error(CommandError::InvalidOrientation)
-                        } // Should never reach
-                        Ok(o) => {
-                            self.project.dem.section_geometry = Some(o);
-                            data.status = CommandStatus::Complete;
-                        }
-                    }
-                }
-            }
-        });
-    }
-
-    fn ui_open_surface(&mut self, ui: &mut egui::Ui) {
-        let title = egui::RichText::new("Open an Existing Surface from File").heading();
-        let ProjectCommand::OpenSurface(data) = &mut self.current_command else {
-            panic!("Wrong intern command assignation. Please report it if raised.")
-            // Should never reach
-        };
+        if !self.project.surfaces.is_empty()
+            | !self.project.models.is_empty()
+            | !self.project.sars.is_empty()
+        {
+            data.status = CommandStatus::Error(CommandError::ProjectInitialized);
+        }
 
         ui.with_layout(egui::Layout::top_down(egui::Align::Center).with_cross_justify(true), |ui| {
             ui.vertical(|ui| {
                 ui.label(title);
                 ui.separator();
                 ui.add_space(10.);
-                ui.label("Use this command to load a surface from file.");
-                ui.label("The file should be a csv file with the headers 'x' for the sampling values and 'z' for the elevation values.");
-                ui.label("Note that the sampling values should be the save as the project's DEM.");
+                ui.label("Use this command to extract the Digital Elevation Model (DEM) of the section from a GeoTIFF or ESRI ASCII (.asc) elevation raster, or from a XYZ point cloud.");
+                ui.label("A raster is sampled with a bilinear interpolation along the line from the start point to the end point, given in the projected coordinates of the file. The geometry of the section is derived from this line.");
+                ui.label("The points of a cloud closer to the line than the buffer are projected on it, and the elevations are aggregated in bins centered on each sampling point.");
                 ui.add_space(5.);
                 ui.separator();
                 ui.add_space(15.);
-                ui.horizontal(|ui| {
-                    ui.label("Name: ");
-                    ui.text_edit_singleline(&mut data.surface_name);
-                });
-                ui.add_space(5.);
+                if data.status == CommandStatus::Error(CommandError::ProjectInitialized) {
+                    ui.disable();
+                }
                 if ui.button(egui::RichText::new("Select file").size(18.)).clicked() {
-                    if let Some(path) = rfd::FileDialog::new().pick_file() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("GeoTIFF", &["tif", "tiff"])
+                        .add_filter("ESRI ASCII", &["asc"])
+                        .add_filter("Point cloud", &["xyz", "txt", "csv"])
+                        .pick_file()
+                    {
                         data.file_path = Some(path.display().to_string());
                     }
                 }
@@ -549,18 +848,413 @@ impl AppDM {
                         });
                     });
                 }
-            });
-        });
-
-        ui.add_space(10.);
-
-        ui.with_layout(
-            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
-            |ui| match &data.status {
-                CommandStatus::Error(e) => match e {
-                    CommandError::InvalidFile => ui.label("Invalid file"),
-                    CommandError::EmptyName => ui.label("Empty name"),
-                    _ => ui.label(""),
+                ui.add_space(5.);
+                egui::Grid::new("raster_section_grid").num_columns(3).show(ui, |ui| {
+                    ui.label("");
+                    ui.label("Easting");
+                    ui.label("Northing");
+                    ui.end_row();
+                    ui.label("Start point");
+                    ui.add(egui::DragValue::new(&mut data.start[0]));
+                    ui.add(egui::DragValue::new(&mut data.start[1]));
+                    ui.end_row();
+                    ui.label("End point");
+                    ui.add(egui::DragValue::new(&mut data.end[0]));
+                    ui.add(egui::DragValue::new(&mut data.end[1]));
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    ui.label("CRS (optional):");
+                    ui.text_edit_singleline(&mut data.crs);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Spacing:");
+                    ui.add(egui::DragValue::new(&mut data.spacing).speed(0.1).range(0.01..=f32::MAX));
+                });
+                let is_point_cloud = data.file_path.as_deref().map(MapDemFormat::from_path) == Some(MapDemFormat::PointCloud);
+                if is_point_cloud {
+                    ui.horizontal(|ui| {
+                        ui.label("Buffer:");
+                        ui.add(egui::DragValue::new(&mut data.buffer).speed(0.1).range(0.0..=f32::MAX));
+                    });
+                    egui::ComboBox::from_label("Aggregation")
+                        .selected_text(data.aggregation.name())
+                        .show_ui(ui, |ui| {
+                            for aggregation in PointAggregation::ALL {
+                                ui.selectable_value(&mut data.aggregation, aggregation, aggregation.name());
+                            }
+                        });
+                }
+                if let Ok(line) = SectionLine::new(data.start, data.end, None) {
+                    ui.label(format!(
+                        "Section azimuth: {:.1}°, length: {:.1}",
+                        line.azimuth().to_degrees(),
+                        line.length()
+                    ));
+                }
+            });
+
+            ui.add_space(10.);
+
+            ui.with_layout(egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true), |ui| {
+                match &data.status {
+                    CommandStatus::Error(CommandError::InvalidFile) => {
+                        ui.label("Error:");
+                        ui.label("Selected file is invalid");
+                    }
+                    CommandStatus::Error(CommandError::NoFile) => {
+                        ui.label("Error: ");
+                        ui.label("No file provided");
+                    }
+                    CommandStatus::Error(CommandError::InputError) => {
+                        ui.label("Error: ");
+                        ui.label("The start and end points should be distinct");
+                    }
+                    CommandStatus::Error(CommandError::MethodError) => {
+                        ui.label("Error: ");
+                        ui.label("The section should lie inside the raster, on pixels with data, or each bin should hold points of the cloud");
+                    }
+                    _ => (),
+                }
+            });
+
+            ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+                let apply_text = match data.status {
+                    CommandStatus::Clean => egui::RichText::new("Apply"),
+                    CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                    CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+                };
+                let apply_button = ui.button(apply_text.size(22.));
+
+                if apply_button.clicked() {
+                    if data.status != CommandStatus::Clean {
+                        data.status = CommandStatus::Clean;
+                    } else {
+                        let crs = Some(data.crs.trim().to_string()).filter(|c| !c.is_empty());
+                        match (&data.file_path, SectionLine::new(data.start, data.end, crs)) {
+                            (None, _) => data.status = CommandStatus::Error(CommandError::NoFile),
+                            (_, Err(_)) => {
+                                data.status = CommandStatus::Error(CommandError::InputError)
+                            }
+                            (Some(f), Ok(line)) => {
+                                let opened = match MapDemFormat::from_path(f) {
+                                    MapDemFormat::GeoTiff => Raster::from_geotiff(f.to_string()).map(|raster| {
+                                        self.project.open_dem_from_raster(&raster, line, data.spacing)
+                                    }),
+                                    MapDemFormat::EsriAscii => Raster::from_esri_ascii(f.to_string()).map(|raster| {
+                                        self.project.open_dem_from_raster(&raster, line, data.spacing)
+                                    }),
+                                    MapDemFormat::PointCloud => PointCloud::from_xyz(f.to_string()).map(|cloud| {
+                                        self.project.open_dem_from_point_cloud(&cloud, line, data.spacing, data.buffer, data.aggregation)
+                                    }),
+                                };
+                                match opened {
+                                    Err(_) => {
+                                        data.status = CommandStatus::Error(CommandError::InvalidFile)
+                                    }
+                                    Ok(Err(_)) => {
+                                        data.status = CommandStatus::Error(CommandError::MethodError)
+                                    }
+                                    Ok(Ok(_)) => data.status = CommandStatus::Complete,
+                                }
+                            }
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    fn ui_dem_geometry(&mut self, ui: &mut egui::Ui) {
+        let title = egui::RichText::new("Set the Section Geometry").heading();
+        let ProjectCommand::DemGeometry(data) = &mut self.current_command else {
+            panic!("Wrong intern command assignation. Please report it if raised.")
+            // Should never reach
+        };
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::Center).with_cross_justify(true), |ui| {
+            ui.vertical(|ui| {
+                ui.label(title);
+                ui.separator();
+                ui.add_space(10.);
+                ui.label("Use this command to configure the geometry of the 2D cross section.");
+                ui.label("The azimuth angle corresponds to the clockwise angle between the North and the increasing x-axis direction.");
+                ui.label("Alternatively, the section can be defined by its start and end points in projected map coordinates, from which the azimuth is derived. The x of the DEM is then the distance from the start point.");
+                ui.add_space(5.);
+                ui.separator();
+                ui.add_space(5.);
+                ui.checkbox(&mut data.from_map_points, "From map coordinates");
+                ui.add_space(5.);
+                if data.from_map_points {
+                    egui::Grid::new("section_line_grid").num_columns(3).show(ui, |ui| {
+                        ui.label("");
+                        ui.label("Easting");
+                        ui.label("Northing");
+                        ui.end_row();
+                        ui.label("Start point");
+                        ui.add(egui::DragValue::new(&mut data.start[0]));
+                        ui.add(egui::DragValue::new(&mut data.start[1]));
+                        ui.end_row();
+                        ui.label("End point");
+                        ui.add(egui::DragValue::new(&mut data.end[0]));
+                        ui.add(egui::DragValue::new(&mut data.end[1]));
+                        ui.end_row();
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("CRS (optional):");
+                        ui.text_edit_singleline(&mut data.crs);
+                    });
+                    if let Ok(line) = SectionLine::new(data.start, data.end, None) {
+                        ui.label(format!(
+                            "Section azimuth: {:.1}°, length: {:.1}",
+                            line.azimuth().to_degrees(),
+                            line.length()
+                        ));
+                        if let Some(x_max) = self.project.dem.dem.x.last() {
+                            ui.label(format!("DEM x extent: {:.1}", x_max));
+                        }
+                    }
+                } else {
+                    ui.add(egui::Slider::new(&mut data.azimuth, 0.0..=360.).text("Section azimuth"));
+                }
+            });
+        });
+
+        ui.add_space(10.);
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
+            |ui| match &data.status {
+                CommandStatus::Error(CommandError::InvalidOrientation) => {
+                    ui.label("Invalid orientation");
+                }
+                CommandStatus::Error(CommandError::InputError) => {
+                    ui.label("The start and end points should be distinct");
+                }
+                _ => (),
+            },
+        );
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+            let apply_text = match data.status {
+                CommandStatus::Clean => egui::RichText::new("Apply"),
+                CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+            };
+            let apply_button = ui.button(apply_text.size(22.));
+
+            if apply_button.clicked() {
+                if data.status != CommandStatus::Clean {
+                    data.status = CommandStatus::Clean;
+                } else if data.from_map_points {
+                    let crs = Some(data.crs.trim().to_string()).filter(|c| !c.is_empty());
+                    match SectionLine::new(data.start, data.end, crs) {
+                        Err(_) => data.status = CommandStatus::Error(CommandError::InputError),
+                        Ok(line) => {
+                            self.project.dem.section_geometry = Some(line.orientation());
+                            self.project.dem.section_line = Some(line);
+                            data.status = CommandStatus::Complete;
+                        }
+                    }
+                } else {
+                    let azimuth = if data.azimuth == 360. {
+                        0.
+                    } else {
+                        data.azimuth
+                    };
+                    match Orientation::from_deg(azimuth, 90.) {
+                        Err(_) => {
+                            data.status = CommandStatus::Error(CommandError::InvalidOrientation)
+                        } // Should never reach
+                        Ok(o) => {
+                            self.project.dem.section_geometry = Some(o);
+                            self.project.dem.section_line = None;
+                            data.status = CommandStatus::Complete;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    fn ui_resample_dem(&mut self, ui: &mut egui::Ui) {
+        let title = egui::RichText::new("Resample the DEM").heading();
+        let ProjectCommand::ResampleDem(data) = &mut self.current_command else {
+            panic!("Wrong intern command assignation. Please report it if raised.")
+            // Should never reach
+        };
+        let dem_x = &self.project.dem.dem.x;
+        let extent = [dem_x[0], dem_x[dem_x.len() - 1]];
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::Center).with_cross_justify(true), |ui| {
+            ui.vertical(|ui| {
+                ui.label(title);
+                ui.separator();
+                ui.add_space(10.);
+                ui.label("Use this command to move the DEM onto a new regular sampling, optionally restricted to a range of x.");
+                ui.label("The surfaces are interpolated on the new sampling and their displacement regenerated. The models are rebuilt from their weights, with their boundaries and gradient points moved to the closest new sampling points.");
+                ui.add_space(5.);
+                ui.separator();
+                ui.add_space(5.);
+                ui.label(format!("Current DEM: {} points from x = {} to x = {}", dem_x.len(), extent[0], extent[1]));
+                ui.add_space(5.);
+                ui.horizontal(|ui| {
+                    ui.label("Spacing:");
+                    ui.add(egui::DragValue::new(&mut data.spacing).speed(0.1).range(0.01..=f32::MAX));
+                });
+                ui.checkbox(&mut data.with_range, "Restrict the range");
+                if data.with_range {
+                    let range = data.range.get_or_insert(extent);
+                    ui.horizontal(|ui| {
+                        ui.label("From x:");
+                        ui.add(egui::DragValue::new(&mut range[0]).range(extent[0]..=extent[1]));
+                        ui.label("to x:");
+                        ui.add(egui::DragValue::new(&mut range[1]).range(extent[0]..=extent[1]));
+                    });
+                }
+            });
+        });
+
+        ui.add_space(10.);
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
+            |ui| match &data.status {
+                CommandStatus::Error(CommandError::MethodError) => {
+                    ui.label("Error:");
+                    ui.label("The sampling is invalid, or the boundaries of a model are merged by the new sampling");
+                }
+                _ => (),
+            },
+        );
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+            let apply_text = match data.status {
+                CommandStatus::Clean => egui::RichText::new("Apply"),
+                CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+            };
+            let apply_button = ui.button(apply_text.size(22.));
+
+            if apply_button.clicked() {
+                if data.status != CommandStatus::Clean {
+                    data.status = CommandStatus::Clean;
+                } else {
+                    let range = if data.with_range { data.range } else { None };
+                    match self.project.resample_dem(data.spacing, range) {
+                        Err(_) => data.status = CommandStatus::Error(CommandError::MethodError),
+                        Ok(_) => data.status = CommandStatus::Complete,
+                    }
+                }
+            }
+        });
+    }
+
+    fn ui_open_surface(&mut self, ui: &mut egui::Ui) {
+        let title = egui::RichText::new("Open an Existing Surface from File").heading();
+        let ProjectCommand::OpenSurface(data) = &mut self.current_command else {
+            panic!("Wrong intern command assignation. Please report it if raised.")
+            // Should never reach
+        };
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::Center).with_cross_justify(true), |ui| {
+            ui.vertical(|ui| {
+                ui.label(title);
+                ui.separator();
+                ui.add_space(10.);
+                ui.label("Use this command to load a surface from file.");
+                ui.label("The file should be a csv file with the headers 'x' for the sampling values and 'z' for the elevation values.");
+                ui.label("Note that the sampling values should be the same as the project's DEM, unless the surface is interpolated on it.");
+                ui.label("An interpolated surface falls back to the DEM outside the range of its sampling values.");
+                ui.label("Depths below the DEM are read from a 'depth' header instead, and are always interpolated on the DEM sampling.");
+                ui.add_space(5.);
+                ui.separator();
+                ui.add_space(15.);
+                ui.horizontal(|ui| {
+                    ui.label("Name: ");
+                    ui.text_edit_singleline(&mut data.surface_name);
+                });
+                ui.add_space(5.);
+                if ui.button(egui::RichText::new("Select file").size(18.)).clicked() {
+                    if let Some(path) = rfd::FileDialog::new().pick_file() {
+                        let path = path.display().to_string();
+                        let header = if data.depth { "depth" } else { "z" };
+                        data.samples = read_profile_file(path.clone(), header).ok();
+                        // Interpolate by default when the sampling differs from the DEM
+                        data.interpolate = data.samples.as_ref().is_some_and(|(x, _)| *x != self.project.dem.dem.x);
+                        data.file_path = Some(path);
+                    }
+                }
+                ui.add_space(5.);
+                if let Some(f) = &data.file_path {
+                    ui.horizontal(|ui| {
+                        ui.label("Selected file: ");
+                        egui::ScrollArea::horizontal().show(ui, |ui| {
+                            ui.label(f);
+                        });
+                    });
+                }
+                ui.add_space(5.);
+                if ui.checkbox(&mut data.depth, "Depths below the DEM").changed() {
+                    if let Some(f) = &data.file_path {
+                        let header = if data.depth { "depth" } else { "z" };
+                        data.samples = read_profile_file(f.to_string(), header).ok();
+                    }
+                }
+                if data.depth {
+                    egui::ComboBox::from_label("Depth measured")
+                        .selected_text(data.depth_mode.name())
+                        .show_ui(ui, |ui| {
+                            for mode in DepthMode::ALL {
+                                ui.selectable_value(&mut data.depth_mode, mode, mode.name());
+                            }
+                        });
+                }
+                ui.checkbox(&mut data.interpolate, "Interpolate on the DEM sampling");
+                if data.interpolate || data.depth {
+                    egui::ComboBox::from_label("Interpolation")
+                        .selected_text(data.method.name())
+                        .show_ui(ui, |ui| {
+                            for method in SurfaceInterpolation::ALL {
+                                ui.selectable_value(&mut data.method, method, method.name());
+                            }
+                        });
+                    if let Some((x, z)) = &data.samples {
+                        let dem = &self.project.dem.dem;
+                        let surface = if data.depth {
+                            Surface1D::from_depth_samples(x, z, dem, data.method, data.depth_mode)
+                        } else {
+                            Surface1D::from_samples(x, z, dem, data.method)
+                        };
+                        if let Ok(surface) = surface {
+                            let to_points = |x: &[f32], z: &[f32]| -> Vec<[f64; 2]> {
+                                x.iter().zip(z.iter()).map(|(x, z)| [*x as f64, *z as f64]).collect()
+                            };
+                            egui_plot::Plot::new("surface_preview")
+                                .height(200.)
+                                .data_aspect(1.)
+                                .show(ui, |plot_ui| {
+                                    plot_ui.line(egui_plot::Line::new(to_points(&dem.x, &dem.surface.z)).name("DEM"));
+                                    plot_ui.line(egui_plot::Line::new(to_points(&dem.x, &surface.z)).name("Interpolated"));
+                                    if !data.depth {
+                                        plot_ui.points(egui_plot::Points::new(to_points(x, z)).radius(3.).name("Samples"));
+                                    }
+                                });
+                        }
+                    }
+                }
+            });
+        });
+
+        ui.add_space(10.);
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
+            |ui| match &data.status {
+                CommandStatus::Error(e) => match e {
+                    CommandError::InvalidFile => ui.label("Invalid file"),
+                    CommandError::EmptyName => ui.label("Empty name"),
+                    _ => ui.label(""),
                 },
                 _ => ui.label(""),
             },
@@ -579,6 +1273,41 @@ impl AppDM {
                     data.status = CommandStatus::Clean;
                 } else {
                     match &data.file_path {
+                        Some(_) if data.depth => match &data.samples {
+                            Some((x, depths)) => {
+                                match self.project.open_surface_from_depths(
+                                    x,
+                                    depths,
+                                    data.surface_name.to_string(),
+                                    data.method,
+                                    data.depth_mode,
+                                ) {
+                                    Err(_) => {
+                                        data.status =
+                                            CommandStatus::Error(CommandError::InvalidFile)
+                                    }
+                                    _ => data.status = CommandStatus::Complete,
+                                }
+                            }
+                            None => data.status = CommandStatus::Error(CommandError::InvalidFile),
+                        },
+                        Some(_) if data.interpolate => match &data.samples {
+                            Some((x, z)) => {
+                                match self.project.open_surface_from_samples(
+                                    x,
+                                    z,
+                                    data.surface_name.to_string(),
+                                    data.method,
+                                ) {
+                                    Err(_) => {
+                                        data.status =
+                                            CommandStatus::Error(CommandError::InvalidFile)
+                                    }
+                                    _ => data.status = CommandStatus::Complete,
+                                }
+                            }
+                            None => data.status = CommandStatus::Error(CommandError::InvalidFile),
+                        },
                         Some(f) => {
                             if f.is_empty() {
                                 data.status = CommandStatus::Error(CommandError::EmptyName);
@@ -788,6 +1517,222 @@ impl AppDM {
         });
     }
 
+    fn ui_search_slbl(&mut self, ui: &mut egui::Ui) {
+        let title = egui::RichText::new("Search the SLBL Explaining the Data").heading();
+        let ProjectCommand::SearchSlbl(data) = &mut self.current_command else {
+            panic!("Wrong intern command assignation. Please report it if raised.")
+            // Should never reach
+        };
+
+        if self.project.sars.iter().all(|sar| sar.disp_data.is_empty())
+            | self.project.dem.section_geometry.is_none()
+        {
+            ui.label("No displacement data or dem geometry available.");
+            return;
+        }
+
+        let x = &self.project.dem.dem.x;
+        let last_index = x.len() - 1;
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::Center).with_cross_justify(true),
+            |ui| {
+                ui.vertical(|ui| {
+                    ui.label(title);
+                    ui.separator();
+                    ui.add_space(10.);
+                    ui.label(
+                        "Use this command to rank exact SLBL surfaces by their misfit to \
+                        displacement data, and add the best ones to the project.",
+                    );
+                    ui.add_space(5.);
+                    ui.separator();
+                    ui.add_space(15.);
+                    ui.label("With data:");
+                    for (i, sar) in self.project.sars.iter().enumerate() {
+                        for (j, sar_data) in sar.disp_data.iter().enumerate() {
+                            let mut selected = data.datasets.contains(&(i, j));
+                            let text = format!("{} / {}", sar.name, sar_data.name);
+                            if ui.checkbox(&mut selected, text).changed() {
+                                if selected {
+                                    data.datasets.push((i, j));
+                                } else {
+                                    data.datasets.retain(|d| *d != (i, j));
+                                }
+                            }
+                        }
+                    }
+                    ui.add_space(10.);
+                    let search = &mut data.search;
+                    for [min, max] in [&mut search.first_pnt, &mut search.last_pnt] {
+                        *max = (*max).min(last_index);
+                        *min = (*min).min(*max);
+                    }
+                    let text_first = format!(
+                        "First point from {}m to {}m",
+                        x[search.first_pnt[0]], x[search.first_pnt[1]]
+                    );
+                    let text_last = format!(
+                        "Last point from {}m to {}m",
+                        x[search.last_pnt[0]], x[search.last_pnt[1]]
+                    );
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut search.first_pnt[0]).range(0..=last_index),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut search.first_pnt[1]).range(0..=last_index),
+                        );
+                        ui.label(text_first);
+                    });
+                    ui.add_space(5.);
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut search.last_pnt[0]).range(0..=last_index));
+                        ui.add(egui::DragValue::new(&mut search.last_pnt[1]).range(0..=last_index));
+                        ui.label(text_last);
+                    });
+                    ui.add_space(5.);
+                    ui.add(
+                        egui::Slider::new(&mut search.point_step, 1..=last_index.max(1))
+                            .text("Point step"),
+                    );
+                    ui.add_space(5.);
+                    let min_tol = search.tol[0];
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut search.tol[0])
+                                .range(0.0..=100.0)
+                                .speed(0.01),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut search.tol[1])
+                                .range(min_tol..=100.0)
+                                .speed(0.01),
+                        );
+                        ui.label("Tolerance range");
+                    });
+                    ui.add_space(5.);
+                    ui.add(egui::Slider::new(&mut search.nb_tol, 1..=50).text("Tolerances number"));
+                    ui.add_space(5.);
+                    ui.label(format!("{} candidates to evaluate", search.nb_candidates()));
+                });
+            },
+        );
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
+            |ui| match &data.status {
+                CommandStatus::Error(e) => match e {
+                    CommandError::EmptySar => {
+                        ui.label("No displacement data selected.");
+                    }
+                    CommandError::MethodError => {
+                        ui.label("The search cannot perform with the given parameters.");
+                    }
+                    _ => (),
+                },
+                _ => (),
+            },
+        );
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+            let apply_text = match data.status {
+                CommandStatus::Clean => egui::RichText::new("Search"),
+                CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+            };
+            let apply_button = ui.button(apply_text.size(22.));
+
+            if apply_button.clicked() {
+                if data.status != CommandStatus::Clean {
+                    data.status = CommandStatus::Clean;
+                    data.promote_status = CommandStatus::Clean;
+                    data.candidates = vec![];
+                } else if data.datasets.is_empty() {
+                    data.status = CommandStatus::Error(CommandError::EmptySar);
+                } else {
+                    match self.project.search_slbl(&data.datasets, &data.search) {
+                        Err(_) => data.status = CommandStatus::Error(CommandError::MethodError),
+                        Ok(candidates) => {
+                            data.selected = vec![false; candidates.len()];
+                            data.candidates = candidates;
+                            data.status = CommandStatus::Complete;
+                        }
+                    }
+                }
+            }
+        });
+
+        if data.status == CommandStatus::Complete {
+            ui.add_space(10.);
+            if data.candidates.is_empty() {
+                ui.label("No valid candidate in the search.");
+                return;
+            }
+            egui::ScrollArea::vertical()
+                .max_height(300.)
+                .show(ui, |ui| {
+                    egui::Grid::new("slbl_candidates")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("");
+                            ui.label("rank");
+                            ui.label("first (m)");
+                            ui.label("last (m)");
+                            ui.label("tolerance");
+                            ui.label("weight");
+                            ui.label("RMSE");
+                            ui.end_row();
+                            for (k, candidate) in data.candidates.iter().enumerate() {
+                                ui.checkbox(&mut data.selected[k], "");
+                                ui.label(format!("{}", k + 1));
+                                ui.label(format!("{}", x[candidate.first_pnt]));
+                                ui.label(format!("{}", x[candidate.last_pnt]));
+                                ui.label(format!("{:.3}", candidate.tol));
+                                ui.label(format!("{:.3}", candidate.weight));
+                                ui.label(format!("{:.4}", candidate.misfit));
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+                let apply_text = match data.promote_status {
+                    CommandStatus::Clean => egui::RichText::new("Add surfaces"),
+                    CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                    CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+                };
+                let apply_button = ui.button(apply_text.size(22.));
+
+                if apply_button.clicked() {
+                    if data.promote_status != CommandStatus::Clean {
+                        data.promote_status = CommandStatus::Clean;
+                    } else {
+                        data.promote_status = CommandStatus::Complete;
+                        for (candidate, _) in data
+                            .candidates
+                            .iter()
+                            .zip(data.selected.iter())
+                            .filter(|(_, selected)| **selected)
+                        {
+                            if self
+                                .project
+                                .surface_from_exact_slbl(
+                                    candidate.first_pnt,
+                                    candidate.last_pnt,
+                                    candidate.tol,
+                                )
+                                .is_err()
+                            {
+                                data.promote_status =
+                                    CommandStatus::Error(CommandError::MethodError);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+    }
+
     fn ui_surface_min(&mut self, ui: &mut egui::Ui) {
         let title = egui::RichText::new("Define a Surface using Minimum Values").heading();
         let ProjectCommand::SurfaceMin(data) = &mut self.current_command else {
@@ -1109,6 +2054,7 @@ impl AppDM {
             panic!("Wrong intern command assignation. Please report it if raised.")
             // Should never reach
         };
+        let convention = &mut self.project.geometry_convention;
 
         ui.with_layout(egui::Layout::top_down(egui::Align::Center).with_cross_justify(true), |ui| {
             ui.vertical(|ui| {
@@ -1116,7 +2062,7 @@ impl AppDM {
                 ui.separator();
                 ui.add_space(10.);
                 ui.label("Use this command to define a new satellite geometry.");
-                ui.label("The azimuth angle is the azimuth of the LOS, i.e. 90°+heading of the satellite.");
+                ui.label("The azimuth can be given as the azimuth of the LOS, pointing away from or toward the satellite, or as the heading of the platform with its looking side. The convention is saved with the project.");
                 ui.add_space(5.);
                 ui.separator();
                 ui.add_space(15.);
@@ -1125,9 +2071,44 @@ impl AppDM {
                     ui.text_edit_singleline(&mut data.name);
                 });
                 ui.add_space(5.);
-                ui.add(egui::Slider::new(&mut data.azimuth, 0.0..=359.99).text("LOS azimuth"));
+                egui::ComboBox::from_label("Azimuth convention")
+                    .selected_text(convention.azimuth.name())
+                    .show_ui(ui, |ui| {
+                        for azimuth in AzimuthConvention::ALL {
+                            ui.selectable_value(&mut convention.azimuth, azimuth, azimuth.name());
+                        }
+                    });
+                let previous_unit = convention.unit;
+                egui::ComboBox::from_label("Angle unit")
+                    .selected_text(convention.unit.name())
+                    .show_ui(ui, |ui| {
+                        for unit in AngleUnit::ALL {
+                            ui.selectable_value(&mut convention.unit, unit, unit.name());
+                        }
+                    });
+                if convention.unit != previous_unit {
+                    data.azimuth = convention.unit.from_rad(previous_unit.to_rad(data.azimuth));
+                    data.incidence = convention.unit.from_rad(previous_unit.to_rad(data.incidence));
+                }
+                ui.add_space(5.);
+                let azimuth_text = match convention.azimuth {
+                    AzimuthConvention::Heading(_) => "Heading",
+                    _ => "LOS azimuth",
+                };
+                let full_turn = convention.unit.from_rad(2. * std::f32::consts::PI);
+                ui.add(
+                    egui::Slider::new(&mut data.azimuth, 0.0..=full_turn * 0.99999)
+                        .text(azimuth_text),
+                );
                 ui.add_space(5.);
-                ui.add(egui::Slider::new(&mut data.incidence, 0.0..=90.).text("LOS incidence"));
+                ui.add(
+                    egui::Slider::new(&mut data.incidence, 0.0..=full_turn / 4.)
+                        .text("LOS incidence"),
+                );
+                ui.add_space(10.);
+                if let Ok(orientation) = convention.orientation(data.azimuth, data.incidence) {
+                    ui_los_diagram(ui, &orientation);
+                }
             });
         });
 
@@ -1158,21 +2139,149 @@ impl AppDM {
                 if data.status != CommandStatus::Clean {
                     data.status = CommandStatus::Clean;
                 } else {
-                    let mut new_bundle = BundleSar::default();
-                    let orientation = Orientation::from_deg(data.azimuth, data.incidence);
-                    match orientation {
-                        Err(_) => data.status = CommandStatus::Error(CommandError::MiscError),
-                        Ok(orientation) => {
-                            new_bundle.sar_geometry = orientation;
-                            new_bundle.name = data.name.to_owned();
-                            self.project.sars.push(new_bundle);
-
+                    let mut new_bundle = BundleSar::default();
+                    let orientation = convention.orientation(data.azimuth, data.incidence);
+                    match orientation {
+                        Err(_) => data.status = CommandStatus::Error(CommandError::MiscError),
+                        Ok(orientation) => {
+                            new_bundle.sar_geometry = orientation;
+                            new_bundle.name = data.name.to_owned();
+                            self.project.sars.push(new_bundle);
+
+                            data.status = CommandStatus::Complete;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    fn ui_sar_sensitivity(&mut self, ui: &mut egui::Ui) {
+        let title = egui::RichText::new("Line of Sight Sensitivity").heading();
+        let ProjectCommand::SarSensitivity(data) = &mut self.current_command else {
+            panic!("Wrong intern command assignation. Please report it if raised.")
+            // Should never reach
+        };
+
+        if self.project.dem.section_geometry.is_none() | self.project.sars.is_empty() {
+            ui.label("No sar geometry or dem geometry available.");
+            return;
+        }
+        if self.project.surfaces.is_empty() & self.project.models.is_empty() {
+            ui.label("No surface or model available.");
+            return;
+        }
+        if self.project.surfaces.is_empty() {
+            if let SensitivitySource::Surface(_) = data.source {
+                data.source = SensitivitySource::Model(0);
+            }
+        }
+        let source_name = |source: SensitivitySource| match source {
+            SensitivitySource::Surface(k) => self.project.surfaces[k].name.to_string(),
+            SensitivitySource::Model(k) => self.project.models[k].name.to_string(),
+        };
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::Center).with_cross_justify(true),
+            |ui| {
+                ui.vertical(|ui| {
+                    ui.label(title);
+                    ui.separator();
+                    ui.add_space(10.);
+                    ui.label("Use this command to check which parts of the section a sar geometry can see, before calibrating.");
+                    ui.label("The factor is the projection of the local movement direction of a surface or a model onto the line of sight. Points where it is close to zero are blind and flagged. The factor is shown in the properties viewer.");
+                    ui.add_space(5.);
+                    ui.separator();
+                    ui.add_space(15.);
+                    egui::ComboBox::from_label("With geometry")
+                        .selected_text(self.project.sars[data.sar_geom].name.to_string())
+                        .show_ui(ui, |ui| {
+                            for k in 0..self.project.sars.len() {
+                                ui.selectable_value(
+                                    &mut data.sar_geom,
+                                    k,
+                                    self.project.sars[k].name.to_string(),
+                                );
+                            }
+                        });
+                    egui::ComboBox::from_label("Movement of")
+                        .selected_text(source_name(data.source))
+                        .show_ui(ui, |ui| {
+                            for k in 0..self.project.surfaces.len() {
+                                let source = SensitivitySource::Surface(k);
+                                ui.selectable_value(&mut data.source, source, source_name(source));
+                            }
+                            for k in 0..self.project.models.len() {
+                                let source = SensitivitySource::Model(k);
+                                ui.selectable_value(&mut data.source, source, source_name(source));
+                            }
+                        });
+                    ui.add_space(10.);
+                    ui.horizontal(|ui| {
+                        ui.label("Minimum sensitivity: ");
+                        ui.add(
+                            egui::DragValue::new(&mut data.min_sensitivity)
+                                .range(0.0..=1.)
+                                .speed(0.01),
+                        );
+                    });
+                });
+            },
+        );
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
+            |ui| match &data.status {
+                CommandStatus::Error(e) => match e {
+                    CommandError::MethodError => {
+                        ui.label("The movement is not sampled on the DEM.");
+                    }
+                    _ => (),
+                },
+                _ => (),
+            },
+        );
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+            let apply_text = match data.status {
+                CommandStatus::Clean => egui::RichText::new("Apply"),
+                CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+            };
+            let apply_button = ui.button(apply_text.size(22.));
+
+            if apply_button.clicked() {
+                if data.status != CommandStatus::Clean {
+                    data.status = CommandStatus::Clean;
+                    data.sensitivity = None;
+                } else {
+                    let profile = match data.source {
+                        SensitivitySource::Surface(k) => &self.project.surfaces[k].profile,
+                        SensitivitySource::Model(k) => &self.project.models[k].resulting_profile,
+                    };
+                    match profile.los_sensitivity(
+                        &self.project.dem.dem,
+                        self.project.dem.section_geometry.as_ref().unwrap(),
+                        &self.project.sars[data.sar_geom].sar_geometry,
+                        data.min_sensitivity,
+                    ) {
+                        Err(_) => data.status = CommandStatus::Error(CommandError::MethodError),
+                        Ok(sensitivity) => {
+                            data.sensitivity = Some(sensitivity);
                             data.status = CommandStatus::Complete;
                         }
                     }
                 }
             }
         });
+
+        if let Some(sensitivity) = &data.sensitivity {
+            ui.add_space(10.);
+            ui.label(format!(
+                "{:.0}% of the moving section is blind to this geometry.",
+                100. * sensitivity.blind_ratio()
+            ));
+        }
     }
 
     fn ui_open_disp(&mut self, ui: &mut egui::Ui) {
@@ -1192,6 +2301,10 @@ impl AppDM {
                     ui.add_space(10.);
                     ui.label("Use this command to load displacement data from file.");
                     ui.label("The file should be a csv file with the header 'x' for the sampling values and 'disp' for the displacement values");
+                    ui.label("An optional column 'sigma' or 'std' gives the standard deviation of each value, used to weight the calibration.");
+                    ui.label("An optional column 'footprint' or 'pixel_size' gives the width of the pixel of each value along the section, over which the model can be averaged.");
+                    ui.label("When the geometry varies along the section, the line of sight of each value is read from the columns 'los_e', 'los_n', 'los_u', a unit vector pointing from the satellite to the ground (negative 'los_u'); vectors pointing toward the satellite are reversed. It can also be read from the 'incidence' and 'heading' columns, always in degrees for a right-looking sensor whatever the convention of the project. It replaces the geometry of the sar for these data.");
+                    ui.label("A time series holds one column per acquisition date instead of 'disp', each header being the date, e.g. '2020-01-31'.");
                     ui.add_space(5.);
                     ui.separator();
                     ui.add_space(15.);
@@ -1207,6 +2320,8 @@ impl AppDM {
                             }
                     });
                     ui.add_space(5.);
+                    ui.checkbox(&mut data.time_series, "Time series");
+                    ui.add_space(5.);
                     if ui.button("Select file").clicked() {
                         if let Some(path) = rfd::FileDialog::new().pick_file() {
                             data.file_path = Some(path.display().to_string());
@@ -1237,40 +2352,542 @@ impl AppDM {
                     },
                     _ => (),
                 },
-            );
+            );
+
+            ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+                let apply_text = match data.status {
+                    CommandStatus::Clean => egui::RichText::new("Apply"),
+                    CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                    CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+                };
+                let apply_button = ui.button(apply_text.size(22.));
+
+                if apply_button.clicked() {
+                    if data.status != CommandStatus::Clean {
+                        data.status = CommandStatus::Clean;
+                    } else {
+                        match &data.file_path {
+                            Some(f) => {
+                                let result = if data.time_series {
+                                    self.project.new_sar_series(
+                                        &data.name,
+                                        data.sar_index,
+                                        f.to_string(),
+                                    )
+                                } else {
+                                    self.project.new_sar_data(
+                                        &data.name,
+                                        data.sar_index,
+                                        f.to_string(),
+                                    )
+                                };
+                                match result {
+                                    Err(_) => {
+                                        data.status =
+                                            CommandStatus::Error(CommandError::MethodError)
+                                    }
+                                    _ => data.status = CommandStatus::Complete,
+                                }
+                            }
+                            None => data.status = CommandStatus::Error(CommandError::NoFile),
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    fn ui_project_disp(&mut self, ui: &mut egui::Ui) {
+        let title = egui::RichText::new("Project Displacement Data on Section").heading();
+        let ProjectCommand::ProjectDisp(data) = &mut self.current_command else {
+            panic!("Wrong intern command assignation. Please report it if raised.")
+            // Should never reach
+        };
+
+        if self.project.dem.section_geometry.is_none() {
+            ui.label("No dem geometry available.");
+            return;
+        }
+        // Only the geometries holding data are listed
+        let sars: Vec<usize> = (0..self.project.sars.len())
+            .filter(|k| !self.project.sars[*k].disp_data.is_empty())
+            .collect();
+        if sars.is_empty() {
+            ui.label("No available data in sar geometry.");
+            return;
+        }
+        if !sars.contains(&data.sar_geom) {
+            data.sar_geom = sars[0];
+            data.sar_data = 0;
+        }
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::Center).with_cross_justify(true),
+            |ui| {
+                ui.vertical(|ui| {
+                    ui.label(title);
+                    ui.separator();
+                    ui.add_space(10.);
+                    ui.label("Use this command to convert displacement data in the line of sight into vectors of the section.");
+                    ui.label("The displacement is assumed parallel to the slope of the DEM or of a chosen surface. Points where the line of sight is not sensitive enough to this direction are flagged and not drawn.");
+                    ui.add_space(5.);
+                    ui.separator();
+                    ui.add_space(15.);
+                    egui::ComboBox::from_label("With geometry")
+                        .selected_text(self.project.sars[data.sar_geom].name.to_string())
+                        .show_ui(ui, |ui| {
+                            for k in sars.iter().copied() {
+                                if ui
+                                    .selectable_value(
+                                        &mut data.sar_geom,
+                                        k,
+                                        self.project.sars[k].name.to_string(),
+                                    )
+                                    .changed()
+                                {
+                                    data.sar_data = 0;
+                                }
+                            }
+                        });
+                    if !self.project.sars[data.sar_geom].disp_data.is_empty() {
+                        egui::ComboBox::from_label("With data")
+                            .selected_text(
+                                self.project.sars[data.sar_geom].disp_data[data.sar_data]
+                                    .name
+                                    .to_string(),
+                            )
+                            .show_ui(ui, |ui| {
+                                for k in 0..self.project.sars[data.sar_geom].disp_data.len() {
+                                    ui.selectable_value(
+                                        &mut data.sar_data,
+                                        k,
+                                        self.project.sars[data.sar_geom].disp_data[k]
+                                            .name
+                                            .to_string(),
+                                    );
+                                }
+                            });
+                    }
+                    ui.add_space(10.);
+                    egui::ComboBox::from_label("Direction")
+                        .selected_text(match data.surface {
+                            Some(k) => self.project.surfaces[k].name.to_string(),
+                            None => "DEM slope".to_string(),
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut data.surface, None, "DEM slope");
+                            for k in 0..self.project.surfaces.len() {
+                                ui.selectable_value(
+                                    &mut data.surface,
+                                    Some(k),
+                                    self.project.surfaces[k].name.to_string(),
+                                );
+                            }
+                        });
+                    ui.add_space(5.);
+                    ui.horizontal(|ui| {
+                        ui.label("Minimum sensitivity: ");
+                        ui.add(
+                            egui::DragValue::new(&mut data.min_sensitivity)
+                                .range(0.0..=1.0)
+                                .speed(0.01),
+                        );
+                    });
+                });
+            },
+        );
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
+            |ui| match &data.status {
+                CommandStatus::Error(e) => match e {
+                    CommandError::EmptySar => {
+                        ui.label("No data in selected sar geometry.");
+                    }
+                    CommandError::MethodError => {
+                        ui.label("An error occured during the projection.");
+                    }
+                    _ => (),
+                },
+                _ => (),
+            },
+        );
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+            let apply_text = match data.status {
+                CommandStatus::Clean => egui::RichText::new("Apply"),
+                CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+            };
+            let apply_button = ui.button(apply_text.size(22.));
+
+            if apply_button.clicked() {
+                if data.status != CommandStatus::Clean {
+                    data.status = CommandStatus::Clean;
+                } else if self.project.sars[data.sar_geom].disp_data.is_empty() {
+                    data.status = CommandStatus::Error(CommandError::EmptySar);
+                } else {
+                    match self.project.project_disp_data(
+                        data.sar_geom,
+                        data.sar_data,
+                        data.surface,
+                        data.min_sensitivity,
+                    ) {
+                        Err(_) => data.status = CommandStatus::Error(CommandError::MethodError),
+                        Ok(_) => data.status = CommandStatus::Complete,
+                    }
+                }
+            }
+        });
+    }
+
+    fn ui_bin_disp(&mut self, ui: &mut egui::Ui) {
+        let title = egui::RichText::new("Bin Displacement Data along the Section").heading();
+        let ProjectCommand::BinDisp(data) = &mut self.current_command else {
+            panic!("Wrong intern command assignation. Please report it if raised.")
+            // Should never reach
+        };
+
+        // Only the geometries holding data are listed
+        let sars: Vec<usize> = (0..self.project.sars.len())
+            .filter(|k| !self.project.sars[*k].disp_data.is_empty())
+            .collect();
+        if sars.is_empty() {
+            ui.label("No available data in sar geometry.");
+            return;
+        }
+        if !sars.contains(&data.sar_geom) {
+            data.sar_geom = sars[0];
+            data.sar_data = 0;
+        }
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::Center).with_cross_justify(true),
+            |ui| {
+                ui.vertical(|ui| {
+                    ui.label(title);
+                    ui.separator();
+                    ui.add_space(10.);
+                    ui.label("Use this command to gather dense and noisy data into bins along the section.");
+                    ui.label("Each bin is summarised by the median of its points, weighted in the calibration by the spread of the points. The binned data are added to the same geometry.");
+                    ui.add_space(5.);
+                    ui.separator();
+                    ui.add_space(15.);
+                    egui::ComboBox::from_label("With geometry")
+                        .selected_text(self.project.sars[data.sar_geom].name.to_string())
+                        .show_ui(ui, |ui| {
+                            for k in sars.iter().copied() {
+                                if ui
+                                    .selectable_value(
+                                        &mut data.sar_geom,
+                                        k,
+                                        self.project.sars[k].name.to_string(),
+                                    )
+                                    .changed()
+                                {
+                                    data.sar_data = 0;
+                                }
+                            }
+                        });
+                    if !self.project.sars[data.sar_geom].disp_data.is_empty() {
+                        egui::ComboBox::from_label("With data")
+                            .selected_text(
+                                self.project.sars[data.sar_geom].disp_data[data.sar_data]
+                                    .name
+                                    .to_string(),
+                            )
+                            .show_ui(ui, |ui| {
+                                for k in 0..self.project.sars[data.sar_geom].disp_data.len() {
+                                    ui.selectable_value(
+                                        &mut data.sar_data,
+                                        k,
+                                        self.project.sars[data.sar_geom].disp_data[k]
+                                            .name
+                                            .to_string(),
+                                    );
+                                }
+                            });
+                    }
+                    ui.add_space(10.);
+                    ui.horizontal(|ui| {
+                        ui.label("Bin width: ");
+                        ui.add(
+                            egui::DragValue::new(&mut data.width)
+                                .range(0.1..=f32::MAX)
+                                .speed(1.)
+                                .suffix(" m"),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Minimum points per bin: ");
+                        ui.add(egui::DragValue::new(&mut data.min_count).range(1..=1000));
+                    });
+                });
+            },
+        );
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
+            |ui| match &data.status {
+                CommandStatus::Error(e) => match e {
+                    CommandError::EmptySar => {
+                        ui.label("No data in selected sar geometry.");
+                    }
+                    CommandError::MethodError => {
+                        ui.label("No bin holds enough points.");
+                    }
+                    _ => (),
+                },
+                _ => (),
+            },
+        );
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+            let apply_text = match data.status {
+                CommandStatus::Clean => egui::RichText::new("Apply"),
+                CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+            };
+            let apply_button = ui.button(apply_text.size(22.));
+
+            if apply_button.clicked() {
+                if data.status != CommandStatus::Clean {
+                    data.status = CommandStatus::Clean;
+                } else if self.project.sars[data.sar_geom].disp_data.is_empty() {
+                    data.status = CommandStatus::Error(CommandError::EmptySar);
+                } else {
+                    match self.project.bin_disp_data(
+                        data.sar_geom,
+                        data.sar_data,
+                        data.width,
+                        data.min_count,
+                    ) {
+                        Err(_) => data.status = CommandStatus::Error(CommandError::MethodError),
+                        Ok(_) => data.status = CommandStatus::Complete,
+                    }
+                }
+            }
+        });
+    }
+
+    fn ui_decompose_los(&mut self, ui: &mut egui::Ui) {
+        let title = egui::RichText::new("Decompose Two Lines of Sight").heading();
+        let ProjectCommand::DecomposeLos(data) = &mut self.current_command else {
+            panic!("Wrong intern command assignation. Please report it if raised.")
+            // Should never reach
+        };
+
+        // Only the geometries holding data are listed
+        let sars: Vec<usize> = (0..self.project.sars.len())
+            .filter(|k| !self.project.sars[*k].disp_data.is_empty())
+            .collect();
+        if sars.is_empty() {
+            ui.label("No available data in sar geometry.");
+            return;
+        }
+        for selected in [&mut data.first, &mut data.second] {
+            if !sars.contains(&selected.0) {
+                *selected = (sars[0], 0);
+            }
+        }
+        if self.project.dem.section_geometry.is_none() {
+            ui.label("No section geometry defined");
+            return;
+        }
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::Center).with_cross_justify(true),
+            |ui| {
+                ui.vertical(|ui| {
+                    ui.label(title);
+                    ui.separator();
+                    ui.add_space(10.);
+                    ui.label("Use this command to combine data of two sar geometries covering the section, typically an ascending and a descending track.");
+                    ui.label("Both data are resampled on the DEM points where they overlap, then solved at each point for a horizontal and a vertical component, the other horizontal component being assumed null.");
+                    ui.add_space(5.);
+                    ui.separator();
+                    ui.add_space(15.);
+                    for (label, selected) in [("First", &mut data.first), ("Second", &mut data.second)] {
+                        egui::ComboBox::from_label(format!("{} geometry", label))
+                            .selected_text(self.project.sars[selected.0].name.to_string())
+                            .show_ui(ui, |ui| {
+                                for k in sars.iter().copied() {
+                                    if ui
+                                        .selectable_value(
+                                            &mut selected.0,
+                                            k,
+                                            self.project.sars[k].name.to_string(),
+                                        )
+                                        .changed()
+                                    {
+                                        selected.1 = 0;
+                                    }
+                                }
+                            });
+                        if !self.project.sars[selected.0].disp_data.is_empty() {
+                            egui::ComboBox::from_label(format!("{} data", label))
+                                .selected_text(
+                                    self.project.sars[selected.0].disp_data[selected.1]
+                                        .name
+                                        .to_string(),
+                                )
+                                .show_ui(ui, |ui| {
+                                    for k in 0..self.project.sars[selected.0].disp_data.len() {
+                                        ui.selectable_value(
+                                            &mut selected.1,
+                                            k,
+                                            self.project.sars[selected.0].disp_data[k]
+                                                .name
+                                                .to_string(),
+                                        );
+                                    }
+                                });
+                        }
+                        ui.add_space(5.);
+                    }
+                    ui.add_space(5.);
+                    egui::ComboBox::from_label("Components")
+                        .selected_text(data.basis.name())
+                        .show_ui(ui, |ui| {
+                            for basis in DecompositionBasis::ALL {
+                                ui.selectable_value(&mut data.basis, basis, basis.name());
+                            }
+                        });
+                    ui.horizontal(|ui| {
+                        ui.label("Minimum determinant: ")
+                            .on_hover_text("Below this value, the lines of sight are too close to separate the components and the point is flagged.");
+                        ui.add(
+                            egui::DragValue::new(&mut data.min_determinant)
+                                .range(0.0..=1.)
+                                .speed(0.01),
+                        );
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Name: ");
+                        ui.text_edit_singleline(&mut data.name);
+                    });
+                });
+            },
+        );
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
+            |ui| match &data.status {
+                CommandStatus::Error(e) => match e {
+                    CommandError::EmptySar => {
+                        ui.label("No data in selected sar geometry.");
+                    }
+                    CommandError::EmptyName => {
+                        ui.label("Please provide a name.");
+                    }
+                    CommandError::MethodError => {
+                        ui.label("The data do not overlap on the section.");
+                    }
+                    _ => (),
+                },
+                _ => (),
+            },
+        );
 
-            ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
-                let apply_text = match data.status {
-                    CommandStatus::Clean => egui::RichText::new("Apply"),
-                    CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
-                    CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
-                };
-                let apply_button = ui.button(apply_text.size(22.));
+        ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+            let apply_text = match data.status {
+                CommandStatus::Clean => egui::RichText::new("Apply"),
+                CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+            };
+            let apply_button = ui.button(apply_text.size(22.));
 
-                if apply_button.clicked() {
-                    if data.status != CommandStatus::Clean {
-                        data.status = CommandStatus::Clean;
-                    } else {
-                        match &data.file_path {
-                            Some(f) => {
-                                match self.project.new_sar_data(
-                                    &data.name,
-                                    data.sar_index,
-                                    f.to_string(),
-                                ) {
-                                    Err(_) => {
-                                        data.status =
-                                            CommandStatus::Error(CommandError::MethodError)
-                                    }
-                                    _ => data.status = CommandStatus::Complete,
-                                }
-                            }
-                            None => data.status = CommandStatus::Error(CommandError::NoFile),
+            if apply_button.clicked() {
+                if data.status != CommandStatus::Clean {
+                    data.status = CommandStatus::Clean;
+                } else if self.project.sars[data.first.0].disp_data.is_empty()
+                    | self.project.sars[data.second.0].disp_data.is_empty()
+                {
+                    data.status = CommandStatus::Error(CommandError::EmptySar);
+                } else if data.name.is_empty() {
+                    data.status = CommandStatus::Error(CommandError::EmptyName);
+                } else {
+                    match self.project.decompose_los(
+                        data.name.to_string(),
+                        data.first,
+                        data.second,
+                        data.basis,
+                        data.min_determinant,
+                    ) {
+                        Err(_) => data.status = CommandStatus::Error(CommandError::MethodError),
+                        Ok(_) => {
+                            data.status = CommandStatus::Complete;
+                            data.decomposition = Some(self.project.decompositions.len() - 1);
+                            data.export_status = CommandStatus::Clean;
                         }
                     }
                 }
+            }
+        });
+
+        let Some(index) = data.decomposition else {
+            return;
+        };
+        let bundle = &self.project.decompositions[index];
+        let nb_ill_conditioned = bundle
+            .decomposition
+            .ill_conditioned
+            .iter()
+            .filter(|flag| **flag)
+            .count();
+        ui.add_space(15.);
+        ui.separator();
+        ui.label(format!(
+            "{}: {} points decomposed",
+            bundle.name,
+            bundle.decomposition.x.len()
+        ));
+        if nb_ill_conditioned > 0 {
+            ui.label(format!(
+                "{} ill-conditioned points, their components are set to zero.",
+                nb_ill_conditioned
+            ));
+        }
+        ui.add_space(10.);
+
+        if ui.button("Export at").clicked() {
+            if let Some(path) = rfd::FileDialog::new().save_file() {
+                data.file_path = Some(path.display().to_string());
+            }
+        }
+        ui.add_space(2.);
+        if let Some(f) = &data.file_path {
+            ui.horizontal(|ui| {
+                ui.label("Target file: ");
+                egui::ScrollArea::horizontal().show(ui, |ui| {
+                    ui.label(f);
+                });
             });
         }
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+            let apply_text = match data.export_status {
+                CommandStatus::Clean => egui::RichText::new("Export"),
+                CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+            };
+            let apply_button = ui.button(apply_text.size(22.));
+
+            if apply_button.clicked() {
+                if data.export_status != CommandStatus::Clean {
+                    data.export_status = CommandStatus::Clean;
+                } else if let Some(path) = &data.file_path {
+                    match bundle.export_values(path, self.project.dem.section_line.as_ref()) {
+                        Err(_) => {
+                            data.export_status = CommandStatus::Error(CommandError::MethodError)
+                        }
+                        Ok(_) => data.export_status = CommandStatus::Complete,
+                    }
+                } else {
+                    data.export_status = CommandStatus::Error(CommandError::EmptyName);
+                }
+            }
+        });
     }
 
     fn ui_calibrate_model(&mut self, ui: &mut egui::Ui) {
@@ -1291,6 +2908,18 @@ impl AppDM {
                 .filter(|sar| !sar.disp_data.is_empty())
                 .collect();
             if !sars.is_empty() {
+                // Settings follow the selected model, starting from its previous calibration
+                let nb_surfaces = self.project.models[data.model].surfaces.len();
+                if data.settings.check(nb_surfaces).is_err() {
+                    data.settings = match &self.project.models[data.model].settings {
+                        Some(settings) => settings.clone(),
+                        None => CalibrationSettings::new(nb_surfaces),
+                    };
+                    if data.settings.bootstrap_samples != 0 {
+                        data.bootstrap = true;
+                        data.bootstrap_samples = data.settings.bootstrap_samples;
+                    }
+                }
                 ui.with_layout(
                     egui::Layout::top_down(egui::Align::Center).with_cross_justify(true),
                     |ui| {
@@ -1306,45 +2935,141 @@ impl AppDM {
                                 .selected_text(self.project.models[data.model].name.to_string())
                                 .show_ui(ui, |ui| {
                                     for k in 0..self.project.models.len() {
-                                        ui.selectable_value(
-                                            &mut data.model,
-                                            k,
-                                            self.project.models[k].name.to_string(),
-                                        );
+                                        if ui
+                                            .selectable_value(
+                                                &mut data.model,
+                                                k,
+                                                self.project.models[k].name.to_string(),
+                                            )
+                                            .changed()
+                                        {
+                                            data.settings = CalibrationSettings::new(0);
+                                        }
                                     }
                                 });
                             ui.add_space(10.);
-                            egui::ComboBox::from_label("With geometry")
-                                .selected_text(self.project.sars[data.sar_geom].name.to_string())
+                            ui.label("With data:");
+                            for (i, sar) in self.project.sars.iter().enumerate() {
+                                for (j, sar_data) in sar.disp_data.iter().enumerate() {
+                                    let mut selected = data.datasets.contains(&(i, j));
+                                    let text = format!("{} / {}", sar.name, sar_data.name);
+                                    if ui.checkbox(&mut selected, text).changed() {
+                                        if selected {
+                                            data.datasets.push((i, j));
+                                        } else {
+                                            data.datasets.retain(|d| *d != (i, j));
+                                        }
+                                    }
+                                }
+                            }
+                            ui.add_space(10.);
+                            egui::ComboBox::from_label("Solver")
+                                .selected_text(data.settings.solver.name())
                                 .show_ui(ui, |ui| {
-                                    for k in 0..self.project.sars.len() {
+                                    for solver in Solver::ALL {
                                         ui.selectable_value(
-                                            &mut data.sar_geom,
-                                            k,
-                                            self.project.sars[k].name.to_string(),
+                                            &mut data.settings.solver,
+                                            solver,
+                                            solver.name(),
                                         );
                                     }
                                 });
-                            if !self.project.sars[data.sar_geom].disp_data.is_empty() {
-                                egui::ComboBox::from_label("With data")
-                                    .selected_text(
-                                        self.project.sars[data.sar_geom].disp_data[data.sar_data]
-                                            .name
-                                            .to_string(),
-                                    )
-                                    .show_ui(ui, |ui| {
-                                        for k in 0..self.project.sars[data.sar_geom].disp_data.len()
-                                        {
-                                            ui.selectable_value(
-                                                &mut data.sar_data,
-                                                k,
-                                                self.project.sars[data.sar_geom].disp_data[k]
-                                                    .name
-                                                    .to_string(),
+                            ui.add_space(5.);
+                            let settings = &mut data.settings;
+                            let is_bounded = settings.solver.is_bounded();
+                            let has_guess = settings.solver == Solver::ParticleSwarm;
+                            let gradients = &self.project.models[data.model].gradients;
+                            egui::Grid::new("calibration_settings")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("surface");
+                                    ui.label("fixed");
+                                    ui.label("gradient");
+                                    if is_bounded {
+                                        ui.label("lower");
+                                        ui.label("upper");
+                                    }
+                                    if has_guess {
+                                        ui.label("guess");
+                                    }
+                                    ui.end_row();
+                                    for (k, gradient) in gradients.iter().enumerate() {
+                                        ui.label(format!("{}", k + 1));
+                                        ui.horizontal(|ui| {
+                                            let mut is_fixed = settings.fixed_weights[k].is_some();
+                                            if ui.checkbox(&mut is_fixed, "").changed() {
+                                                settings.fixed_weights[k] = if is_fixed {
+                                                    Some(settings.initial_guess[k])
+                                                } else {
+                                                    None
+                                                };
+                                            }
+                                            if let Some(w) = &mut settings.fixed_weights[k] {
+                                                ui.add(egui::DragValue::new(w).speed(0.01));
+                                            }
+                                        });
+                                        // Only gradients with at least two control points
+                                        // can be calibrated
+                                        ui.add_enabled(
+                                            settings.fixed_weights[k].is_none()
+                                                && gradient.len() > 1,
+                                            egui::Checkbox::new(
+                                                &mut settings.free_gradients[k],
+                                                "",
+                                            ),
+                                        );
+                                        if is_bounded {
+                                            ui.add(
+                                                egui::DragValue::new(&mut settings.lower_bounds[k])
+                                                    .speed(0.1),
+                                            );
+                                            ui.add(
+                                                egui::DragValue::new(&mut settings.upper_bounds[k])
+                                                    .speed(0.1),
                                             );
                                         }
-                                    });
+                                        if has_guess {
+                                            ui.add(
+                                                egui::DragValue::new(
+                                                    &mut settings.initial_guess[k],
+                                                )
+                                                .speed(0.01),
+                                            );
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                            if (0..nb_surfaces).any(|k| settings.calibrates_gradient(k)) {
+                                ui.add_space(5.);
+                                ui.horizontal(|ui| {
+                                    ui.label("Gradient smoothing");
+                                    ui.add(
+                                        egui::DragValue::new(&mut settings.gradient_smoothing)
+                                            .range(0.0..=f32::MAX)
+                                            .speed(0.1),
+                                    );
+                                });
                             }
+                            ui.add_space(5.);
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut settings.fit_offset, "Fit offset");
+                                ui.checkbox(&mut settings.fit_ramp, "Fit ramp");
+                            });
+                            ui.checkbox(
+                                &mut settings.footprint_average,
+                                "Average over pixel footprint",
+                            );
+                            ui.add_space(5.);
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut data.bootstrap, "Bootstrap uncertainty");
+                                if data.bootstrap {
+                                    ui.add(
+                                        egui::DragValue::new(&mut data.bootstrap_samples)
+                                            .range(10..=10000)
+                                            .suffix(" samples"),
+                                    );
+                                }
+                            });
                         });
                     },
                 );
@@ -1354,7 +3079,7 @@ impl AppDM {
                     |ui| match &data.status {
                         CommandStatus::Error(e) => match e {
                             CommandError::EmptySar => {
-                                ui.label("No data in selected sar geometry.");
+                                ui.label("No displacement data selected.");
                             }
                             CommandError::MethodError => {
                                 ui.label("An error occured with the solver.");
@@ -1377,13 +3102,18 @@ impl AppDM {
                         if data.status != CommandStatus::Clean {
                             data.status = CommandStatus::Clean;
                         } else {
-                            if self.project.sars[data.sar_geom].disp_data.is_empty() {
+                            if data.datasets.is_empty() {
                                 data.status = CommandStatus::Error(CommandError::EmptySar);
                             } else {
+                                data.settings.bootstrap_samples = if data.bootstrap {
+                                    data.bootstrap_samples
+                                } else {
+                                    0
+                                };
                                 match self.project.calibrate_model(
                                     data.model,
-                                    data.sar_geom,
-                                    data.sar_data,
+                                    &data.datasets,
+                                    &data.settings,
                                 ) {
                                     Err(_) => {
                                         data.status =
@@ -1403,6 +3133,165 @@ impl AppDM {
         }
     }
 
+    fn ui_calibrate_series(&mut self, ui: &mut egui::Ui) {
+        let title = egui::RichText::new("Calibrate a Model over Time").heading();
+        let ProjectCommand::CalibrateSeries(data) = &mut self.current_command else {
+            panic!("Wrong intern command assignation. Please report it if raised.")
+            // Should never reach
+        };
+
+        if self.project.models.is_empty() | self.project.dem.section_geometry.is_none() {
+            ui.label("No model or dem geometry available.");
+            return;
+        }
+        if self
+            .project
+            .sars
+            .iter()
+            .all(|sar| sar.disp_series.is_empty())
+        {
+            ui.label("No time series in sar geometry.");
+            return;
+        }
+        if self.project.sars[data.sar].disp_series.is_empty() {
+            data.sar = self
+                .project
+                .sars
+                .iter()
+                .position(|sar| !sar.disp_series.is_empty())
+                .unwrap_or_default();
+            data.series = 0;
+        }
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::Center).with_cross_justify(true),
+            |ui| {
+                ui.vertical(|ui| {
+                    ui.label(title);
+                    ui.separator();
+                    ui.add_space(10.);
+                    ui.label(
+                        "Use this command to calibrate the weights of a model at each date of \
+                        a displacement time series.",
+                    );
+                    ui.label(
+                        "The bounds and fixed weights of the last calibration of the model \
+                        are used. The weights over time are shown in the properties viewer.",
+                    );
+                    ui.add_space(5.);
+                    ui.separator();
+                    ui.add_space(15.);
+                    egui::ComboBox::from_label("On model")
+                        .selected_text(self.project.models[data.model].name.to_string())
+                        .show_ui(ui, |ui| {
+                            for k in 0..self.project.models.len() {
+                                ui.selectable_value(
+                                    &mut data.model,
+                                    k,
+                                    self.project.models[k].name.to_string(),
+                                );
+                            }
+                        });
+                    ui.add_space(10.);
+                    egui::ComboBox::from_label("With geometry")
+                        .selected_text(self.project.sars[data.sar].name.to_string())
+                        .show_ui(ui, |ui| {
+                            for (k, sar) in self.project.sars.iter().enumerate() {
+                                if !sar.disp_series.is_empty()
+                                    && ui
+                                        .selectable_value(&mut data.sar, k, sar.name.to_string())
+                                        .changed()
+                                {
+                                    data.series = 0;
+                                }
+                            }
+                        });
+                    let disp_series = &self.project.sars[data.sar].disp_series;
+                    egui::ComboBox::from_label("With time series")
+                        .selected_text(disp_series[data.series].name.to_string())
+                        .show_ui(ui, |ui| {
+                            for (k, series) in disp_series.iter().enumerate() {
+                                ui.selectable_value(&mut data.series, k, series.name.to_string());
+                            }
+                        });
+                    ui.label(format!(
+                        "{} dates",
+                        disp_series[data.series].series.nb_epochs()
+                    ));
+                    ui.add_space(10.);
+                    egui::ComboBox::from_label("Solver")
+                        .selected_text(data.solver.name())
+                        .show_ui(ui, |ui| {
+                            for solver in Solver::ALL {
+                                ui.selectable_value(&mut data.solver, solver, solver.name());
+                            }
+                        });
+                });
+            },
+        );
+
+        ui.with_layout(
+            egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
+            |ui| match &data.status {
+                CommandStatus::Error(e) => match e {
+                    CommandError::MethodError => {
+                        ui.label("An error occured with the solver.");
+                    }
+                    _ => (),
+                },
+                _ => (),
+            },
+        );
+
+        ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
+            let apply_text = match data.status {
+                CommandStatus::Clean => egui::RichText::new("Apply"),
+                CommandStatus::Complete => egui::RichText::new(Phosphor::CHECK),
+                CommandStatus::Error(_) => egui::RichText::new(Phosphor::WARNING),
+            };
+            let apply_button = ui.button(apply_text.size(22.));
+
+            if apply_button.clicked() {
+                if data.status != CommandStatus::Clean {
+                    data.status = CommandStatus::Clean;
+                } else {
+                    let model = &self.project.models[data.model];
+                    let nb_surfaces = model.surfaces.len();
+                    let mut settings = match &model.settings {
+                        Some(settings) if settings.check(nb_surfaces).is_ok() => settings.clone(),
+                        _ => CalibrationSettings::new(nb_surfaces),
+                    };
+                    settings.solver = data.solver;
+                    settings.bootstrap_samples = 0;
+                    match self.project.calibrate_series(
+                        data.model,
+                        data.sar,
+                        data.series,
+                        &settings,
+                    ) {
+                        Err(_) => data.status = CommandStatus::Error(CommandError::MethodError),
+                        Ok(_) => data.status = CommandStatus::Complete,
+                    }
+                }
+            }
+        });
+
+        if let Some(fit) = &self.project.models[data.model].series_fit {
+            ui.add_space(10.);
+            if let Some((sar, series)) = self
+                .project
+                .sars
+                .get(fit.sar)
+                .and_then(|sar| sar.disp_series.get(fit.series).map(|s| (sar, s)))
+            {
+                ui.label(format!(
+                    "Calibrated over time on {} / {}",
+                    sar.name, series.name
+                ));
+            }
+        }
+    }
+
     fn ui_surface_export(&mut self, ui: &mut egui::Ui) {
         let title = egui::RichText::new("Export Surface").heading();
         let ProjectCommand::SurfaceExport(data) = &mut self.current_command else {
@@ -1481,9 +3370,11 @@ impl AppDM {
                         data.status = CommandStatus::Clean;
                     } else {
                         if let Some(path) = &data.file_path {
-                            match self.project.surfaces[data.surface]
-                                .export_values(path, &self.project.dem.dem)
-                            {
+                            match self.project.surfaces[data.surface].export_values(
+                                path,
+                                &self.project.dem.dem,
+                                self.project.dem.section_line.as_ref(),
+                            ) {
                                 Err(_) => {
                                     data.status = CommandStatus::Error(CommandError::MethodError)
                                 }
@@ -1622,7 +3513,18 @@ impl AppDM {
                         let geom = &self.project.sars[data.sar_geom];
                         let sar_data = &geom.disp_data[data.sar_data];
                         if data.amp_in_los.is_empty() {
+                            // Nuisance terms fitted during the calibration are removed
                             data.amp_data = sar_data.disp_data.amplitude.to_owned();
+                            if let Some(fit) = model
+                                .fits
+                                .iter()
+                                .find(|f| f.sar == data.sar_geom && f.data == data.sar_data)
+                            {
+                                let nuisance = fit.nuisance(&sar_data.disp_data.x);
+                                for (amp, n) in data.amp_data.iter_mut().zip(nuisance) {
+                                    *amp -= n;
+                                }
+                            }
                             data.full_amp = model
                                 .resulting_profile
                                 .vecs
@@ -1644,12 +3546,39 @@ impl AppDM {
                             let mut profile = model.resulting_profile.clone();
                             profile.interpolate_on_origins(los_origins);
                             data.amp = profile.vecs.iter().map(|v| v.amplitude()).collect();
-                            data.amp_in_los = profile.projected_amplitude_onto(
-                                geom.sar_geometry.to_owned(),
-                                section_geom.to_owned(),
-                            );
+                            let footprint_average =
+                                model.settings.as_ref().is_some_and(|s| s.footprint_average);
+                            let los_vecs = sar_data.disp_data.los_vectors(&geom.sar_geometry);
+                            data.amp_in_los = match &sar_data.disp_data.footprint {
+                                Some(footprint) if footprint_average => {
+                                    model.resulting_profile.footprint_amplitude_onto(
+                                        &los_vecs,
+                                        section_geom,
+                                        &los_x,
+                                        footprint,
+                                    )
+                                }
+                                _ => profile.projected_amplitude_onto_each(&los_vecs, section_geom),
+                            };
+                            data.sigma_data =
+                                sar_data.disp_data.sigma.to_owned().unwrap_or_default();
 
-                            data.rmse = rmse(&data.amp_in_los, &data.amp_data);
+                            data.residuals = residuals(&data.amp_in_los, &data.amp_data);
+                            let nb_params = match &model.settings {
+                                Some(settings) => settings.nb_params(&model.gradients),
+                                None => model.weights.len(),
+                            };
+                            data.metrics =
+                                FitMetrics::new(&data.amp_in_los, &data.amp_data, nb_params);
+                            data.reduced_chi_square =
+                                sar_data.disp_data.sigma.as_ref().and_then(|sigma| {
+                                    reduced_chi_square(
+                                        &data.amp_in_los,
+                                        &data.amp_data,
+                                        sigma,
+                                        nb_params,
+                                    )
+                                });
 
                             self.is_viewer_properties = true;
                         }
@@ -1660,15 +3589,89 @@ impl AppDM {
                         ui.add_space(5.);
                         ui.label(format!("Geometry: {}", geom.name.to_owned()));
                         ui.add_space(2.);
-                        ui.label(format!(
-                            "az: {}° ; i: {}°",
-                            rad2deg(geom.sar_geometry.azimuth),
-                            rad2deg(geom.sar_geometry.incidence)
+                        ui.label(geometry_label(
+                            &self.project.geometry_convention,
+                            &geom.sar_geometry,
                         ));
                         ui.add_space(5.);
                         ui.label(format!("Data: {}", sar_data.name.to_owned()));
                         ui.add_space(5.);
-                        ui.label(format!("RMSE: {}", data.rmse));
+                        if let Some(metrics) = &data.metrics {
+                            egui::Grid::new("fit_metrics").striped(true).show(ui, |ui| {
+                                for (name, value) in [
+                                    ("RMSE", metrics.rmse),
+                                    ("MAE", metrics.mae),
+                                    ("Bias", metrics.bias),
+                                    ("R²", metrics.r2),
+                                    ("Normalised RMSE", metrics.nrmse),
+                                    ("Max abs residual", metrics.max_abs_residual),
+                                    ("AIC", metrics.aic),
+                                    ("BIC", metrics.bic),
+                                ] {
+                                    ui.label(name);
+                                    ui.label(format!("{:.4}", value));
+                                    ui.end_row();
+                                }
+                                if let Some(chi2) = data.reduced_chi_square {
+                                    ui.label("Reduced chi-square");
+                                    ui.label(format!("{:.4}", chi2));
+                                    ui.end_row();
+                                }
+                            });
+                        }
+                        ui.add_space(5.);
+                        if let Some(uncertainty) = &model.uncertainty {
+                            ui.label(if uncertainty.bootstrap_samples == 0 {
+                                "Weights (linearised uncertainty):".to_string()
+                            } else {
+                                format!(
+                                    "Weights (bootstrap, {} samples):",
+                                    uncertainty.bootstrap_samples
+                                )
+                            });
+                            egui::Grid::new("weights_uncertainty")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    ui.label("weight");
+                                    ui.label("std err");
+                                    ui.label("95% interval");
+                                    ui.end_row();
+                                    for k in 0..model.weights.len() {
+                                        ui.label(format!("{:.4}", model.weights[k]));
+                                        ui.label(format!("{:.4}", uncertainty.std_errors[k]));
+                                        ui.label(format!(
+                                            "[{:.4}, {:.4}]",
+                                            uncertainty.confidence_95[k][0],
+                                            uncertainty.confidence_95[k][1]
+                                        ));
+                                        ui.end_row();
+                                    }
+                                });
+                            ui.add_space(5.);
+                        }
+                        if !model.fits.is_empty() {
+                            ui.label("Calibration misfits:");
+                            egui::Grid::new("calibration_misfits")
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for fit in model.fits.iter() {
+                                        let Some((sar, disp)) =
+                                            self.project.sars.get(fit.sar).and_then(|sar| {
+                                                sar.disp_data.get(fit.data).map(|d| (sar, d))
+                                            })
+                                        else {
+                                            continue;
+                                        };
+                                        ui.label(format!("{} / {}", sar.name, disp.name));
+                                        ui.label(format!("RMSE: {}", fit.rmse));
+                                        if fit.offset != 0. || fit.ramp != 0. {
+                                            ui.label(format!("offset: {:.4}", fit.offset));
+                                            ui.label(format!("ramp: {:.4e} /m", fit.ramp));
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        }
                         ui.add_space(15.);
 
                         if ui.button("Export at").clicked() {
@@ -1684,6 +3687,11 @@ impl AppDM {
                                     ui.label(f);
                                 });
                             });
+                            ui.label(format!(
+                                "The fit metrics are written to {} and the observed, predicted and residual values at the data points to {}.",
+                                sidecar_path(f, "_metrics"),
+                                sidecar_path(f, "_data"),
+                            ));
                         }
 
                         ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
@@ -1699,7 +3707,18 @@ impl AppDM {
                                     data.export_status = CommandStatus::Clean;
                                 } else {
                                     if let Some(path) = &data.file_path {
-                                        match model.export_values(path, &data.amp_in_los) {
+                                        match model.export_values(
+                                            path,
+                                            &data.full_amp_in_los,
+                                            data.metrics.as_ref(),
+                                            &[
+                                                data.data_x.clone(),
+                                                data.amp_data.clone(),
+                                                data.amp_in_los.clone(),
+                                                data.residuals.clone(),
+                                            ],
+                                            self.project.dem.section_line.as_ref(),
+                                        ) {
                                             Err(_) => {
                                                 data.export_status =
                                                     CommandStatus::Error(CommandError::MethodError)
@@ -1726,6 +3745,63 @@ impl AppDM {
     }
 }
 
+/// Draw the line of sight of an orientation seen from above and in its vertical plane
+fn ui_los_diagram(ui: &mut egui::Ui, orientation: &Orientation) {
+    let los: Vector3Rep = orientation.to_owned().into();
+    let (north, east, up) = los.coords();
+    let size = 120.;
+    let stroke = egui::Stroke::new(2., ui.visuals().strong_text_color());
+    let los_stroke = egui::Stroke::new(2.5, egui::Color32::from_rgb(200, 60, 60));
+    let font = egui::FontId::proportional(12.);
+    let text_color = ui.visuals().text_color();
+
+    ui.horizontal(|ui| {
+        // Map view, north upward, the look direction from the satellite track
+        let (response, painter) = ui.allocate_painter(egui::vec2(size, size), egui::Sense::hover());
+        let center = response.rect.center();
+        let radius = size / 2. - 14.;
+        painter.circle_stroke(center, radius, egui::Stroke::new(1., egui::Color32::GRAY));
+        painter.text(
+            center - egui::vec2(0., radius + 7.),
+            egui::Align2::CENTER_CENTER,
+            "N",
+            font.clone(),
+            text_color,
+        );
+        let horizontal = (north * north + east * east).sqrt();
+        if horizontal > 0. {
+            let direction = egui::vec2(east / horizontal, -north / horizontal);
+            let heading = egui::vec2(-direction.y, direction.x);
+            painter.line_segment(
+                [center - heading * radius, center + heading * radius],
+                egui::Stroke::new(1., egui::Color32::GRAY),
+            );
+            painter.arrow(center, direction * radius, los_stroke);
+        }
+
+        // Vertical plane of the line of sight, from the satellite down to the ground
+        let (response, painter) = ui.allocate_painter(egui::vec2(size, size), egui::Sense::hover());
+        let rect = response.rect.shrink(14.);
+        let target = rect.right_bottom();
+        painter.line_segment([rect.left_bottom(), rect.right_bottom()], stroke);
+        let length = rect.height().min(rect.width());
+        // The screen y axis points downward, and the line of sight too
+        let satellite = target + egui::vec2(-horizontal * length, up * length);
+        painter.arrow(satellite, target - satellite, los_stroke);
+        painter.text(
+            satellite,
+            egui::Align2::CENTER_BOTTOM,
+            "SAT",
+            font.clone(),
+            text_color,
+        );
+    });
+    ui.label(format!(
+        "LOS unit vector (E, N, U): ({:.3}, {:.3}, {:.3})",
+        east, north, up
+    ));
+}
+
 /// from https://sts10.github.io/2019/06/06/is-all-equal-function.html
 fn is_all_diff(vec: &Vec<usize>) -> bool {
     vec.iter()
@@ -1,4 +1,5 @@
 use crate::app::AppDM;
+use crate::project::geometry_label;
 use egui_phosphor::regular as Phosphor;
 use src_logic::prelude::*;
 
@@ -20,6 +21,15 @@ impl AppDM {
                 .as_str()
                 + "°)";
         }
+        if let Some(crs) = self
+            .project
+            .dem
+            .section_line
+            .as_ref()
+            .and_then(|l| l.crs.as_ref())
+        {
+            dem_header = dem_header + " [" + crs + "]";
+        }
         egui::CollapsingHeader::new(dem_header)
             .default_open(true)
             .show(ui, |ui| {
@@ -144,6 +154,38 @@ impl AppDM {
                                         }
                                     });
                                 });
+                                let mass = bundle.mass(&self.project.dem.dem).cloned();
+                                egui::CollapsingHeader::new("Sliding mass")
+                                    .show(ui, |ui| {
+                                        match &mass {
+                                            Some(mass) => {
+                                                ui.label(format!("Max depth: {:.1} m at x = {:.1} m", mass.max_depth, mass.max_depth_x));
+                                                ui.label(format!("Length: {:.1} m ; area: {:.0} m²", mass.length, mass.area));
+                                            }
+                                            None => {
+                                                ui.label("Max depth: n/a");
+                                                ui.label("Length: n/a ; area: n/a");
+                                            }
+                                        }
+                                        ui.horizontal(|ui| {
+                                            let value = bundle.lateral_extent.value();
+                                            egui::ComboBox::from_id_salt("lateral_extent")
+                                                .selected_text(bundle.lateral_extent.name())
+                                                .show_ui(ui, |ui| {
+                                                    for extent in [LateralExtent::Width(value), LateralExtent::ShapeFactor(value)] {
+                                                        ui.selectable_value(&mut bundle.lateral_extent, extent, extent.name());
+                                                    }
+                                                });
+                                            match &mut bundle.lateral_extent {
+                                                LateralExtent::Width(w) => ui.add(egui::DragValue::new(w).speed(1.).range(0.0..=f32::MAX).suffix(" m")),
+                                                LateralExtent::ShapeFactor(f) => ui.add(egui::DragValue::new(f).speed(0.01).range(0.0..=f32::MAX)),
+                                            };
+                                        });
+                                        match &mass {
+                                            Some(mass) => ui.label(format!("Volume: {:.0} m³", mass.volume(bundle.lateral_extent))),
+                                            None => ui.label("Volume: n/a"),
+                                        };
+                                    });
                             });
                     });
                 });
@@ -204,6 +246,7 @@ impl AppDM {
         ui.separator();
 
         let nb_sat = self.project.sars.len();
+        let convention = self.project.geometry_convention;
         egui::CollapsingHeader::new(
             "Calibration data (".to_string() + nb_sat.to_string().as_str() + ")",
         )
@@ -213,18 +256,15 @@ impl AppDM {
                 let bundle = &mut self.project.sars[k];
                 ui.push_id(k, |ui| {
                     let nb_disp_data = bundle.disp_data.len();
+                    let nb_data = nb_disp_data + bundle.disp_series.len();
                     let mut bundle_name = bundle.name.clone().to_string();
-                    if nb_disp_data != 0 {
-                        bundle_name = bundle_name + " (" + nb_disp_data.to_string().as_str() + ")";
+                    if nb_data != 0 {
+                        bundle_name = bundle_name + " (" + nb_data.to_string().as_str() + ")";
                     }
                     egui::CollapsingHeader::new(bundle_name)
                         .default_open(true)
                         .show(ui, |ui| {
-                            ui.label(format!(
-                                "az: {}° ; i: {}°",
-                                rad2deg(bundle.sar_geometry.azimuth),
-                                rad2deg(bundle.sar_geometry.incidence)
-                            ));
+                            ui.label(geometry_label(&convention, &bundle.sar_geometry));
                             for i in 0..nb_disp_data {
                                 ui.horizontal(|ui| {
                                     ui.with_layout(
@@ -233,24 +273,101 @@ impl AppDM {
                                             ui.label(bundle.disp_data[i].name.to_owned());
                                         },
                                     );
-                                    // ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                                    //     ui.set_width(ui.available_width());
-                                    //     if self.is_viewer_properties {
-                                    //         if ui.button(Self::get_display_icon(true, !self.is_viewer_properties, bundle.disp_data[i].section_arrow)).clicked() {
-                                    //             bundle.disp_data[i].section_arrow = !bundle.disp_data[i].section_arrow;
-                                    //         };
-                                    //     } else {
-                                    //         if ui.button(Self::get_display_icon(true, !self.is_viewer_properties, bundle.disp_data[i].property_disp)).clicked() {
-                                    //             bundle.disp_data[i].property_disp = !bundle.disp_data[i].property_disp;
-                                    //         };
-                                    //     }
-                                    // });
+                                    if !bundle.disp_data[i].disp_data.projected_vecs.is_empty() {
+                                        let disp_data = &mut bundle.disp_data[i];
+                                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                            ui.set_width(ui.available_width());
+                                            if self.is_viewer_properties {
+                                                ui.disable();
+                                            }
+                                            if ui.button(Self::get_display_icon(true, !self.is_viewer_properties, disp_data.section_arrow)).clicked() {
+                                                disp_data.section_arrow = !disp_data.section_arrow;
+                                            }
+                                            if disp_data.section_arrow {
+                                                ui.menu_button(egui::RichText::new(Phosphor::PALETTE), |ui| {
+                                                    let mut checked = disp_data.color_arrow.is_none();
+                                                    ui.horizontal(|ui| {
+                                                        ui.checkbox(&mut checked, "auto");
+                                                        match checked {
+                                                            false if disp_data.color_arrow.is_none() => disp_data.color_arrow = Some([0, 0, 0]),
+                                                            false => {
+                                                                if let Some(arrow_color) = &mut disp_data.color_arrow {
+                                                                    egui::color_picker::color_edit_button_srgb(ui, arrow_color);
+                                                                };
+                                                            },
+                                                            true if disp_data.color_arrow.is_some() => disp_data.color_arrow = None,
+                                                            true => (),
+                                                        }
+                                                    });
+                                                });
+                                            }
+                                        });
+                                    }
                                 });
+                                let disp_data = &mut bundle.disp_data[i];
+                                if !disp_data.disp_data.projected_vecs.is_empty() & disp_data.section_arrow {
+                                    ui.add(egui::Slider::new(&mut disp_data.arrow_scaling_factor, 0.01..=1000.0).logarithmic(true));
+                                }
+                            }
+                            for series in bundle.disp_series.iter() {
+                                ui.label(format!("{} ({} dates)", series.name, series.series.nb_epochs()));
                             }
                         });
                 });
             }
         });
+
+        let nb_decompositions = self.project.decompositions.len();
+        if nb_decompositions != 0 {
+            ui.separator();
+            egui::CollapsingHeader::new(
+                "Decompositions (".to_string() + nb_decompositions.to_string().as_str() + ")",
+            )
+            .default_open(true)
+            .show(ui, |ui| {
+                for k in 0..nb_decompositions {
+                    let bundle = &mut self.project.decompositions[k];
+                    ui.push_id(k, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                                ui.label(bundle.name.to_owned());
+                            });
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                ui.set_width(ui.available_width());
+                                if self.is_viewer_properties {
+                                    ui.disable();
+                                }
+                                if ui.button(Self::get_display_icon(true, !self.is_viewer_properties, bundle.section_arrow)).clicked() {
+                                    bundle.section_arrow = !bundle.section_arrow;
+                                }
+                                if bundle.section_arrow {
+                                    ui.menu_button(egui::RichText::new(Phosphor::PALETTE), |ui| {
+                                        let mut checked = bundle.color_arrow.is_none();
+                                        ui.horizontal(|ui| {
+                                            ui.checkbox(&mut checked, "auto");
+                                            match checked {
+                                                false if bundle.color_arrow.is_none() => bundle.color_arrow = Some([0, 0, 0]),
+                                                false => {
+                                                    if let Some(arrow_color) = &mut bundle.color_arrow {
+                                                        egui::color_picker::color_edit_button_srgb(ui, arrow_color);
+                                                    };
+                                                },
+                                                true if bundle.color_arrow.is_some() => bundle.color_arrow = None,
+                                                true => (),
+                                            }
+                                        });
+                                    });
+                                }
+                            });
+                        });
+                        ui.label(format!("{} ; {} points", bundle.decomposition.basis.name(), bundle.decomposition.x.len()));
+                        if bundle.section_arrow {
+                            ui.add(egui::Slider::new(&mut bundle.arrow_scaling_factor, 0.01..=1000.0).logarithmic(true));
+                        }
+                    });
+                }
+            });
+        }
     }
 
     fn get_display_icon(
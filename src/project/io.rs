@@ -74,6 +74,15 @@ pub(crate) fn save_all_csv(project: &Project, root_folder: &String) -> Result<()
                 + ".csv";
             project.sars[g].disp_data[d].to_csv(&path)?;
         }
+        for t in 0..project.sars[g].disp_series.len() {
+            let path = root_folder.to_string()
+                + "/disp_"
+                + (g + 1).to_string().as_str()
+                + "_series_"
+                + (t + 1).to_string().as_str()
+                + ".csv";
+            project.sars[g].disp_series[t].to_csv(&path)?;
+        }
     }
 
     Ok(())
@@ -129,6 +138,12 @@ fn load_all_csv(project: &mut Project, project_file: &ProjectFile) -> Result<()>
                     }
                     None => (),
                 }
+                if let Some(series) = &geoms[g].series {
+                    for t in 0..series.len() {
+                        let path = root.to_string() + series[t].file_name.to_string().as_str();
+                        project.sars[g].disp_series[t].from_csv(&path)?;
+                    }
+                }
             }
         }
         None => (),
@@ -151,7 +166,10 @@ impl ProjectFile {
         let surface = if app_project.surfaces.is_empty() {
             None
         } else {
-            Some(SurfaceRelated::from_project(&app_project.surfaces))
+            Some(SurfaceRelated::from_project(
+                &app_project.surfaces,
+                &app_project.dem.dem,
+            ))
         };
         let model = if app_project.models.is_empty() {
             None
@@ -202,16 +220,97 @@ impl ProjectFile {
             None => (),
         }
 
+        // A hand-edited file may refer to missing data
+        let sars = &project.sars;
+        for model in project.models.iter_mut() {
+            model.fits.retain(|fit| {
+                sars.get(fit.sar)
+                    .is_some_and(|sar| fit.data < sar.disp_data.len())
+            });
+            if let Some(fit) = &model.series_fit {
+                if !sars
+                    .get(fit.sar)
+                    .is_some_and(|sar| fit.series < sar.disp_series.len())
+                {
+                    model.series_fit = None;
+                }
+            }
+        }
+
         project
     }
 }
 
+/// Stable identifier of an azimuth convention in the project file
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AzimuthConventionRelated {
+    LookDirection,
+    TowardSatellite,
+    HeadingRight,
+    HeadingLeft,
+}
+
+impl AzimuthConventionRelated {
+    fn from_project(convention: AzimuthConvention) -> Self {
+        match convention {
+            AzimuthConvention::LookDirection => AzimuthConventionRelated::LookDirection,
+            AzimuthConvention::TowardSatellite => AzimuthConventionRelated::TowardSatellite,
+            AzimuthConvention::Heading(LookSide::Right) => AzimuthConventionRelated::HeadingRight,
+            AzimuthConvention::Heading(LookSide::Left) => AzimuthConventionRelated::HeadingLeft,
+        }
+    }
+
+    fn to_project(self) -> AzimuthConvention {
+        match self {
+            AzimuthConventionRelated::LookDirection => AzimuthConvention::LookDirection,
+            AzimuthConventionRelated::TowardSatellite => AzimuthConvention::TowardSatellite,
+            AzimuthConventionRelated::HeadingRight => AzimuthConvention::Heading(LookSide::Right),
+            AzimuthConventionRelated::HeadingLeft => AzimuthConvention::Heading(LookSide::Left),
+        }
+    }
+}
+
+/// Stable identifier of an angle unit in the project file
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AngleUnitRelated {
+    Degrees,
+    Radians,
+}
+
+impl AngleUnitRelated {
+    fn from_project(unit: AngleUnit) -> Self {
+        match unit {
+            AngleUnit::Degrees => AngleUnitRelated::Degrees,
+            AngleUnit::Radians => AngleUnitRelated::Radians,
+        }
+    }
+
+    fn to_project(self) -> AngleUnit {
+        match self {
+            AngleUnitRelated::Degrees => AngleUnit::Degrees,
+            AngleUnitRelated::Radians => AngleUnit::Radians,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ProjectRelated {
     name: String,
     note: Option<String>,
     dem_file_name: String,
     dem_azimuth: Option<f32>,
+    #[serde(default)]
+    azimuth_convention: Option<AzimuthConventionRelated>,
+    #[serde(default)]
+    angle_unit: Option<AngleUnitRelated>,
+    #[serde(default)]
+    section_start: Option<[f64; 2]>,
+    #[serde(default)]
+    section_end: Option<[f64; 2]>,
+    #[serde(default)]
+    section_crs: Option<String>,
 }
 
 impl ProjectRelated {
@@ -228,11 +327,19 @@ impl ProjectRelated {
             None => None,
         };
 
+        let convention = &project.geometry_convention;
+        let section_line = project.dem.section_line.as_ref();
+
         Self {
             name,
             note,
             dem_file_name,
             dem_azimuth,
+            azimuth_convention: Some(AzimuthConventionRelated::from_project(convention.azimuth)),
+            angle_unit: Some(AngleUnitRelated::from_project(convention.unit)),
+            section_start: section_line.map(|l| l.start),
+            section_end: section_line.map(|l| l.end),
+            section_crs: section_line.and_then(|l| l.crs.clone()),
         }
     }
 
@@ -251,6 +358,20 @@ impl ProjectRelated {
             },
             None => None,
         };
+        if let (Some(start), Some(end)) = (self.section_start, self.section_end) {
+            dem.section_line = SectionLine::new(start, end, self.section_crs.clone()).ok();
+        }
+        // The line defines the geometry of the section when present
+        if let Some(line) = &dem.section_line {
+            dem.section_geometry = Some(line.orientation());
+        }
+        let geometry_convention = GeometryConvention {
+            azimuth: self
+                .azimuth_convention
+                .map(|c| c.to_project())
+                .unwrap_or_default(),
+            unit: self.angle_unit.map(|u| u.to_project()).unwrap_or_default(),
+        };
 
         Project {
             name,
@@ -260,6 +381,8 @@ impl ProjectRelated {
             surfaces: vec![],
             models: vec![],
             sars: vec![],
+            decompositions: vec![],
+            geometry_convention,
         }
     }
 }
@@ -268,15 +391,22 @@ impl ProjectRelated {
 struct SurfaceRelated {
     name: String,
     file_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mass: Option<MassRelated>,
 }
 
 impl SurfaceRelated {
-    fn from_project(surfaces: &Vec<BundleSurface>) -> Vec<Self> {
+    fn from_project(surfaces: &Vec<BundleSurface>, dem: &Dem1D) -> Vec<Self> {
         let mut relateds = vec![];
         for k in 0..surfaces.len() {
             let name = surfaces[k].name.to_string();
             let file_name = "surface_".to_string() + (k + 1).to_string().as_str() + ".csv";
-            let surface_related = Self { name, file_name };
+            let mass = MassRelated::from_project(&surfaces[k], dem);
+            let surface_related = Self {
+                name,
+                file_name,
+                mass,
+            };
             relateds.push(surface_related);
         }
         relateds
@@ -285,10 +415,60 @@ impl SurfaceRelated {
     fn to_project(&self, project: &mut Project) {
         let mut bundle = BundleSurface::default();
         bundle.name = self.name.to_string();
+        if let Some(mass) = &self.mass {
+            bundle.lateral_extent = mass.lateral_extent();
+        }
         project.surfaces.push(bundle);
     }
 }
 
+/// Summary of the sliding mass of a surface
+///
+/// Only the lateral extent is read back, the other figures are written for the reports and
+/// computed again from the surface.
+#[derive(Debug, Serialize, Deserialize)]
+struct MassRelated {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    width: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shape_factor: Option<f32>,
+    max_depth: f32,
+    max_depth_x: f32,
+    length: f32,
+    area: f32,
+    volume: f32,
+}
+
+impl MassRelated {
+    fn from_project(bundle: &BundleSurface, dem: &Dem1D) -> Option<Self> {
+        let mass = match &bundle.mass {
+            Some(mass) => mass.clone(),
+            None => SlidingMass::new(&bundle.surface, dem).ok()?,
+        };
+        let (width, shape_factor) = match bundle.lateral_extent {
+            LateralExtent::Width(w) => (Some(w), None),
+            LateralExtent::ShapeFactor(f) => (None, Some(f)),
+        };
+        Some(Self {
+            width,
+            shape_factor,
+            max_depth: mass.max_depth,
+            max_depth_x: mass.max_depth_x,
+            length: mass.length,
+            area: mass.area,
+            volume: mass.volume(bundle.lateral_extent),
+        })
+    }
+
+    fn lateral_extent(&self) -> LateralExtent {
+        match (self.width, self.shape_factor) {
+            (Some(w), _) => LateralExtent::Width(w),
+            (None, Some(f)) => LateralExtent::ShapeFactor(f),
+            (None, None) => LateralExtent::default(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ModelRelated {
     name: String,
@@ -296,6 +476,14 @@ struct ModelRelated {
     weights: Vec<f32>,
     boundaries: Vec<(usize, usize)>,
     gradients: Vec<Vec<(usize, f32)>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uncertainty: Option<UncertaintyRelated>,
+    #[serde(default, rename = "fit", skip_serializing_if = "Vec::is_empty")]
+    fits: Vec<FitRelated>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    settings: Option<CalibrationRelated>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series_fit: Option<SeriesFitRelated>,
 }
 
 impl ModelRelated {
@@ -307,12 +495,33 @@ impl ModelRelated {
             let weights = models[k].weights.clone();
             let boundaries = models[k].boundaries.clone();
             let gradients = models[k].gradients.clone();
+            let uncertainty = models[k]
+                .uncertainty
+                .as_ref()
+                .map(UncertaintyRelated::from_project);
+            let fits = models[k]
+                .fits
+                .iter()
+                .map(FitRelated::from_project)
+                .collect();
+            let settings = models[k]
+                .settings
+                .as_ref()
+                .map(CalibrationRelated::from_project);
+            let series_fit = models[k]
+                .series_fit
+                .as_ref()
+                .map(SeriesFitRelated::from_project);
             let model_related = Self {
                 name,
                 file_name,
                 weights,
                 boundaries,
                 gradients,
+                uncertainty,
+                fits,
+                settings,
+                series_fit,
             };
             relateds.push(model_related);
         }
@@ -325,16 +534,223 @@ impl ModelRelated {
         bundle.weights = self.weights.clone();
         bundle.boundaries = self.boundaries.clone();
         bundle.gradients = self.gradients.clone();
+        bundle.uncertainty = self.uncertainty.as_ref().map(|u| u.to_project());
+        bundle.fits = self.fits.iter().map(|f| f.to_project()).collect();
+        bundle.settings = self.settings.as_ref().map(|s| s.to_project());
+        bundle.series_fit = self.series_fit.as_ref().map(|s| s.to_project());
         project.models.push(bundle);
     }
 }
 
+/// Stable identifier of a solver in the project file, independent of its displayed name
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SolverRelated {
+    NonNegativeLinear,
+    Linear,
+    BoundedLinear,
+    ParticleSwarm,
+}
+
+impl SolverRelated {
+    fn from_project(solver: Solver) -> Self {
+        match solver {
+            Solver::NonNegativeLinear => SolverRelated::NonNegativeLinear,
+            Solver::Linear => SolverRelated::Linear,
+            Solver::BoundedLinear => SolverRelated::BoundedLinear,
+            Solver::ParticleSwarm => SolverRelated::ParticleSwarm,
+        }
+    }
+
+    fn to_project(self) -> Solver {
+        match self {
+            SolverRelated::NonNegativeLinear => Solver::NonNegativeLinear,
+            SolverRelated::Linear => Solver::Linear,
+            SolverRelated::BoundedLinear => Solver::BoundedLinear,
+            SolverRelated::ParticleSwarm => Solver::ParticleSwarm,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CalibrationRelated {
+    solver: SolverRelated,
+    lower_bounds: Vec<f32>,
+    upper_bounds: Vec<f32>,
+    fixed_weights: Vec<(usize, f32)>,
+    initial_guess: Vec<f32>,
+    bootstrap_samples: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    free_gradients: Vec<usize>,
+    #[serde(default)]
+    gradient_smoothing: f32,
+    #[serde(default)]
+    fit_offset: bool,
+    #[serde(default)]
+    fit_ramp: bool,
+    #[serde(default)]
+    footprint_average: bool,
+}
+
+impl CalibrationRelated {
+    fn from_project(settings: &CalibrationSettings) -> Self {
+        Self {
+            solver: SolverRelated::from_project(settings.solver),
+            lower_bounds: settings.lower_bounds.clone(),
+            upper_bounds: settings.upper_bounds.clone(),
+            fixed_weights: settings
+                .fixed_weights
+                .iter()
+                .enumerate()
+                .filter_map(|(k, w)| w.map(|w| (k, w)))
+                .collect(),
+            initial_guess: settings.initial_guess.clone(),
+            bootstrap_samples: settings.bootstrap_samples,
+            free_gradients: (0..settings.free_gradients.len())
+                .filter(|k| settings.free_gradients[*k])
+                .collect(),
+            gradient_smoothing: settings.gradient_smoothing,
+            fit_offset: settings.fit_offset,
+            fit_ramp: settings.fit_ramp,
+            footprint_average: settings.footprint_average,
+        }
+    }
+
+    fn to_project(&self) -> CalibrationSettings {
+        let mut fixed_weights = vec![None; self.initial_guess.len()];
+        for (k, w) in &self.fixed_weights {
+            if let Some(fixed) = fixed_weights.get_mut(*k) {
+                *fixed = Some(*w);
+            }
+        }
+        let mut free_gradients = vec![false; self.initial_guess.len()];
+        for k in &self.free_gradients {
+            if let Some(free) = free_gradients.get_mut(*k) {
+                *free = true;
+            }
+        }
+        CalibrationSettings {
+            solver: self.solver.to_project(),
+            lower_bounds: self.lower_bounds.clone(),
+            upper_bounds: self.upper_bounds.clone(),
+            fixed_weights,
+            initial_guess: self.initial_guess.clone(),
+            bootstrap_samples: self.bootstrap_samples,
+            free_gradients,
+            gradient_smoothing: self.gradient_smoothing,
+            fit_offset: self.fit_offset,
+            fit_ramp: self.fit_ramp,
+            footprint_average: self.footprint_average,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SeriesFitRelated {
+    sar: usize,
+    series: usize,
+    dates: Vec<String>,
+    weights: Vec<Vec<f32>>,
+    misfits: Vec<f32>,
+}
+
+impl SeriesFitRelated {
+    fn from_project(fit: &SeriesFit) -> Self {
+        Self {
+            sar: fit.sar,
+            series: fit.series,
+            dates: fit.weights.dates.clone(),
+            weights: fit.weights.weights.clone(),
+            misfits: fit.weights.misfits.clone(),
+        }
+    }
+
+    fn to_project(&self) -> SeriesFit {
+        SeriesFit {
+            sar: self.sar,
+            series: self.series,
+            weights: WeightSeries {
+                dates: self.dates.clone(),
+                weights: self.weights.clone(),
+                misfits: self.misfits.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FitRelated {
+    sar: usize,
+    data: usize,
+    rmse: f32,
+    #[serde(default)]
+    offset: f32,
+    #[serde(default)]
+    ramp: f32,
+}
+
+impl FitRelated {
+    fn from_project(fit: &DatasetFit) -> Self {
+        Self {
+            sar: fit.sar,
+            data: fit.data,
+            rmse: fit.rmse,
+            offset: fit.offset,
+            ramp: fit.ramp,
+        }
+    }
+
+    fn to_project(&self) -> DatasetFit {
+        DatasetFit {
+            sar: self.sar,
+            data: self.data,
+            rmse: self.rmse,
+            offset: self.offset,
+            ramp: self.ramp,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UncertaintyRelated {
+    covariance: Vec<Vec<f32>>,
+    std_errors: Vec<f32>,
+    confidence_95: Vec<(f32, f32)>,
+    bootstrap_samples: usize,
+}
+
+impl UncertaintyRelated {
+    fn from_project(uncertainty: &WeightUncertainty) -> Self {
+        Self {
+            covariance: uncertainty.covariance.clone(),
+            std_errors: uncertainty.std_errors.clone(),
+            confidence_95: uncertainty
+                .confidence_95
+                .iter()
+                .map(|[a, b]| (*a, *b))
+                .collect(),
+            bootstrap_samples: uncertainty.bootstrap_samples,
+        }
+    }
+
+    fn to_project(&self) -> WeightUncertainty {
+        WeightUncertainty {
+            covariance: self.covariance.clone(),
+            std_errors: self.std_errors.clone(),
+            confidence_95: self.confidence_95.iter().map(|(a, b)| [*a, *b]).collect(),
+            bootstrap_samples: self.bootstrap_samples,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct DispGeomRelated {
     name: String,
     azimuth: f32,
     incidence: f32,
     datas: Option<Vec<DispDataRelated>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    series: Option<Vec<DispDataRelated>>,
 }
 
 impl DispGeomRelated {
@@ -349,11 +765,17 @@ impl DispGeomRelated {
             } else {
                 Some(DispDataRelated::from_project(&sar[k].disp_data, k))
             };
+            let series = if sar[k].disp_series.is_empty() {
+                None
+            } else {
+                Some(DispDataRelated::from_series(&sar[k].disp_series, k))
+            };
             let geom_related = Self {
                 name,
                 azimuth,
                 incidence,
                 datas,
+                series,
             };
             relateds.push(geom_related);
         }
@@ -374,6 +796,15 @@ impl DispGeomRelated {
             None => (),
         }
         bundle.disp_data = sub_bundles;
+        if let Some(series) = &self.series {
+            bundle.disp_series = series
+                .iter()
+                .map(|s| BundleDispSeries {
+                    name: s.name.to_string(),
+                    series: DispSeries::default(),
+                })
+                .collect();
+        }
         project.sars.push(bundle);
     }
 }
@@ -401,6 +832,21 @@ impl DispDataRelated {
         relateds
     }
 
+    fn from_series(series: &Vec<BundleDispSeries>, geom_index: usize) -> Vec<Self> {
+        let mut relateds = vec![];
+        for k in 0..series.len() {
+            let name = series[k].name.to_string();
+            let file_name = "disp".to_string()
+                + "_"
+                + (geom_index + 1).to_string().as_str()
+                + "_series_"
+                + (k + 1).to_string().as_str()
+                + ".csv";
+            relateds.push(Self { name, file_name });
+        }
+        relateds
+    }
+
     fn to_project(&self) -> BundleDispData {
         let mut bundle = BundleDispData::default();
         bundle.name = self.name.to_string();
@@ -446,7 +892,12 @@ impl BundleSurface {
         Ok(())
     }
 
-    pub(crate) fn export_values(&mut self, path: &String, dem: &Dem1D) -> Result<()> {
+    pub(crate) fn export_values(
+        &mut self,
+        path: &String,
+        dem: &Dem1D,
+        section_line: Option<&SectionLine>,
+    ) -> Result<()> {
         if self.surface.slope.is_none() {
             self.surface.get_slope(dem);
         }
@@ -457,16 +908,21 @@ impl BundleSurface {
             dem.x.clone(),
             self.surface.z.clone(),
             self.surface.slope.clone().unwrap(),
+            self.surface.depths(dem, DepthMode::Vertical),
+            self.surface.depths(dem, DepthMode::Normal),
             origins.iter().map(|[a, _]| *a).collect(),
             origins.iter().map(|[_, b]| *b).collect(),
             vecs.iter().map(|v| v.coords().0).collect(),
             vecs.iter().map(|v| v.coords().1).collect(),
         ];
 
+        // The depth is vertical and the thickness normal to the DEM
         let headers = vec![
             "x".to_string(),
             "z".to_string(),
             "slope".to_string(),
+            "depth".to_string(),
+            "thickness".to_string(),
             "ox".to_string(),
             "oz".to_string(),
             "vx".to_string(),
@@ -474,6 +930,7 @@ impl BundleSurface {
         ];
 
         let writer = CsvWriter::from_datas_headers(datas, headers)?;
+        let writer = with_map_coordinates(writer, section_line, &dem.x)?;
         writer.write(path, None)?;
         Ok(())
     }
@@ -513,7 +970,22 @@ impl BundleModel {
         Ok(())
     }
 
-    pub(crate) fn export_values(&self, path: &String, amp_los: &Vec<f32>) -> Result<()> {
+    /// Export the vectors of the model, and the analysis against the data in sidecar files
+    /// named after `path`
+    ///
+    /// * `_metrics`: the metrics of the fit
+    /// * `_data`: at each data point, `data_values` holds x, the observed and predicted
+    ///   amplitudes in the line of sight, and the residual
+    ///
+    /// The map coordinates of the points are added if the section is georeferenced.
+    pub(crate) fn export_values(
+        &self,
+        path: &String,
+        amp_los: &Vec<f32>,
+        metrics: Option<&FitMetrics>,
+        data_values: &[Vec<f32>; 4],
+        section_line: Option<&SectionLine>,
+    ) -> Result<()> {
         let origins = self.resulting_profile.origins.clone();
         let vecs = self.resulting_profile.vecs.clone();
 
@@ -540,23 +1012,177 @@ impl BundleModel {
         }
 
         let writer = CsvWriter::from_datas_headers(datas, headers)?;
+        let x: Vec<f32> = origins.iter().map(|[a, _]| *a).collect();
+        let writer = with_map_coordinates(writer, section_line, &x)?;
         writer.write(path, None)?;
+
+        if let Some(metrics) = metrics {
+            let datas = metrics.values().iter().map(|v| vec![*v]).collect();
+            let headers = FitMetrics::NAMES.iter().map(|n| n.to_string()).collect();
+            let writer = CsvWriter::from_datas_headers(datas, headers)?;
+            writer.write(&sidecar_path(path, "_metrics"), None)?;
+        }
+
+        if !data_values[0].is_empty() {
+            let headers = ["x", "disp", "amp_in_los", "residual"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect();
+            let writer = CsvWriter::from_datas_headers(data_values.to_vec(), headers)?;
+            let writer = with_map_coordinates(writer, section_line, &data_values[0])?;
+            writer.write(&sidecar_path(path, "_data"), None)?;
+        }
         Ok(())
     }
 }
 
+/// Append the map coordinates of the positions `x` along the section, if it is georeferenced
+fn with_map_coordinates(
+    writer: CsvWriter,
+    section_line: Option<&SectionLine>,
+    x: &[f32],
+) -> Result<CsvWriter> {
+    let Some(line) = section_line else {
+        return Ok(writer);
+    };
+    let points: Vec<[f64; 2]> = x.iter().map(|x| line.point_at(*x)).collect();
+    writer.with_precise_datas(
+        vec![
+            points.iter().map(|p| p[0]).collect(),
+            points.iter().map(|p| p[1]).collect(),
+        ],
+        vec!["easting".to_string(), "northing".to_string()],
+    )
+}
+
+/// Path of a file next to `path`, with a suffix added to its stem
+pub(crate) fn sidecar_path(path: &String, suffix: &str) -> String {
+    let path = Path::new(path);
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(ext) => format!("{}{}.{}", stem, suffix, ext.to_string_lossy()),
+        None => format!("{}{}", stem, suffix),
+    };
+    path.with_file_name(file_name).display().to_string()
+}
+
 impl BundleDispData {
     fn from_csv(&mut self, path: &String) -> Result<()> {
         let reader = CsvReader::read(path.clone(), None)?;
         let datas = reader.get_datas(&vec!["x".to_string(), "disp".to_string()])?;
-        self.disp_data.x = datas[0].clone();
-        self.disp_data.amplitude = datas[1].clone();
+        // Checked as when importing the data
+        let mut disp_data = DispData::new(datas[0].clone(), datas[1].clone())?;
+        if reader.headers.contains(&"sigma".to_string()) {
+            disp_data = disp_data.with_sigma(reader.get_data(&"sigma".to_string())?)?;
+        }
+        if reader.headers.contains(&"footprint".to_string()) {
+            disp_data = disp_data.with_footprint(reader.get_data(&"footprint".to_string())?)?;
+        }
+        if reader.headers.contains(&"los_e".to_string()) {
+            let datas = reader.get_datas(
+                &["los_e", "los_n", "los_u"]
+                    .iter()
+                    .map(|h| h.to_string())
+                    .collect(),
+            )?;
+            disp_data = disp_data.with_los(
+                (0..datas[0].len())
+                    .map(|k| Vector3Rep::new(datas[1][k], datas[0][k], datas[2][k]))
+                    .collect(),
+            )?;
+        }
+        self.disp_data = disp_data;
+        Ok(())
+    }
+
+    fn to_csv(&self, path: &String) -> Result<()> {
+        let mut datas = vec![self.disp_data.x.clone(), self.disp_data.amplitude.clone()];
+        let mut headers = vec!["x".to_string(), "disp".to_string()];
+        if let Some(sigma) = &self.disp_data.sigma {
+            datas.push(sigma.clone());
+            headers.push("sigma".to_string());
+        }
+        if let Some(footprint) = &self.disp_data.footprint {
+            datas.push(footprint.clone());
+            headers.push("footprint".to_string());
+        }
+        if let Some(los) = &self.disp_data.los {
+            datas.push(los.iter().map(|v| v.coords().1).collect());
+            datas.push(los.iter().map(|v| v.coords().0).collect());
+            datas.push(los.iter().map(|v| v.coords().2).collect());
+            headers.extend(["los_e", "los_n", "los_u"].iter().map(|h| h.to_string()));
+        }
+        let writer = CsvWriter::from_datas_headers(datas, headers)?;
+        writer.write(&path.clone(), None)?;
+        Ok(())
+    }
+}
+
+impl BundleDecomposition {
+    /// Export the decomposed components and the vectors in the section
+    ///
+    /// The ill-conditioned points are flagged by a 1. The map coordinates of the points are
+    /// added if the section is georeferenced.
+    pub(crate) fn export_values(
+        &self,
+        path: &String,
+        section_line: Option<&SectionLine>,
+    ) -> Result<()> {
+        let decomposition = &self.decomposition;
+        let (horizontal, vertical) = match decomposition.basis {
+            DecompositionBasis::Section => ("horizontal", "vertical"),
+            DecompositionBasis::EastUp => ("east", "up"),
+        };
+        let origins = &decomposition.profile.origins;
+        let vecs = &decomposition.profile.vecs;
+        let datas = vec![
+            decomposition.x.clone(),
+            origins.iter().map(|[_, b]| *b).collect(),
+            decomposition.horizontal.clone(),
+            decomposition.vertical.clone(),
+            vecs.iter().map(|v| v.coords().0).collect(),
+            vecs.iter().map(|v| v.coords().1).collect(),
+            decomposition
+                .ill_conditioned
+                .iter()
+                .map(|flag| if *flag { 1. } else { 0. })
+                .collect(),
+        ];
+        let headers = vec![
+            "x".to_string(),
+            "oz".to_string(),
+            horizontal.to_string(),
+            vertical.to_string(),
+            "vx".to_string(),
+            "vz".to_string(),
+            "ill_conditioned".to_string(),
+        ];
+        let writer = CsvWriter::from_datas_headers(datas, headers)?;
+        let writer = with_map_coordinates(writer, section_line, &decomposition.x)?;
+        writer.write(path, None)?;
+        Ok(())
+    }
+}
+
+impl BundleDispSeries {
+    fn from_csv(&mut self, path: &String) -> Result<()> {
+        let reader = CsvReader::read(path.clone(), None)?;
+        self.series = DispSeries::from_csv_reader(&reader, &mut String::new(), &mut String::new())?;
         Ok(())
     }
 
     fn to_csv(&self, path: &String) -> Result<()> {
-        let datas = vec![self.disp_data.x.clone(), self.disp_data.amplitude.clone()];
-        let headers = vec!["x".to_string(), "disp".to_string()];
+        let mut datas = vec![self.series.x.clone()];
+        let mut headers = vec!["x".to_string()];
+        if let Some(sigma) = &self.series.sigma {
+            datas.push(sigma.clone());
+            headers.push("sigma".to_string());
+        }
+        datas.extend(self.series.amplitudes.iter().cloned());
+        headers.extend(self.series.dates.iter().cloned());
         let writer = CsvWriter::from_datas_headers(datas, headers)?;
         writer.write(&path.clone(), None)?;
         Ok(())
@@ -568,6 +1194,110 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_sidecar_path() {
+        let path = Path::new("folder").join("model.csv").display().to_string();
+        let expect = Path::new("folder")
+            .join("model_metrics.csv")
+            .display()
+            .to_string();
+        assert_eq!(sidecar_path(&path, "_metrics"), expect);
+        assert_eq!(
+            sidecar_path(&"model".to_string(), "_metrics"),
+            "model_metrics"
+        );
+    }
+
+    #[test]
+    fn test_disp_data_reload() {
+        let path = std::env::temp_dir()
+            .join("sldm_test_disp_data_reload.csv")
+            .display()
+            .to_string();
+        let mut bundle = BundleDispData::default();
+
+        std::fs::write(&path, "x;disp;sigma\n0;1;0.5\n10;2;0.5\n").unwrap();
+        bundle.from_csv(&path).unwrap();
+        assert_eq!(bundle.disp_data.sigma, Some(vec![0.5, 0.5]));
+
+        // Rejected as when importing the data
+        std::fs::write(&path, "x;disp;sigma\n0;1;0\n10;2;0.5\n").unwrap();
+        assert!(bundle.from_csv(&path).is_err());
+        std::fs::write(&path, "x;disp;footprint\n0;1;-5\n10;2;5\n").unwrap();
+        assert!(bundle.from_csv(&path).is_err());
+
+        // The lines of sight are normalized, from the satellite to the ground
+        std::fs::write(&path, "x;disp;los_e;los_n;los_u\n0;1;0;3;-4\n10;2;0;0;-2\n").unwrap();
+        bundle.from_csv(&path).unwrap();
+        let los = bundle.disp_data.los.as_ref().unwrap();
+        assert!((los[0].amplitude() - 1.).abs() < 1e-6);
+        assert_eq!(los[1].coords(), (0., 0., -1.));
+        std::fs::write(&path, "x;disp;los_e;los_n;los_u\n0;1;0;0;0\n10;2;0;0;-1\n").unwrap();
+        assert!(bundle.from_csv(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_solver_toml() {
+        for solver in Solver::ALL {
+            let related = SolverRelated::from_project(solver);
+            assert_eq!(related.to_project(), solver);
+        }
+        let settings = CalibrationRelated::from_project(&CalibrationSettings::new(2));
+        let toml = toml::to_string(&settings).unwrap();
+        assert!(toml.contains("solver = \"non_negative_linear\""));
+
+        // Unknown solvers are rejected
+        let unknown = toml.replace("non_negative_linear", "simplex");
+        assert!(toml::from_str::<CalibrationRelated>(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_convention_toml() {
+        for azimuth in AzimuthConvention::ALL {
+            assert_eq!(
+                AzimuthConventionRelated::from_project(azimuth).to_project(),
+                azimuth
+            );
+        }
+        for unit in AngleUnit::ALL {
+            assert_eq!(AngleUnitRelated::from_project(unit).to_project(), unit);
+        }
+        let toml = r#"
+            name = "Conventions"
+            dem_file_name = "dem.csv"
+            azimuth_convention = "heading_left"
+            angle_unit = "radians"
+        "#;
+        let project: ProjectRelated = toml::from_str(toml).unwrap();
+        let convention = project.to_project(&String::new()).geometry_convention;
+        assert_eq!(
+            convention.azimuth,
+            AzimuthConvention::Heading(LookSide::Left)
+        );
+        assert_eq!(convention.unit, AngleUnit::Radians);
+
+        // Unknown values are rejected
+        assert!(toml::from_str::<ProjectRelated>(&toml.replace("heading_left", "north")).is_err());
+        assert!(toml::from_str::<ProjectRelated>(&toml.replace("radians", "grads")).is_err());
+    }
+
+    #[test]
+    fn test_toml_section_line() {
+        let toml = r#"
+            name = "Section"
+            dem_file_name = "dem.csv"
+            dem_azimuth = 1.0
+            section_start = [0.0, 0.0]
+            section_end = [100.0, 0.0]
+        "#;
+        let project: ProjectRelated = toml::from_str(toml).unwrap();
+        let dem = project.to_project(&String::new()).dem;
+        let geometry = dem.section_geometry.unwrap();
+        assert_eq!(geometry, dem.section_line.unwrap().orientation());
+        assert!((geometry.azimuth - std::f32::consts::PI / 2.).abs() < 1e-6);
+    }
+
     #[test]
     fn test_toml_empty() {
         let project = ProjectFile {
@@ -576,6 +1306,11 @@ mod tests {
                 note: None,
                 dem_file_name: String::from("dem.csv"),
                 dem_azimuth: None,
+                azimuth_convention: None,
+                angle_unit: None,
+                section_start: None,
+                section_end: None,
+                section_crs: None,
             },
             surface: None,
             model: None,
@@ -587,8 +1322,67 @@ mod tests {
         file.write_all(toml.as_bytes()).unwrap();
     }
 
+    #[test]
+    fn test_toml_invalid_fits() {
+        let toml = r#"
+            [project]
+            name = "Fits"
+            dem_file_name = "dem.csv"
+
+            [[model]]
+            name = "model"
+            file_name = "model_1.csv"
+            weights = [1.0]
+            boundaries = [[1, 3]]
+            gradients = [[]]
+
+            [[model.fit]]
+            sar = 0
+            data = 0
+            rmse = 1.0
+
+            [[model.fit]]
+            sar = 0
+            data = 1
+            rmse = 1.0
+
+            [[model.fit]]
+            sar = 1
+            data = 0
+            rmse = 1.0
+
+            [model.series_fit]
+            sar = 0
+            series = 0
+            dates = []
+            weights = []
+            misfits = []
+
+            [[disp_data]]
+            name = "geometry"
+            azimuth = 260.0
+            incidence = 35.0
+
+            [[disp_data.datas]]
+            name = "data"
+            file_name = "disp_1_data_1.csv"
+        "#;
+        let project_file: ProjectFile = toml::from_str(toml).unwrap();
+        let project = project_file.to_project(&String::from("project.toml"));
+        assert_eq!(project.models[0].fits.len(), 1);
+        assert_eq!(project.models[0].fits[0].data, 0);
+        // The geometry holds no series
+        assert!(project.models[0].series_fit.is_none());
+    }
+
     #[test]
     fn test_toml() {
+        let line = SectionLine::new(
+            [354_200.5, 5_012_050.],
+            [355_010., 5_011_900.25],
+            Some(String::from("EPSG:32632")),
+        )
+        .unwrap();
         let proj = ProjectFile {
             project: ProjectRelated {
                 name: String::from("My dear little project"),
@@ -596,16 +1390,31 @@ mod tests {
                     "Usefull data I want to remember about my project",
                 )),
                 dem_file_name: String::from("dem.csv"),
-                dem_azimuth: Some(289.),
+                dem_azimuth: Some(line.orientation().azimuth),
+                azimuth_convention: Some(AzimuthConventionRelated::HeadingRight),
+                angle_unit: Some(AngleUnitRelated::Degrees),
+                section_start: Some(line.start),
+                section_end: Some(line.end),
+                section_crs: Some(String::from("EPSG:32632")),
             },
             surface: Some(vec![
                 SurfaceRelated {
                     name: String::from("surf1"),
                     file_name: String::from("surf1.csv"),
+                    mass: Some(MassRelated {
+                        width: Some(150.),
+                        shape_factor: None,
+                        max_depth: 42.5,
+                        max_depth_x: 310.,
+                        length: 480.,
+                        area: 12_000.,
+                        volume: 1_800_000.,
+                    }),
                 },
                 SurfaceRelated {
                     name: String::from("surf2"),
                     file_name: String::from("surf2.csv"),
+                    mass: None,
                 },
             ]),
             model: Some(vec![
@@ -615,6 +1424,10 @@ mod tests {
                     weights: vec![],
                     boundaries: vec![],
                     gradients: vec![],
+                    uncertainty: None,
+                    fits: vec![],
+                    settings: None,
+                    series_fit: None,
                 },
                 ModelRelated {
                     name: String::from("model two"),
@@ -622,6 +1435,10 @@ mod tests {
                     weights: vec![],
                     boundaries: vec![],
                     gradients: vec![],
+                    uncertainty: None,
+                    fits: vec![],
+                    settings: None,
+                    series_fit: None,
                 },
                 ModelRelated {
                     name: String::from("model three"),
@@ -629,6 +1446,10 @@ mod tests {
                     weights: vec![],
                     boundaries: vec![],
                     gradients: vec![],
+                    uncertainty: None,
+                    fits: vec![],
+                    settings: None,
+                    series_fit: None,
                 },
             ]),
             disp_data: Some(vec![
@@ -646,12 +1467,14 @@ mod tests {
                             file_name: String::from("data2.csv"),
                         },
                     ]),
+                    series: None,
                 },
                 DispGeomRelated {
                     name: String::from("sat_geometry2"),
                     azimuth: 260.,
                     incidence: 35.,
                     datas: None,
+                    series: None,
                 },
             ]),
         };
@@ -14,6 +14,8 @@ pub(crate) struct Project {
     pub(crate) surfaces: Vec<BundleSurface>,
     pub(crate) models: Vec<BundleModel>,
     pub(crate) sars: Vec<BundleSar>,
+    pub(crate) decompositions: Vec<BundleDecomposition>,
+    pub(crate) geometry_convention: GeometryConvention,
 }
 
 impl Default for Project {
@@ -26,15 +28,141 @@ impl Default for Project {
             surfaces: vec![],
             models: vec![],
             sars: vec![],
+            decompositions: vec![],
+            geometry_convention: GeometryConvention::default(),
         }
     }
 }
 
 impl Project {
-    pub(crate) fn open_dem_from_file(&mut self, path: String) -> Result<()> {
-        let reader = CsvReader::read(path, None)?;
-        let dem = Dem1D::from_csv_reader(&reader, &mut String::new(), &mut String::new())?;
+    /// Open the DEM from its sampling and elevations once repaired
+    pub(crate) fn open_dem(&mut self, x: &[f32], z: &[f32], repair: &DemRepair) -> Result<()> {
+        self.dem.dem = repair.apply(x, z)?;
+        self.clear_masses();
+        Ok(())
+    }
+
+    /// Sample the DEM of the section along `line` in an elevation raster, which also sets the
+    /// geometry of the section
+    pub(crate) fn open_dem_from_raster(
+        &mut self,
+        raster: &Raster,
+        line: SectionLine,
+        spacing: f32,
+    ) -> Result<()> {
+        let (dem, _) = raster.sample_section(&line, spacing)?;
+        self.dem.dem = dem;
+        self.clear_masses();
+        self.dem.section_geometry = Some(line.orientation());
+        self.dem.section_line = Some(line);
+        Ok(())
+    }
+
+    /// Bin the DEM of the section along `line` from the points of a cloud within `buffer`,
+    /// which also sets the geometry of the section
+    pub(crate) fn open_dem_from_point_cloud(
+        &mut self,
+        cloud: &PointCloud,
+        line: SectionLine,
+        spacing: f32,
+        buffer: f32,
+        aggregation: PointAggregation,
+    ) -> Result<()> {
+        let (dem, _) = cloud.sample_section(&line, spacing, buffer, aggregation)?;
         self.dem.dem = dem;
+        self.clear_masses();
+        self.dem.section_geometry = Some(line.orientation());
+        self.dem.section_line = Some(line);
+        Ok(())
+    }
+
+    /// Forget the sliding masses computed on the former DEM
+    fn clear_masses(&mut self) {
+        for bundle in self.surfaces.iter_mut() {
+            bundle.mass = None;
+        }
+    }
+
+    /// Move the DEM onto a new regular sampling, along with every object sampled on it
+    ///
+    /// The surfaces are interpolated and their profiles regenerated. The models are rebuilt
+    /// from their weights, with their boundaries and gradient points moved to the closest
+    /// points of the new sampling, and lose their calibration against the data. The
+    /// projections of the data and the decompositions, sampled on the former DEM, are
+    /// removed and have to be computed again.
+    pub(crate) fn resample_dem(&mut self, spacing: f32, range: Option<[f32; 2]>) -> Result<()> {
+        let old_x = self.dem.dem.x.clone();
+        let dem = self.dem.dem.resample(spacing, range)?;
+        let len = dem.x.len();
+        if len < 4 {
+            return Err(anyhow!("The new sampling has too few points"));
+        }
+
+        let mut surfaces = Vec::with_capacity(self.surfaces.len());
+        for bundle in self.surfaces.iter() {
+            let mut surface = bundle.surface.interpolate_on_x(&old_x, &dem.x);
+            let profile = DispProfile::from_surface_direct(&mut surface, &dem)?;
+            surfaces.push((surface, profile));
+        }
+
+        let remap = |k: usize| dem.nearest_index(old_x[k.min(old_x.len() - 1)]);
+        let mut models = Vec::with_capacity(self.models.len());
+        for bundle in self.models.iter() {
+            let mut model = bundle.clone();
+            model.surfaces = bundle
+                .surfaces
+                .iter()
+                .map(|s| s.interpolate_on_x(&old_x, &dem.x))
+                .collect();
+            model.boundaries = bundle
+                .boundaries
+                .iter()
+                .map(|(a, b)| (remap(*a).clamp(1, len - 3), remap(*b).clamp(2, len - 2)))
+                .collect();
+            if model.boundaries.iter().any(|(a, b)| a >= b) {
+                return Err(anyhow!("The boundaries of {} are merged", model.name));
+            }
+            model.gradients = bundle
+                .gradients
+                .iter()
+                .map(|gradient| {
+                    let mut gradient: Vec<(usize, f32)> =
+                        gradient.iter().map(|(k, w)| (remap(*k), *w)).collect();
+                    gradient.dedup_by_key(|(k, _)| *k);
+                    gradient
+                })
+                .collect();
+            model.settings = None;
+            model.uncertainty = None;
+            model.fits.clear();
+            model.series_fit = None;
+            let boundaries = model.boundaries.iter().map(|(a, b)| [*a, *b]).collect();
+            model.resulting_profile = DispProfile::from_surfaces(
+                &dem,
+                &mut model.surfaces,
+                &boundaries,
+                &model.gradients,
+                &model.weights,
+            )?;
+            models.push(model);
+        }
+
+        self.dem.dem = dem;
+        for (bundle, (surface, profile)) in self.surfaces.iter_mut().zip(surfaces) {
+            bundle.surface = surface;
+            bundle.profile = profile;
+            bundle.mass = None;
+        }
+        self.models = models;
+        for bundle in self
+            .sars
+            .iter_mut()
+            .flat_map(|sar| sar.disp_data.iter_mut())
+        {
+            bundle.disp_data.projected_vecs.clear();
+            bundle.disp_data.ill_conditioned.clear();
+        }
+        self.decompositions.clear();
         Ok(())
     }
 
@@ -55,6 +183,43 @@ impl Project {
         Ok(())
     }
 
+    /// Open a surface sampled differently from the DEM by interpolating it on the DEM sampling
+    pub(crate) fn open_surface_from_samples(
+        &mut self,
+        x: &[f32],
+        z: &[f32],
+        name: String,
+        method: SurfaceInterpolation,
+    ) -> Result<()> {
+        let mut surface = Surface1D::from_samples(x, z, &self.dem.dem, method)?;
+        let profile = DispProfile::from_surface_direct(&mut surface, &self.dem.dem)?;
+        let mut bundle = BundleSurface::default();
+        bundle.name = name;
+        bundle.surface = surface;
+        bundle.profile = profile;
+        self.surfaces.push(bundle);
+        Ok(())
+    }
+
+    /// Open a surface from its depths below the DEM, interpolated on the DEM sampling
+    pub(crate) fn open_surface_from_depths(
+        &mut self,
+        x: &[f32],
+        depths: &[f32],
+        name: String,
+        method: SurfaceInterpolation,
+        mode: DepthMode,
+    ) -> Result<()> {
+        let mut surface = Surface1D::from_depth_samples(x, depths, &self.dem.dem, method, mode)?;
+        let profile = DispProfile::from_surface_direct(&mut surface, &self.dem.dem)?;
+        let mut bundle = BundleSurface::default();
+        bundle.name = name;
+        bundle.surface = surface;
+        bundle.profile = profile;
+        self.surfaces.push(bundle);
+        Ok(())
+    }
+
     pub(crate) fn surface_from_exact_slbl(
         &mut self,
         first_pnt: usize,
@@ -194,21 +359,122 @@ impl Project {
         let mut new_bundle = BundleDispData::default();
         let reader = CsvReader::read(file_path, None)?;
         new_bundle.name = name.to_owned();
-        new_bundle.disp_data =
-            DispData::from_csv_reader(&reader, &mut String::new(), &mut String::new())?;
+        new_bundle.disp_data = DispData::from_csv_reader(
+            &reader,
+            &mut String::new(),
+            &mut String::new(),
+            &mut String::new(),
+        )?;
         self.sars[sar_index].disp_data.push(new_bundle);
         Ok(())
     }
 
-    pub(crate) fn calibrate_model(
+    pub(crate) fn new_sar_series(
+        &mut self,
+        name: &String,
+        sar_index: usize,
+        file_path: String,
+    ) -> Result<()> {
+        let mut new_bundle = BundleDispSeries::default();
+        let reader = CsvReader::read(file_path, None)?;
+        new_bundle.name = name.to_owned();
+        new_bundle.series =
+            DispSeries::from_csv_reader(&reader, &mut String::new(), &mut String::new())?;
+        self.sars[sar_index].disp_series.push(new_bundle);
+        Ok(())
+    }
+
+    /// Gather displacement data into bins along the section, as a new data set
+    ///
+    /// The binned data are weighted by the standard error of the median of each bin.
+    pub(crate) fn bin_disp_data(
         &mut self,
-        model_index: usize,
         sar_index: usize,
         sar_data_index: usize,
+        width: f32,
+        min_count: usize,
     ) -> Result<()> {
+        let bundle = &self.sars[sar_index].disp_data[sar_data_index];
+        let bins = bundle.disp_data.bin(width, min_count)?;
+        let mut new_bundle = BundleDispData::default();
+        new_bundle.name = format!("{}_binned_{}", bundle.name, width);
+        new_bundle.disp_data = bins.to_disp_data()?;
+        self.sars[sar_index].disp_data.push(new_bundle);
+        Ok(())
+    }
+
+    /// Decompose two displacement data sets seen from different geometries
+    ///
+    /// Each data set is given as its sar geometry index and its data index.
+    pub(crate) fn decompose_los(
+        &mut self,
+        name: String,
+        first: (usize, usize),
+        second: (usize, usize),
+        basis: DecompositionBasis,
+        min_determinant: f32,
+    ) -> Result<()> {
+        let Some(section_geometry) = &self.dem.section_geometry else {
+            return Err(anyhow!("No section geometry defined"));
+        };
+        let first_sar = &self.sars[first.0];
+        let second_sar = &self.sars[second.0];
+        let decomposition = LosDecomposition::new(
+            &self.dem.dem,
+            section_geometry,
+            (
+                &first_sar.sar_geometry,
+                &first_sar.disp_data[first.1].disp_data,
+            ),
+            (
+                &second_sar.sar_geometry,
+                &second_sar.disp_data[second.1].disp_data,
+            ),
+            basis,
+            min_determinant,
+        )?;
+        let mut bundle = BundleDecomposition::default();
+        bundle.name = name;
+        bundle.decomposition = decomposition;
+        self.decompositions.push(bundle);
+        Ok(())
+    }
+
+    pub(crate) fn project_disp_data(
+        &mut self,
+        sar_index: usize,
+        sar_data_index: usize,
+        surface_index: Option<usize>,
+        min_sensitivity: f32,
+    ) -> Result<()> {
+        let Some(section_geometry) = &self.dem.section_geometry else {
+            return Err(anyhow!("No section geometry defined"));
+        };
+        let direction = surface_index.map(|k| &self.surfaces[k].surface);
+        let sar_geom = &mut self.sars[sar_index];
+        let bundle = &mut sar_geom.disp_data[sar_data_index];
+        bundle.disp_data.project_on_section(
+            &self.dem.dem,
+            direction,
+            section_geometry,
+            &sar_geom.sar_geometry,
+            min_sensitivity,
+        )?;
+        bundle.section_arrow = true;
+        Ok(())
+    }
+
+    pub(crate) fn calibrate_model(
+        &mut self,
+        model_index: usize,
+        datasets: &[(usize, usize)],
+        settings: &CalibrationSettings,
+    ) -> Result<()> {
+        if datasets.is_empty() {
+            return Err(anyhow!("No displacement data selected"));
+        }
         let model = &self.models[model_index];
-        let sar_geom = &self.sars[sar_index];
-        let sar_data = &sar_geom.disp_data[sar_data_index];
+        let los_datasets = self.los_datasets(datasets);
         let mut bundle = model.clone();
         let boundaries = model.boundaries.iter().map(|(a, b)| [*a, *b]).collect();
         let result = DispProfile::from_solver(
@@ -216,14 +482,28 @@ impl Project {
             &model.surfaces,
             &boundaries,
             &model.gradients,
-            &sar_data.disp_data,
+            &los_datasets,
             &self.dem.section_geometry.clone().unwrap(),
-            &sar_geom.sar_geometry,
+            settings,
         );
 
         match result {
-            Ok((profile, weights)) => {
-                bundle.weights = weights;
+            Ok((profile, calibration)) => {
+                bundle.weights = calibration.weights;
+                bundle.gradients = calibration.gradients;
+                bundle.uncertainty = calibration.uncertainty;
+                bundle.settings = Some(settings.clone());
+                bundle.fits = datasets
+                    .iter()
+                    .enumerate()
+                    .map(|(k, (sar, data))| DatasetFit {
+                        sar: *sar,
+                        data: *data,
+                        rmse: calibration.misfits[k],
+                        offset: calibration.offsets[k],
+                        ramp: calibration.ramps[k],
+                    })
+                    .collect();
                 bundle.resulting_profile = profile;
                 bundle.name = "Calibrated_".to_string() + bundle.name.as_str();
                 self.models.push(bundle);
@@ -232,12 +512,83 @@ impl Project {
             Err(e) => Err(anyhow!(e)),
         }
     }
+
+    /// Calibrate the weights of a model at each epoch of a time series
+    ///
+    /// The weights over time are kept with the model, its profile is left unchanged.
+    pub(crate) fn calibrate_series(
+        &mut self,
+        model_index: usize,
+        sar_index: usize,
+        series_index: usize,
+        settings: &CalibrationSettings,
+    ) -> Result<()> {
+        let Some(section_geometry) = &self.dem.section_geometry else {
+            return Err(anyhow!("No section geometry defined"));
+        };
+        let model = &self.models[model_index];
+        let sar = &self.sars[sar_index];
+        let boundaries = model.boundaries.iter().map(|(a, b)| [*a, *b]).collect();
+        let weights = calibrate_series(
+            &self.dem.dem,
+            &model.surfaces,
+            &boundaries,
+            &model.gradients,
+            &sar.sar_geometry,
+            &sar.disp_series[series_index].series,
+            section_geometry,
+            settings,
+        )?;
+        self.models[model_index].series_fit = Some(SeriesFit {
+            sar: sar_index,
+            series: series_index,
+            weights,
+        });
+        Ok(())
+    }
+
+    /// Rank the exact SLBL surfaces of the search by their misfit to the selected data
+    pub(crate) fn search_slbl(
+        &self,
+        datasets: &[(usize, usize)],
+        search: &SlblSearch,
+    ) -> Result<Vec<SlblCandidate>> {
+        if datasets.is_empty() {
+            return Err(anyhow!("No displacement data selected"));
+        }
+        let Some(section_geometry) = &self.dem.section_geometry else {
+            return Err(anyhow!("No section geometry defined"));
+        };
+        let candidates = search_slbl(
+            &self.dem.dem,
+            search,
+            &self.los_datasets(datasets),
+            section_geometry,
+            &CalibrationSettings::new(1),
+        )?;
+        Ok(candidates)
+    }
+
+    /// Acquisition geometry and data of each selected (sar, data) pair
+    fn los_datasets(&self, datasets: &[(usize, usize)]) -> Vec<(Orientation, DispData)> {
+        datasets
+            .iter()
+            .map(|(sar_index, sar_data_index)| {
+                let sar_geom = &self.sars[*sar_index];
+                (
+                    sar_geom.sar_geometry.clone(),
+                    sar_geom.disp_data[*sar_data_index].disp_data.clone(),
+                )
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct BundleDem {
     pub(crate) dem: Dem1D,
     pub(crate) section_geometry: Option<Orientation>,
+    pub(crate) section_line: Option<SectionLine>,
     pub(crate) section_surface: bool,
     pub(crate) min_bound: [f64; 2],
     pub(crate) max_bound: [f64; 2],
@@ -248,6 +599,7 @@ impl Default for BundleDem {
         BundleDem {
             dem: Dem1D::default(),
             section_geometry: None,
+            section_line: None,
             section_surface: true,
             min_bound: [0., 0.],
             max_bound: [0., 0.],
@@ -267,6 +619,10 @@ pub(crate) struct BundleSurface {
     pub(crate) section_pillar: bool,
     pub(crate) color_surface: Option<[u8; 3]>,
     pub(crate) color_arrow: Option<[u8; 3]>,
+
+    /// Geometry of the sliding mass, computed on first use
+    pub(crate) mass: Option<SlidingMass>,
+    pub(crate) lateral_extent: LateralExtent,
 }
 
 impl Default for BundleSurface {
@@ -281,10 +637,24 @@ impl Default for BundleSurface {
             section_pillar: false,
             color_surface: None,
             color_arrow: None,
+            mass: None,
+            lateral_extent: LateralExtent::default(),
         }
     }
 }
 
+impl BundleSurface {
+    /// Geometry of the sliding mass between the DEM and the surface
+    ///
+    /// None if the surface is not sampled on the DEM
+    pub(crate) fn mass(&mut self, dem: &Dem1D) -> Option<&SlidingMass> {
+        if self.mass.is_none() {
+            self.mass = SlidingMass::new(&self.surface, dem).ok();
+        }
+        self.mass.as_ref()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct BundleModel {
     pub(crate) name: String,
@@ -293,6 +663,10 @@ pub(crate) struct BundleModel {
     pub(crate) weights: Vec<f32>,
     pub(crate) boundaries: Vec<(usize, usize)>,
     pub(crate) gradients: Vec<Vec<(usize, f32)>>,
+    pub(crate) uncertainty: Option<WeightUncertainty>,
+    pub(crate) fits: Vec<DatasetFit>,
+    pub(crate) settings: Option<CalibrationSettings>,
+    pub(crate) series_fit: Option<SeriesFit>,
 
     pub(crate) resulting_profile: DispProfile,
 
@@ -309,6 +683,10 @@ impl Default for BundleModel {
             weights: vec![],
             boundaries: vec![],
             gradients: vec![],
+            uncertainty: None,
+            fits: vec![],
+            settings: None,
+            series_fit: None,
             resulting_profile: DispProfile::default(),
             section_arrow: true,
             arrow_scaling_factor: 10.0,
@@ -317,19 +695,211 @@ impl Default for BundleModel {
     }
 }
 
+/// Weights of a model calibrated at each epoch of a displacement time series
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct SeriesFit {
+    pub(crate) sar: usize,
+    pub(crate) series: usize,
+    pub(crate) weights: WeightSeries,
+}
+
+/// Misfit of a calibrated model against one of the data sets used for calibration
+///
+/// The offset and the ramp are the nuisance terms fitted to the data set, null if not fitted.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct DatasetFit {
+    pub(crate) sar: usize,
+    pub(crate) data: usize,
+    pub(crate) rmse: f32,
+    pub(crate) offset: f32,
+    pub(crate) ramp: f32,
+}
+
+impl DatasetFit {
+    /// Nuisance terms of the data set at positions `x` along the section
+    pub(crate) fn nuisance(&self, x: &[f32]) -> Vec<f32> {
+        x.iter().map(|x| self.offset + self.ramp * x).collect()
+    }
+}
+
+/// Read the sampling and the values under `header` of a DEM or surface csv file, to check
+/// them before opening it
+pub(crate) fn read_profile_file(path: String, header: &str) -> Result<(Vec<f32>, Vec<f32>)> {
+    // Missing values are reported and repaired when sampling the profile
+    let reader = CsvReader::read_with_missing(path, None)?;
+    Ok((
+        reader.get_data(&String::from("x"))?,
+        reader.get_data(&header.to_string())?,
+    ))
+}
+
+/// Short description of an acquisition geometry in the convention of the project
+pub(crate) fn geometry_label(convention: &GeometryConvention, orientation: &Orientation) -> String {
+    let (azimuth, incidence) = convention.angles(orientation);
+    let azimuth_name = match convention.azimuth {
+        AzimuthConvention::LookDirection => "az",
+        AzimuthConvention::TowardSatellite => "az to sat",
+        AzimuthConvention::Heading(LookSide::Right) => "heading (R)",
+        AzimuthConvention::Heading(LookSide::Left) => "heading (L)",
+    };
+    let symbol = convention.unit.symbol();
+    format!(
+        "{}: {}{} ; i: {}{}",
+        azimuth_name, azimuth, symbol, incidence, symbol
+    )
+}
+
 #[derive(Debug, Default)]
 pub(crate) struct BundleSar {
     pub(crate) name: String,
     pub(crate) sar_geometry: Orientation,
     pub(crate) disp_data: Vec<BundleDispData>,
+    pub(crate) disp_series: Vec<BundleDispSeries>,
 }
 
 #[derive(Debug, Default)]
+pub(crate) struct BundleDispSeries {
+    pub(crate) name: String,
+    pub(crate) series: DispSeries,
+}
+
+#[derive(Debug)]
 pub(crate) struct BundleDispData {
     pub(crate) name: String,
     pub(crate) disp_data: DispData,
-    #[allow(dead_code)] //
     pub(crate) section_arrow: bool,
+    pub(crate) arrow_scaling_factor: f32,
+    pub(crate) color_arrow: Option<[u8; 3]>,
     #[allow(dead_code)] //
     pub(crate) property_disp: bool,
 }
+
+impl Default for BundleDispData {
+    fn default() -> Self {
+        BundleDispData {
+            name: String::new(),
+            disp_data: DispData::default(),
+            section_arrow: false,
+            arrow_scaling_factor: 10.0,
+            color_arrow: None,
+            property_disp: false,
+        }
+    }
+}
+
+/// Displacement decomposed from two lines of sight
+#[derive(Debug)]
+pub(crate) struct BundleDecomposition {
+    pub(crate) name: String,
+    pub(crate) decomposition: LosDecomposition,
+    pub(crate) section_arrow: bool,
+    pub(crate) arrow_scaling_factor: f32,
+    pub(crate) color_arrow: Option<[u8; 3]>,
+}
+
+impl Default for BundleDecomposition {
+    fn default() -> Self {
+        BundleDecomposition {
+            name: String::new(),
+            decomposition: LosDecomposition::default(),
+            section_arrow: true,
+            arrow_scaling_factor: 10.0,
+            color_arrow: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample_dem() {
+        let mut project = Project::default();
+        let x: Vec<f32> = (0..21).map(|k| 10. * k as f32).collect();
+        let z: Vec<f32> = x.iter().map(|x| 200. - 0.5 * x).collect();
+        project.open_dem(&x, &z, &DemRepair::default()).unwrap();
+        let depths: Vec<f32> = x.iter().map(|x| x * (200. - x) / 2000.).collect();
+        project
+            .open_surface_from_depths(
+                &x,
+                &depths,
+                String::from("surface"),
+                SurfaceInterpolation::Linear,
+                DepthMode::Vertical,
+            )
+            .unwrap();
+        project
+            .combine_unit_models(
+                &String::from("model"),
+                &vec![SurfaceParams {
+                    index: 0,
+                    boundaries: (2, 18),
+                    gradient_points: vec![(2, 1.), (10, 2.), (18, 1.)],
+                    weight: 2.,
+                }],
+            )
+            .unwrap();
+
+        // Results computed against data on the former sampling
+        let mut disp_data = BundleDispData::default();
+        disp_data.disp_data = DispData::new(vec![50., 100.], vec![-1., -2.]).unwrap();
+        disp_data.disp_data.projected_vecs = vec![Vector2Rep::new(1., 1.); 2];
+        disp_data.disp_data.ill_conditioned = vec![false; 2];
+        let mut sar = BundleSar::default();
+        sar.disp_data.push(disp_data);
+        project.sars.push(sar);
+        project.decompositions.push(BundleDecomposition::default());
+        let model = &mut project.models[0];
+        model.settings = Some(CalibrationSettings::new(1));
+        model.uncertainty = Some(WeightUncertainty::default());
+        model.fits.push(DatasetFit::default());
+
+        project.resample_dem(5., None).unwrap();
+        assert_eq!(project.dem.dem.x.len(), 41);
+        assert_eq!(project.surfaces[0].surface.z.len(), 41);
+        assert_eq!(project.surfaces[0].profile.vecs.len(), 41);
+
+        let model = &project.models[0];
+        assert_eq!(model.surfaces[0].z.len(), 41);
+        assert_eq!(model.boundaries, vec![(4, 36)]);
+        assert_eq!(model.gradients[0], vec![(4, 1.), (20, 2.), (36, 1.)]);
+        assert_eq!(model.weights, vec![2.]);
+        assert_eq!(model.resulting_profile.vecs.len(), 41);
+        assert!(model.settings.is_none() && model.uncertainty.is_none());
+        assert!(model.fits.is_empty());
+
+        let disp_data = &project.sars[0].disp_data[0].disp_data;
+        assert!(disp_data.projected_vecs.is_empty() && disp_data.ill_conditioned.is_empty());
+        assert_eq!(disp_data.x, vec![50., 100.]);
+        assert!(project.decompositions.is_empty());
+
+        // The boundaries would be merged
+        assert!(project.resample_dem(100., None).is_err());
+    }
+
+    #[test]
+    fn test_mass_on_new_dem() {
+        let mut project = Project::default();
+        let x: Vec<f32> = (0..11).map(|k| 10. * k as f32).collect();
+        project
+            .open_dem(&x, &vec![100.; 11], &DemRepair::default())
+            .unwrap();
+        let mut bundle = BundleSurface::default();
+        bundle.surface = Surface1D::new(vec![
+            100., 95., 90., 90., 90., 90., 90., 90., 90., 95., 100.,
+        ]);
+        project.surfaces.push(bundle);
+        let dem = project.dem.dem.clone();
+        assert_eq!(project.surfaces[0].mass(&dem).unwrap().max_depth, 10.);
+
+        // The surface is no more sampled on the DEM
+        let x: Vec<f32> = (0..21).map(|k| 5. * k as f32).collect();
+        project
+            .open_dem(&x, &vec![100.; 21], &DemRepair::default())
+            .unwrap();
+        assert!(project.surfaces[0].mass.is_none());
+        let dem = project.dem.dem.clone();
+        assert!(project.surfaces[0].mass(&dem).is_none());
+    }
+}